@@ -0,0 +1,12 @@
+#!/usr/bin/env bash
+
+# Install the app so file managers can open .chopchop projects with it
+cargo install --path .
+
+data_dir=$HOME/.local/share
+mkdir -p "$data_dir/applications" "$data_dir/mime/packages" "$data_dir/icons/hicolor/scalable/apps"
+cp data/com.ohmm-software.Chop-Chop.desktop "$data_dir/applications/"
+cp data/com.ohmm-software.Chop-Chop.xml "$data_dir/mime/packages/"
+cp data/icons/tree-circle-symbolic.svg "$data_dir/icons/hicolor/scalable/apps/com.ohmm-software.Chop-Chop.svg"
+update-mime-database "$data_dir/mime"
+update-desktop-database "$data_dir/applications"
@@ -0,0 +1,7 @@
+#!/usr/bin/env bash
+
+locale_dir=$HOME/.local/share/locale
+for lang in $(cat po/LINGUAS); do
+    mkdir -p "$locale_dir/$lang/LC_MESSAGES"
+    msgfmt --check "po/$lang.po" --output-file="$locale_dir/$lang/LC_MESSAGES/chop-chop.mo"
+done
@@ -0,0 +1,310 @@
+use async_channel::Sender;
+use fraction::{Decimal, Fraction, Zero};
+
+use crate::layout::CutLayout;
+use crate::modeling::{Dimension, Material, Problem, SubProblem, Supply};
+use crate::solvers::{Message, Objective, Solver};
+use crate::utils::compute_total_price;
+
+/// Largest number of different purchased supplies tried together in one mix
+pub const MAX_MIX_SIZE: usize = 3;
+
+/// Largest number of purchased supplies compared for one material (up to 92 mixes)
+pub const MAX_CANDIDATES: usize = 8;
+
+/// The mixes of purchased supplies tried for one material
+#[derive(Debug, Clone)]
+pub struct StockAdvice {
+    pub material: Material,
+
+    /// Supplies of the material, in the same order as its `SubProblem`
+    pub supplies: Vec<Supply>,
+
+    /// Indices of the supplies used in every mix: those on hand and those of locked cut lists
+    pub kept: Vec<usize>,
+
+    /// Indices of the purchased supplies left out of every mix (beyond `MAX_CANDIDATES`)
+    pub left_out: Vec<usize>,
+
+    /// Mixes that could be solved, best first
+    pub mixes: Vec<StockMix>,
+}
+
+/// A choice of purchased supplies to buy, and the result of solving with only those
+///
+/// On-hand (free) supplies and those used by locked cut lists are used in every mix.
+#[derive(Debug, Clone)]
+pub struct StockMix {
+    /// Indices into `StockAdvice::supplies`
+    pub supply_indices: Vec<usize>,
+
+    /// Including sales tax and vendor fees
+    pub price: Decimal,
+
+    /// Total length of offcuts in meters
+    pub waste: Fraction,
+}
+
+/// Solves each 1D material with every mix of up to `MAX_MIX_SIZE` of its purchased supplies
+///
+/// Candidate stock lengths are entered as ordinary supplies (e.g., 8, 10, 12, and 16 ft boards at
+/// their prices). Only the `MAX_CANDIDATES` with the lowest price per length are compared, and
+/// supplies used by locked cut lists are kept in every mix instead. Mixes are solved as the project
+/// would be, including `Solver::drop_costly_vendors`. Mixes that fail to solve are left out, and
+/// the rest are ranked with `rank_mixes`. Progress is reported across all mixes of
+/// all materials, and the advice is sent when done.
+pub fn advise_stock(
+    solver: &dyn Solver,
+    problem: Problem,
+    objective: Objective,
+    sender: &Option<Sender<Message>>,
+) -> Vec<StockAdvice> {
+    let mut materials: Vec<_> = problem
+        .keys()
+        .filter(|m| m.dimension == Dimension::OneD)
+        .cloned()
+        .collect();
+    materials.sort_by(|a, b| a.name.cmp(&b.name));
+
+    // Purchased supplies are the candidates (the others are always available)
+    let candidates: Vec<(Vec<usize>, Vec<usize>)> = materials
+        .iter()
+        .map(|material| split_candidates(&problem[material]))
+        .collect();
+    let mixes: Vec<Vec<Vec<usize>>> = candidates
+        .iter()
+        .map(|(c, _)| combinations(c, MAX_MIX_SIZE))
+        .collect();
+    let total_count: usize = mixes.iter().map(|m| m.len()).sum();
+
+    let mut advice = Vec::new();
+    let mut count = 0;
+    for ((material, (candidates, left_out)), mixes) in
+        materials.into_iter().zip(candidates).zip(mixes)
+    {
+        let sub_problem = problem[&material].clone();
+        let kept = (0..sub_problem.supplies.len())
+            .filter(|i| !candidates.contains(i) && !left_out.contains(i))
+            .collect();
+        let mut results = Vec::new();
+        for supply_indices in mixes {
+            let mut mix_problem = sub_problem.clone();
+            for i in candidates.iter().chain(left_out.iter()) {
+                if !supply_indices.contains(i) {
+                    mix_problem.supplies[*i].max_quantity = 0;
+                }
+            }
+            let mut single = Problem::new();
+            single.insert(material.clone(), mix_problem);
+            if let Ok(solution) = solver
+                .solve_materials(single.clone(), sender)
+                .map(|solution| solver.drop_costly_vendors(single, solution, sender))
+            {
+                let sub_solution = &solution[&material];
+                let waste = sub_solution
+                    .cut_lists
+                    .iter()
+                    .map(|c| CutLayout::from(c, sub_solution).offcut() * Fraction::from(c.quantity))
+                    .fold(Fraction::zero(), |a, b| a + b);
+                results.push(StockMix {
+                    supply_indices,
+                    price: compute_total_price(&solution),
+                    waste,
+                });
+            }
+            count += 1;
+            solver.send_progress(sender, count as f64 / total_count as f64);
+        }
+        rank_mixes(&mut results, objective);
+        advice.push(StockAdvice {
+            material,
+            supplies: sub_problem.supplies,
+            kept,
+            left_out,
+            mixes: results,
+        });
+    }
+    solver.send_message(sender, Message::Advice(advice.clone()));
+    advice
+}
+
+/// Sorts mixes best first, breaking ties with the other objective and then the number of supplies
+pub fn rank_mixes(mixes: &mut [StockMix], objective: Objective) {
+    mixes.sort_by(|a, b| {
+        let order = match objective {
+            Objective::Cost => (a.price, a.waste).cmp(&(b.price, b.waste)),
+            Objective::Waste => (a.waste, a.price).cmp(&(b.waste, b.price)),
+        };
+        order.then(a.supply_indices.len().cmp(&b.supply_indices.len()))
+    });
+}
+
+/// The purchased supplies to compare, and those left out beyond `MAX_CANDIDATES`
+///
+/// Supplies used by locked cut lists aren't candidates, since every mix needs them.
+fn split_candidates(sub_problem: &SubProblem) -> (Vec<usize>, Vec<usize>) {
+    let supplies = &sub_problem.supplies;
+    let mut candidates: Vec<usize> = (0..supplies.len())
+        .filter(|i| {
+            supplies[*i].cost(1) > Decimal::zero()
+                && !sub_problem
+                    .locked_cut_lists
+                    .iter()
+                    .any(|c| c.supply_index == *i)
+        })
+        .collect();
+
+    // Cheapest per length first, which the best mixes are most likely to use
+    let rate = |i: &usize| {
+        let price: f64 = supplies[*i].unit_price().try_into().unwrap();
+        price / supplies[*i].length.to_meters_f64()
+    };
+    candidates.sort_by(|a, b| rate(a).total_cmp(&rate(b)));
+    let mut left_out = candidates.split_off(candidates.len().min(MAX_CANDIDATES));
+    candidates.sort();
+    left_out.sort();
+    (candidates, left_out)
+}
+
+/// All non-empty combinations of up to `max_size` items, in lexicographic order
+fn combinations(items: &[usize], max_size: usize) -> Vec<Vec<usize>> {
+    let mut result = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        result.push(vec![*item]);
+        if max_size > 1 {
+            for mut rest in combinations(&items[i + 1..], max_size - 1) {
+                rest.insert(0, *item);
+                result.push(rest);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{CutList, Part, Vendor};
+    use crate::size::Size;
+    use crate::solvers::naive_solver::NaiveSolver;
+
+    fn supply(length: f64, price: f64, max_quantity: i64) -> Supply {
+        Supply {
+            length: Size::from_meters(length),
+            price: Decimal::from(price),
+            max_quantity,
+            ..Default::default()
+        }
+    }
+
+    fn part(length: f64) -> Part {
+        Part {
+            length: Size::from_meters(length),
+            quantity: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_advise_stock() {
+        let material = Material::default();
+        let mut problem = Problem::new();
+        problem.insert(
+            material.clone(),
+            SubProblem {
+                supplies: vec![supply(2.0, 3.0, -1), supply(3.0, 4.0, -1)],
+                parts: vec![part(2.0), part(3.0)],
+                ..Default::default()
+            },
+        );
+
+        // Only the 3m boards fit both parts, but a 2m and a 3m board waste nothing
+        let advice = advise_stock(&NaiveSolver::default(), problem, Objective::Cost, &None);
+        let mixes = &advice[0].mixes;
+        assert_eq!(mixes.len(), 2);
+        assert_eq!(mixes[0].supply_indices, vec![0, 1]);
+        assert_eq!(mixes[0].price, Decimal::from(7));
+        assert_eq!(mixes[0].waste, Fraction::zero());
+        assert_eq!(mixes[1].supply_indices, vec![1]);
+        assert_eq!(mixes[1].price, Decimal::from(8));
+    }
+
+    #[test]
+    fn test_advise_stock_locked() {
+        let material = Material::default();
+        let mut problem = Problem::new();
+        problem.insert(
+            material.clone(),
+            SubProblem {
+                supplies: vec![supply(2.0, 3.0, -1), supply(3.0, 4.0, -1)],
+                parts: vec![part(2.0), part(3.0)],
+                locked_cut_lists: vec![CutList {
+                    supply_index: 0,
+                    part_indices: vec![0],
+                    quantity: 1,
+                    locked: false,
+                }],
+                ..Default::default()
+            },
+        );
+
+        // The 2m board of the locked cut list is bought in every mix
+        let advice = advise_stock(&NaiveSolver::default(), problem, Objective::Cost, &None);
+        assert_eq!(advice[0].kept, vec![0]);
+        let mixes = &advice[0].mixes;
+        assert_eq!(mixes.len(), 1);
+        assert_eq!(mixes[0].supply_indices, vec![1]);
+        assert_eq!(mixes[0].price, Decimal::from(7));
+    }
+
+    #[test]
+    fn test_advise_stock_vendor_fees() {
+        let material = Material::default();
+        let mut problem = Problem::new();
+        problem.insert(
+            material.clone(),
+            SubProblem {
+                supplies: vec![
+                    Supply {
+                        vendor: String::from("Lumber yard"),
+                        ..supply(2.0, 1.0, -1)
+                    },
+                    supply(2.0, 5.0, -1),
+                ],
+                parts: vec![part(2.0)],
+                vendors: vec![Vendor {
+                    name: String::from("Lumber yard"),
+                    delivery_fee: Decimal::from(20),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        );
+
+        // The cheaper board isn't worth the delivery fee, even when the mix offers it
+        let advice = advise_stock(&NaiveSolver::default(), problem, Objective::Cost, &None);
+        let mixes = &advice[0].mixes;
+        assert_eq!(mixes.len(), 3);
+        assert_eq!(mixes[0].supply_indices, vec![1]);
+        assert_eq!(mixes[0].price, Decimal::from(5));
+        assert_eq!(mixes[1].supply_indices, vec![0, 1]);
+        assert_eq!(mixes[1].price, Decimal::from(5));
+        assert_eq!(mixes[2].supply_indices, vec![0]);
+        assert_eq!(mixes[2].price, Decimal::from(21));
+    }
+
+    #[test]
+    fn test_split_candidates() {
+        // Every board costs $1 per meter except the 1m board at $2
+        let mut supplies: Vec<_> = (1..=10).map(|i| supply(i as f64, i as f64, -1)).collect();
+        supplies[0].price = Decimal::from(2);
+        supplies.push(supply(1.0, 0.0, 4));
+        let sub_problem = SubProblem {
+            supplies,
+            ..Default::default()
+        };
+        let (candidates, left_out) = split_candidates(&sub_problem);
+        assert_eq!(candidates, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(left_out, vec![0, 9]);
+    }
+}
@@ -0,0 +1,47 @@
+use gettextrs::{gettext, ngettext};
+
+/// Gettext domain, also the name of the installed `.mo` files
+pub const GETTEXT_PACKAGE: &str = "chop-chop";
+
+/// Translates `format` and fills its `{}` placeholders with `args`, in order
+///
+/// Use this instead of `format!`, which would need the translated string at compile time.
+pub fn i18n_f(format: &str, args: &[&str]) -> String {
+    fill(gettext(format), args)
+}
+
+/// Like `i18n_f`, but picks the singular or a plural form of the message for `n`
+pub fn ni18n_f(singular: &str, plural: &str, n: u32, args: &[&str]) -> String {
+    fill(ngettext(singular, plural, n), args)
+}
+
+/// Marks a constant for extraction without translating it (pass it to `gettext` where shown)
+pub const fn gettext_noop(message: &'static str) -> &'static str {
+    message
+}
+
+fn fill(format: String, args: &[&str]) -> String {
+    let mut parts = format.split("{}");
+    let mut result = parts.next().unwrap_or_default().to_string();
+    let mut args = args.iter();
+    for part in parts {
+        result.push_str(args.next().unwrap_or(&""));
+        result.push_str(part);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(fill(String::from("{} of {}"), &["1", "2"]), "1 of 2");
+        assert_eq!(fill(String::from("Repeats: {}"), &[]), "Repeats: ");
+        assert_eq!(
+            fill(String::from("No placeholders"), &["1"]),
+            "No placeholders"
+        );
+    }
+}
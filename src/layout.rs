@@ -0,0 +1,520 @@
+use std::collections::BTreeMap;
+
+use fraction::{Fraction, Zero};
+
+use crate::modeling::{CutList, EndAngles, Part, SubSolution};
+use crate::size::Size;
+
+/// The outline of a 1D part along its supply, in meters
+///
+/// Angled ends set the bottom edge in from the top edge (or out, for negative offsets), so the two
+/// edges can differ in length. `length` is measured along the longer edge.
+#[derive(Debug, Clone)]
+pub struct PartShape {
+    pub length: Fraction,
+
+    /// How far the bottom edge starts after the top edge
+    pub start_offset: Fraction,
+
+    /// How far the bottom edge ends before the top edge
+    pub end_offset: Fraction,
+}
+
+impl PartShape {
+    pub fn from(part: &Part, depth: &Size) -> Self {
+        let depth = depth.to_meters_f64();
+        let offset = |angle: Fraction| -> Fraction {
+            let angle: f64 = angle.try_into().unwrap();
+            round_meters(depth * angle.to_radians().tan())
+        };
+        Self {
+            length: part.length.to_meters(),
+            start_offset: offset(part.angles.start),
+            end_offset: offset(part.angles.end),
+        }
+    }
+
+    /// The shape after turning the part over, which swaps the top and bottom edges
+    pub fn flipped(&self) -> Self {
+        Self {
+            length: self.length,
+            start_offset: -self.start_offset,
+            end_offset: -self.end_offset,
+        }
+    }
+
+    /// Lengths of the top and bottom edges
+    fn edges(&self) -> (Fraction, Fraction) {
+        let difference = self.start_offset + self.end_offset;
+        if difference >= Fraction::zero() {
+            let bottom = self.length - difference;
+            (self.length, bottom.max(Fraction::zero()))
+        } else {
+            let top = self.length + difference;
+            (top.max(Fraction::zero()), self.length)
+        }
+    }
+
+    /// Average length of the two edges
+    ///
+    /// No arrangement of parts can use less of a supply than this (it is the area of the part
+    /// divided by the depth).
+    pub fn average_length(&self) -> Fraction {
+        let (top, bottom) = self.edges();
+        (top + bottom) / 2
+    }
+}
+
+/// Rounds a length in meters away from zero to the nearest micrometer
+///
+/// Angled offsets are irrational, and fractions with small denominators keep sums from overflowing.
+fn round_meters(meters: f64) -> Fraction {
+    let micrometers = Fraction::new((meters.abs() * 1e6).ceil() as u64, 1_000_000u64);
+    if meters < 0.0 {
+        -micrometers
+    } else {
+        micrometers
+    }
+}
+
+/// Where a part sits along its supply, measured in meters from the end of the supply
+///
+/// `start` and `end` are measured along the top edge; `shape` gives the bottom edge.
+#[derive(Debug, Clone)]
+pub struct Placement {
+    pub part_index: usize,
+    pub start: Fraction,
+    pub end: Fraction,
+
+    /// The shape of the part as placed (after flipping, if flipped)
+    pub shape: PartShape,
+
+    /// Whether the part is turned over so its bottom edge is on top
+    pub flipped: bool,
+}
+
+impl Placement {
+    /// The far end of whichever edge reaches further
+    pub fn extent(&self) -> Fraction {
+        self.end.max(self.end - self.shape.end_offset)
+    }
+
+    /// Compares how much room the placement leaves for later parts (smaller is better)
+    fn frontier(&self) -> (Fraction, Fraction) {
+        (self.extent(), self.end + self.end - self.shape.end_offset)
+    }
+}
+
+/// Places parts one after another from the end of a supply
+///
+/// Each part is turned over when that lets it nest more tightly against the previous part, so
+/// complementary angles share a single cut. Parts are separated by the blade width.
+#[derive(Debug, Clone)]
+pub struct Packer {
+    blade_width: Fraction,
+
+    /// Where the first part starts (after any end trim)
+    start: Fraction,
+
+    /// First free positions along the top and bottom edges (before the kerf)
+    top: Fraction,
+    bottom: Fraction,
+
+    is_empty: bool,
+}
+
+impl Packer {
+    pub fn new(blade_width: Fraction) -> Self {
+        Self::with_trim(blade_width, Fraction::zero())
+    }
+
+    /// Starts placing parts after `end_trim` has been cut off the supply
+    pub fn with_trim(blade_width: Fraction, end_trim: Fraction) -> Self {
+        Self {
+            blade_width,
+            start: end_trim,
+            top: end_trim,
+            bottom: end_trim,
+            is_empty: true,
+        }
+    }
+
+    /// Places a part after those already placed
+    pub fn place(&mut self, part_index: usize, shape: &PartShape) -> Placement {
+        let (top, bottom) = if self.is_empty {
+            (self.start, self.start)
+        } else {
+            (self.top + self.blade_width, self.bottom + self.blade_width)
+        };
+
+        // Try both ways up, turning the part over only if that reaches less far
+        let mut best: Option<Placement> = None;
+        for (shape, flipped) in [(shape.clone(), false), (shape.flipped(), true)] {
+            let (top_length, _) = shape.edges();
+            let start = top.max(bottom - shape.start_offset);
+            let placement = Placement {
+                part_index,
+                start,
+                end: start + top_length,
+                shape,
+                flipped,
+            };
+            if best
+                .as_ref()
+                .is_none_or(|b| placement.frontier() < b.frontier())
+            {
+                best = Some(placement);
+            }
+        }
+        let placement = best.unwrap();
+        self.top = placement.end;
+        self.bottom = placement.end - placement.shape.end_offset;
+        self.is_empty = false;
+        placement
+    }
+
+    /// Length of supply used so far, without a kerf after the last part
+    pub fn used(&self) -> Fraction {
+        self.top.max(self.bottom)
+    }
+}
+
+/// Length of supply needed to cut parts in order (see `Packer`)
+pub fn used_length<'a>(
+    shapes: impl IntoIterator<Item = &'a PartShape>,
+    blade_width: Fraction,
+) -> Fraction {
+    let mut packer = Packer::new(blade_width);
+    for shape in shapes {
+        packer.place(0, shape);
+    }
+    packer.used()
+}
+
+/// Material removed by a cut, in meters
+#[derive(Debug, Clone)]
+pub struct Kerf {
+    /// Index of the placement the cut ends (the kerf follows its end angle)
+    pub placement_index: usize,
+
+    /// Start and end along the top edge
+    pub start: Fraction,
+    pub end: Fraction,
+}
+
+/// The physical layout of a 1D cut list
+///
+/// Parts are measured off from the end of the supply in cut list order (see `Packer`), after the
+/// end trim. Each cut removes up to the blade width after the part; the last kerf may be narrower
+/// if it runs off the end of the supply.
+#[derive(Debug, Clone)]
+pub struct CutLayout {
+    /// Length of the supply in meters
+    pub length: Fraction,
+
+    /// Length cut off the end before the first part in meters
+    pub end_trim: Fraction,
+
+    pub placements: Vec<Placement>,
+
+    pub kerfs: Vec<Kerf>,
+
+    /// Start of the unused remainder in meters (equal to `length` if nothing remains)
+    pub offcut_start: Fraction,
+}
+
+impl CutLayout {
+    pub fn from(cut_list: &CutList, sub_solution: &SubSolution) -> Self {
+        let length = sub_solution.supplies[cut_list.supply_index]
+            .length
+            .to_meters();
+        let blade_width = sub_solution.blade_width.to_meters();
+        let end_trim = sub_solution.end_trim.to_meters();
+        let mut packer = Packer::with_trim(blade_width, end_trim);
+        let mut placements = Vec::new();
+        let mut kerfs = Vec::new();
+        let mut position = end_trim;
+        for part_index in cut_list.part_indices.iter() {
+            let shape = PartShape::from(&sub_solution.parts[*part_index], &sub_solution.depth);
+            let placement = packer.place(*part_index, &shape);
+            let (end, offset) = (placement.end, placement.shape.end_offset);
+
+            // No cut is needed if the part ends square, exactly at the end of the supply
+            position = placement.extent();
+            if end < length || end - offset < length {
+                let kerf_end = if end + blade_width < length {
+                    end + blade_width
+                } else {
+                    length
+                };
+                if kerf_end > end {
+                    kerfs.push(Kerf {
+                        placement_index: placements.len(),
+                        start: end,
+                        end: kerf_end,
+                    });
+                }
+                position = (kerf_end.max(kerf_end - offset)).min(length);
+            }
+            placements.push(placement);
+        }
+        Self {
+            length,
+            end_trim,
+            placements,
+            kerfs,
+            offcut_start: position,
+        }
+    }
+
+    /// Positions to mark along the top edge, measured from the end of the supply (one per cut)
+    ///
+    /// Marks are past the kerf (e.g., 36 1/8" after a 36" part with a 1/8" blade), so the blade
+    /// goes on the side of the mark nearer the measured end. Cuts too close to the end of the supply
+    /// for a whole kerf are marked at the end of the part instead.
+    pub fn marks(&self) -> Vec<Fraction> {
+        self.placements
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.end < self.length)
+            .map(|(i, p)| {
+                self.kerfs
+                    .iter()
+                    .find(|k| k.placement_index == i)
+                    .map(|k| k.end)
+                    .filter(|end| *end < self.length)
+                    .unwrap_or(p.end)
+            })
+            .collect()
+    }
+
+    /// Length of the unused remainder in meters
+    pub fn offcut(&self) -> Fraction {
+        self.length - self.offcut_start
+    }
+}
+
+/// A single piece cut while the stop block is at one setting
+#[derive(Debug, Clone)]
+pub struct Cut {
+    /// Numbered in cutting order across the whole sub-solution (starting at 1)
+    pub number: usize,
+
+    pub part_index: usize,
+    pub cut_list_index: usize,
+
+    /// Which repeat of the cut list the piece comes from (starting at 1)
+    pub repeat: usize,
+}
+
+/// Every cut made with the stop block at one distance from the blade
+#[derive(Debug, Clone)]
+pub struct CutStep {
+    /// Distance from the blade to the stop block in meters
+    pub length: Fraction,
+
+    /// Saw angles for the ends of every piece cut at this setting
+    pub angles: EndAngles,
+
+    pub cuts: Vec<Cut>,
+}
+
+/// Groups the cuts of a sub-solution by length and angles so each setting is only used once
+///
+/// Settings run from longest to shortest, so the stop block only moves toward the blade and every
+/// board is still long enough to reach it. Within a setting, cuts follow the cut lists in order.
+pub fn cut_sequence(sub_solution: &SubSolution) -> Vec<CutStep> {
+    let mut groups: BTreeMap<(Fraction, Fraction, Fraction), Vec<Cut>> = BTreeMap::new();
+    for (cut_list_index, cut_list) in sub_solution.cut_lists.iter().enumerate() {
+        for repeat in 1..=cut_list.quantity {
+            for part_index in cut_list.part_indices.iter() {
+                let part = &sub_solution.parts[*part_index];
+                let key = (part.length.to_meters(), part.angles.start, part.angles.end);
+                groups.entry(key).or_default().push(Cut {
+                    number: 0,
+                    part_index: *part_index,
+                    cut_list_index,
+                    repeat,
+                });
+            }
+        }
+    }
+
+    let mut number = 1;
+    let mut steps = Vec::new();
+    for ((length, start, end), mut cuts) in groups.into_iter().rev() {
+        for cut in cuts.iter_mut() {
+            cut.number = number;
+            number += 1;
+        }
+        steps.push(CutStep {
+            length,
+            angles: EndAngles { start, end },
+            cuts,
+        });
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use fraction::Decimal;
+
+    use super::*;
+    use crate::modeling::Supply;
+    use crate::size::{Size, SizeUnit};
+
+    fn inches(value: Fraction) -> Size {
+        Size {
+            unit: SizeUnit::Inches,
+            major: value,
+            minor: Fraction::zero(),
+        }
+    }
+
+    #[test]
+    fn test_cut_layout() {
+        let part = Part {
+            name: String::new(),
+            length: inches(Fraction::from(36)),
+            quantity: 2,
+            assembly: String::new(),
+            angles: EndAngles::default(),
+            splice: None,
+            segment: None,
+            fill: false,
+        };
+        let sub_solution = SubSolution {
+            supplies: vec![Supply {
+                name: String::new(),
+                length: inches(Fraction::from(96)),
+                price: Decimal::zero(),
+                max_quantity: -1,
+                vendor: String::new(),
+                ..Default::default()
+            }],
+            parts: vec![part],
+            cut_lists: vec![CutList {
+                supply_index: 0,
+                part_indices: vec![0, 0],
+                quantity: 1,
+                locked: false,
+            }],
+            blade_width: inches(Fraction::new(1u64, 8u64)),
+            depth: Size::default(),
+            end_trim: Size::default(),
+            vendors: Vec::new(),
+            lower_bound: None,
+        };
+        let layout = CutLayout::from(&sub_solution.cut_lists[0], &sub_solution);
+        let marks: Vec<_> = layout
+            .marks()
+            .into_iter()
+            .map(|m| Size::from_meters_in(m, &SizeUnit::Inches).major)
+            .collect();
+        // "Mark at 36 1/8, 72 1/4"
+        assert_eq!(
+            marks,
+            vec![Fraction::new(289u64, 8u64), Fraction::new(289u64, 4u64)]
+        );
+        let offcut = Size::from_meters_in(layout.offcut(), &SizeUnit::Inches).major;
+        assert_eq!(offcut, Fraction::new(95u64, 4u64));
+    }
+
+    #[test]
+    fn test_cut_sequence() {
+        let part = |name: &str, length: u64| Part {
+            name: String::from(name),
+            length: inches(Fraction::from(length)),
+            quantity: 1,
+            assembly: String::new(),
+            angles: EndAngles::default(),
+            splice: None,
+            segment: None,
+            fill: false,
+        };
+        let supply = Supply {
+            name: String::new(),
+            length: inches(Fraction::from(96)),
+            price: Decimal::zero(),
+            max_quantity: -1,
+            vendor: String::new(),
+            ..Default::default()
+        };
+        let sub_solution = SubSolution {
+            supplies: vec![supply],
+            parts: vec![part("Rail", 24), part("Leg", 30), part("Stile", 24)],
+            cut_lists: vec![
+                CutList {
+                    supply_index: 0,
+                    part_indices: vec![0, 1],
+                    quantity: 2,
+                    locked: false,
+                },
+                CutList {
+                    supply_index: 0,
+                    part_indices: vec![2, 2, 1],
+                    quantity: 1,
+                    locked: false,
+                },
+            ],
+            blade_width: inches(Fraction::new(1u64, 8u64)),
+            depth: Size::default(),
+            end_trim: Size::default(),
+            vendors: Vec::new(),
+            lower_bound: None,
+        };
+        let steps = cut_sequence(&sub_solution);
+
+        // One setting per length, longest first
+        let lengths: Vec<_> = steps
+            .iter()
+            .map(|s| Size::from_meters_in(s.length, &SizeUnit::Inches).major)
+            .collect();
+        assert_eq!(lengths, vec![Fraction::from(30), Fraction::from(24)]);
+
+        // Pieces are numbered consecutively in cutting order
+        let cuts: Vec<_> = steps
+            .iter()
+            .flat_map(|s| s.cuts.iter())
+            .map(|c| (c.number, c.part_index, c.cut_list_index, c.repeat))
+            .collect();
+        assert_eq!(
+            cuts,
+            vec![
+                (1, 1, 0, 1),
+                (2, 1, 0, 2),
+                (3, 1, 1, 1),
+                (4, 0, 0, 1),
+                (5, 0, 0, 2),
+                (6, 2, 1, 1),
+                (7, 2, 1, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mitered_parts() {
+        let part = Part {
+            name: String::new(),
+            length: Size::from_meters(Fraction::new(1u64, 2u64)),
+            quantity: 4,
+            assembly: String::new(),
+            angles: EndAngles {
+                start: Fraction::from(45),
+                end: Fraction::from(45),
+            },
+            splice: None,
+            segment: None,
+            fill: false,
+        };
+        let shape = PartShape::from(&part, &Size::from_meters(Fraction::new(1u64, 20u64)));
+        assert_eq!(shape.start_offset, Fraction::new(1u64, 20u64));
+
+        // Turning every other frame piece over lets neighbors share a cut
+        let mut packer = Packer::new(Fraction::zero());
+        let flipped: Vec<_> = (0..4).map(|_| packer.place(0, &shape).flipped).collect();
+        assert_eq!(flipped, vec![false, true, false, true]);
+        assert_eq!(packer.used(), Fraction::new(37u64, 20u64));
+    }
+}
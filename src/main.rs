@@ -1,3 +1,6 @@
+mod advisor;
+mod i18n;
+mod layout;
 mod modeling;
 mod size;
 mod solvers;
@@ -6,17 +9,27 @@ mod utils;
 
 use adw::Application;
 use adw::prelude::*;
+use gettextrs::LocaleCategory;
 use gtk::{CssProvider, gdk::Display, gio, glib};
+use i18n::GETTEXT_PACKAGE;
 use ui::window::Window;
 
 const APP_ID: &str = "com.ohmm-software.Chop-Chop";
 
+/// Extension of project files (older projects saved as .json also open)
+const PROJECT_EXTENSION: &str = "chopchop";
+
 fn main() -> glib::ExitCode {
+    setup_translations();
+
     // Register and include resources
     gio::resources_register_include!("gresource").expect("Failed to register resources.");
 
     // Create a new application
-    let app = Application::builder().application_id(APP_ID).build();
+    let app = Application::builder()
+        .application_id(APP_ID)
+        .flags(gio::ApplicationFlags::HANDLES_OPEN)
+        .build();
 
     // Connect to the "startup" and "activate" signals
     app.connect_startup(|_| {
@@ -26,6 +39,15 @@ fn main() -> glib::ExitCode {
         new_window(app, true);
     });
 
+    // Files passed on the command line (or from a file manager) each get their own window
+    app.connect_open(|app, files, _| {
+        for file_path in files.iter().filter_map(|f| f.path()) {
+            let window = Window::new(app, false);
+            window.open_file(&file_path);
+            window.present();
+        }
+    });
+
     // Set up application-global actions and keybindings
     setup_actions(&app);
     setup_accels(&app);
@@ -55,7 +77,12 @@ fn load_css() {
 
 fn setup_actions(app: &Application) {
     let new_action = gio::ActionEntry::builder("new")
-        .activate(|app: &Application, _, _| new_window(app, false))
+        .activate(|app: &Application, _, _| {
+            // Only a window opened at launch starts with the start page
+            let window = Window::new(app, false);
+            window.show_project();
+            window.present();
+        })
         .build();
     let quit_action = gio::ActionEntry::builder("quit")
         .activate(|app: &Application, _, _| {
@@ -68,6 +95,16 @@ fn setup_actions(app: &Application) {
     app.add_action_entries([new_action, quit_action]);
 }
 
+/// Loads the translations installed by `install_translations.sh`
+fn setup_translations() {
+    let locale_dir = glib::user_data_dir().join("locale");
+    gettextrs::setlocale(LocaleCategory::LcAll, "");
+    gettextrs::bindtextdomain(GETTEXT_PACKAGE, locale_dir).expect("Failed to bind text domain.");
+    gettextrs::bind_textdomain_codeset(GETTEXT_PACKAGE, "UTF-8")
+        .expect("Failed to set text domain encoding.");
+    gettextrs::textdomain(GETTEXT_PACKAGE).expect("Failed to set text domain.");
+}
+
 fn setup_accels(app: &Application) {
     // Use gtk::accelerator_name to find key names
     app.set_accels_for_action("app.new", &["<Ctrl>N"]);
@@ -76,6 +113,8 @@ fn setup_accels(app: &Application) {
     app.set_accels_for_action("win.save", &["<Ctrl>S"]);
     app.set_accels_for_action("win.save-as", &["<Shift><Ctrl>S"]);
     app.set_accels_for_action("win.print", &["<Ctrl>P"]);
+    app.set_accels_for_action("win.print-labels", &["<Shift><Ctrl>P"]);
     app.set_accels_for_action("win.preferences", &["<Ctrl>comma"]);
+    app.set_accels_for_action("win.library", &["<Ctrl>L"]);
     app.set_accels_for_action("win.close", &["<Ctrl>W"]);
 }
@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 
-use fraction::Decimal;
+use fraction::{Decimal, Fraction, Zero};
 use serde::{Deserialize, Serialize};
 
+use crate::i18n::i18n_f;
 use crate::size::Size;
 
 /// The dimensionality of a material (1D or 2D)
@@ -33,8 +34,9 @@ pub struct Material {
 ///
 /// Examples:
 /// - Lengths of 8ft available for purchase at $3.50 each, no limit
+/// - Lengths of 8ft at $3.50 each or $30 for a bundle of 10
 /// - Lengths of 3ft available on hand (free), limit 5
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Supply {
     pub name: String,
     pub length: Size,
@@ -44,6 +46,132 @@ pub struct Supply {
 
     /// -1 indicates that the supply is unlimited
     pub max_quantity: i64,
+
+    /// Name of the `Vendor` selling the supply (empty if unspecified or on-hand)
+    #[serde(default)]
+    pub vendor: String,
+
+    /// Items at the unit price are only sold in multiples of this (0 or 1 if sold individually)
+    #[serde(default)]
+    pub pack_size: i64,
+
+    /// Quantities sold together for less than the unit price, used wherever they lower the cost
+    #[serde(default)]
+    pub bundles: Vec<Bundle>,
+}
+
+impl Supply {
+    /// Lowest cost of buying at least `count` items, using packs and bundles as needed
+    pub fn cost(&self, count: usize) -> Decimal {
+        self.purchase(count).0
+    }
+
+    /// Number of items bought (at the lowest cost) to end up with at least `count`
+    pub fn purchase_quantity(&self, count: usize) -> usize {
+        self.purchase(count).1
+    }
+
+    /// Number of items short of `count` once `max_quantity` is bought (e.g., 1 short of 5 when
+    /// packs of 4 are limited to 5 items)
+    pub fn shortfall(&self, count: usize) -> usize {
+        count.saturating_sub(self.purchase_quantity(count))
+    }
+
+    /// Lowest price per item (e.g., $3 each when a bundle of 10 is $30)
+    pub fn unit_price(&self) -> Decimal {
+        self.bundles
+            .iter()
+            .filter(|b| b.quantity > 0)
+            .map(|b| b.price / Decimal::from(b.quantity))
+            .fold(self.price, |a, b| a.min(b))
+    }
+
+    /// Cheapest combination of packs and bundles covering `count` items, found by dynamic
+    /// programming over the number of items bought
+    ///
+    /// No more than `max_quantity` items are bought (unless it is -1), so where packs and bundles
+    /// can't cover `count` within it, the most items that can be bought are (see `shortfall`).
+    fn purchase(&self, count: usize) -> (Decimal, usize) {
+        if count == 0 {
+            return (Decimal::zero(), 0);
+        }
+        let pack_size = self.pack_size.max(1) as usize;
+        let mut options = vec![(pack_size, self.price * Decimal::from(pack_size))];
+        for bundle in self.bundles.iter().filter(|b| b.quantity > 0) {
+            options.push((bundle.quantity as usize, bundle.price));
+        }
+
+        // The cheapest purchase never has a whole pack or bundle to spare
+        let largest = options.iter().map(|(quantity, _)| *quantity).max().unwrap();
+        let mut limit = count + largest - 1;
+        if self.max_quantity != -1 {
+            limit = limit.min(self.max_quantity.max(0) as usize);
+        }
+
+        // Lowest cost of buying exactly each number of items, if packs and bundles add up to it
+        let mut exact: Vec<Option<Decimal>> = vec![Some(Decimal::zero())];
+        for n in 1..=limit {
+            let cost = options
+                .iter()
+                .filter(|(quantity, _)| *quantity <= n)
+                .filter_map(|(quantity, price)| exact[n - quantity].map(|rest| rest + *price))
+                .min();
+            exact.push(cost);
+        }
+        (count..=limit)
+            .filter_map(|n| exact[n].map(|cost| (cost, n)))
+            .min()
+            .or_else(|| {
+                (0..=limit)
+                    .rev()
+                    .find_map(|n| exact[n].map(|cost| (cost, n)))
+            })
+            .unwrap()
+    }
+}
+
+/// A quantity of a supply sold together for one price
+///
+/// Examples:
+/// - A bundle of 10 for $30
+/// - A box of 4 sheets for $100
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Bundle {
+    pub quantity: i64,
+    pub price: Decimal,
+}
+
+/// A store that supplies are bought from, with the charges it adds to an order
+///
+/// Examples:
+/// - Lumber yard, 8% sales tax, $50 delivery
+/// - Hardware store, 8% sales tax, $5 fee on orders under $25
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Vendor {
+    pub name: String,
+
+    /// Fraction of the subtotal added as sales tax (e.g., 0.08 for 8%)
+    pub tax_rate: Decimal,
+
+    /// Charged once if anything is bought from the vendor
+    pub delivery_fee: Decimal,
+
+    /// Orders with a subtotal below the minimum are charged `small_order_fee`
+    pub minimum_order: Decimal,
+    pub small_order_fee: Decimal,
+}
+
+impl Vendor {
+    /// Fixed charges for an order with the given subtotal (nothing if the order is empty)
+    pub fn fees(&self, subtotal: Decimal) -> Decimal {
+        if subtotal <= Decimal::zero() {
+            Decimal::zero()
+        } else if subtotal < self.minimum_order {
+            self.delivery_fee + self.small_order_fee
+        } else {
+            self.delivery_fee
+        }
+    }
 }
 
 /// A desired part to obtain from a material
@@ -53,13 +181,105 @@ pub struct Supply {
 /// Examples:
 /// - Need ten 8ft lengths
 /// - Need two 3ft lengths
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// - As many 6in shims as fit in the offcuts, at least four
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Part {
     pub name: String,
     pub length: Size,
 
     /// i64 used for consistency with `Supply`, where -1 is used to indicate unlimited
+    ///
+    /// For fill parts this is the minimum to make.
     pub quantity: i64,
+
+    /// Whether to make as many more as fit in the offcuts of supplies already being used
+    #[serde(default)]
+    pub fill: bool,
+
+    /// The assembly instance the part belongs to (e.g., "Cabinet 1 / Drawer 2")
+    ///
+    /// Empty if the part is not in an assembly.
+    #[serde(default)]
+    pub assembly: String,
+
+    /// Cut angles at the ends of the part (only used for 1D materials)
+    #[serde(default)]
+    pub angles: EndAngles,
+
+    /// How the part may be joined from shorter segments (`None` if it must be cut whole)
+    #[serde(default)]
+    pub splice: Option<Splice>,
+
+    /// Which segment of a spliced part this is (`None` for whole parts)
+    #[serde(default)]
+    pub segment: Option<Segment>,
+}
+
+impl Part {
+    /// The name of the part, prefixed by its assembly (if any) and followed by its segment (if any)
+    pub fn label(&self) -> String {
+        let label = if self.assembly.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} / {}", self.assembly, self.name)
+        };
+        match &self.segment {
+            Some(segment) => i18n_f(
+                "{}, segment {} of {}",
+                &[
+                    &label,
+                    &segment.number.to_string(),
+                    &segment.count.to_string(),
+                ],
+            ),
+            None => label,
+        }
+    }
+}
+
+/// Limits on splicing a 1D part that is longer than every supply
+///
+/// Examples:
+/// - Baseboard with scarf joints: segments of at least 2ft, 1in overlap per join
+/// - Rope: segments of at least 1m, 30cm per knot
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Splice {
+    pub min_segment: Size,
+
+    /// Extra length used up by each join (e.g., the overlap of a scarf joint)
+    pub allowance: Size,
+}
+
+/// One of the pieces a spliced part is joined from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    /// Numbered from the start of the part (starting at 1)
+    pub number: usize,
+    pub count: usize,
+
+    /// Length of the whole part
+    pub part_length: Size,
+}
+
+/// Cut angles at both ends of a 1D part, in degrees from square
+///
+/// A positive angle leaves the top edge of the part longer than the bottom edge at that end, and a
+/// negative angle leaves it shorter. How much longer depends on the depth of the material (see
+/// `SubProblem::depth`). The length of a part is measured along its longer edge.
+///
+/// Examples:
+/// - Picture frame sides: 45 and 45
+/// - Parallel ends (e.g., a stair rail): 30 and -30
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EndAngles {
+    pub start: Fraction,
+    pub end: Fraction,
+}
+
+impl EndAngles {
+    pub fn is_square(&self) -> bool {
+        self.start.is_zero() && self.end.is_zero()
+    }
 }
 
 /// A list of cuts to perform on an item
@@ -73,6 +293,10 @@ pub struct CutList {
 
     /// The number of times this cut list should be performed
     pub quantity: usize,
+
+    /// Whether the cut list was pinned by the user rather than chosen by the solver
+    #[serde(default)]
+    pub locked: bool,
 }
 
 /// A problem definition for a single material
@@ -90,6 +314,30 @@ pub struct SubProblem {
     /// to make the cut; we can straddle the edge, or the remaining piece might be exactly what we
     /// need.
     pub blade_width: Size,
+
+    /// Depth of the material across which angled ends are cut
+    ///
+    /// Zero if unknown, in which case angled ends take up no extra length.
+    #[serde(default)]
+    pub depth: Size,
+
+    /// Length cut off the end of every supply before any parts, including its kerf
+    ///
+    /// Used to square up or clean rough factory ends. Supplies are only this much shorter while
+    /// solving; `SubSolution` lists their full lengths.
+    #[serde(default)]
+    pub end_trim: Size,
+
+    /// Vendors of the supplies (shared by every material in a problem)
+    #[serde(default)]
+    pub vendors: Vec<Vendor>,
+
+    /// Cut lists pinned by the user
+    ///
+    /// Solvers only see the parts and supplies left over after these are taken out (see
+    /// `Solver::solve`), and the locked cut lists are added back to the `SubSolution`.
+    #[serde(default)]
+    pub locked_cut_lists: Vec<CutList>,
 }
 
 /// A solution for a single material
@@ -109,6 +357,29 @@ pub struct SubSolution {
     ///
     /// Indices within `CutList` refer to `supplies` and `parts`
     pub cut_lists: Vec<CutList>,
+
+    /// Width of the blade (copied from `SubProblem` for drawing kerfs)
+    #[serde(default)]
+    pub blade_width: Size,
+
+    /// Depth of the material (copied from `SubProblem` for laying out angled ends)
+    #[serde(default)]
+    pub depth: Size,
+
+    /// Length trimmed off each supply (copied from `SubProblem` for laying out parts)
+    #[serde(default)]
+    pub end_trim: Size,
+
+    /// Vendors of the supplies (copied from `SubProblem` for pricing orders)
+    #[serde(default)]
+    pub vendors: Vec<Vendor>,
+
+    /// No solution can cost less than this
+    ///
+    /// Solvers that prove their own bound may set it; `Solver::solve` keeps the larger of it and
+    /// `Solver::lower_bound`.
+    #[serde(default)]
+    pub lower_bound: Option<Decimal>,
 }
 
 pub type Problem = HashMap<Material, SubProblem>;
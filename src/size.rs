@@ -1,11 +1,21 @@
-use fraction::{Fraction, Zero};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+
+use fraction::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Fraction, Zero};
+use gettextrs::gettext;
 use serde::{Deserialize, Serialize};
 
+use crate::i18n::i18n_f;
+
 const FEET_TO_METERS_NUM: u64 = 3048;
 const FEET_TO_METERS_DEN: u64 = 10000;
 
+#[derive(Debug, Clone)]
 pub enum FractionFormat {
-    Decimal(usize),
+    /// Number of decimal places and the decimal separator (e.g., '.' or ',')
+    Decimal(usize, char),
     Fraction,
     Mixed,
 }
@@ -13,7 +23,9 @@ pub enum FractionFormat {
 impl FractionFormat {
     pub fn format(&self, fraction: Fraction) -> String {
         match *self {
-            Self::Decimal(precision) => Self::format_decimal(fraction, precision),
+            Self::Decimal(precision, separator) => {
+                Self::format_decimal(fraction, precision, separator)
+            }
             Self::Mixed => Self::format_mixed(fraction),
             Self::Fraction => format!("{}", fraction),
         }
@@ -33,12 +45,12 @@ impl FractionFormat {
         }
     }
 
-    fn format_decimal(fraction: Fraction, precision: usize) -> String {
+    fn format_decimal(fraction: Fraction, precision: usize, separator: char) -> String {
         // Convert to f64 to get correct rounding behavior
         let float: f64 = fraction.try_into().unwrap();
 
         let result = format!("{0:.1$}", float, precision);
-        if precision > 0 {
+        let result = if precision > 0 {
             // Trim trailing zeros
             let parts: Vec<_> = result.split(".").collect();
             vec![parts[0], parts[1].trim_end_matches('0')]
@@ -47,11 +59,15 @@ impl FractionFormat {
                 .to_string()
         } else {
             result
-        }
+        };
+        result.replace('.', &separator.to_string())
     }
 }
 
 /// The `major` and `minor` fields allow splitting the size into e.g. feet and inches
+///
+/// Sizes compare by length regardless of unit (e.g., 1 ft equals 12 in). Sums and differences
+/// are in the unit of the left operand.
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct Size {
     pub unit: SizeUnit,
@@ -60,22 +76,52 @@ pub struct Size {
 }
 
 impl Size {
+    /// Formats the size with the minor part carried into the major part (see `normalized`)
     pub fn format(&self, fraction_format: &FractionFormat) -> String {
+        let size = self.normalized();
         let mut output = format!(
             "{} {}",
-            fraction_format.format(self.major),
-            self.unit.major_symbol()
+            fraction_format.format(size.major),
+            size.unit.major_symbol()
         );
-        if self.unit.has_minor() {
+        if size.unit.has_minor() {
             output += &format!(
                 ", {} {}",
-                fraction_format.format(self.minor),
-                self.unit.minor_symbol()
+                fraction_format.format(size.minor),
+                size.unit.minor_symbol()
             );
         }
         output
     }
 
+    /// Formats the size in `unit` (or its own unit if `None`), followed by the size in the other
+    /// measurement system if `dual` (e.g., "8 ft, 0 in (243.84 cm)")
+    pub fn format_in(
+        &self,
+        unit: Option<&SizeUnit>,
+        dual: bool,
+        fraction_format: &FractionFormat,
+    ) -> String {
+        let size = self.to_unit(unit.unwrap_or(&self.unit));
+        let output = size.format(fraction_format);
+        if dual {
+            let other = self.to_unit(&size.unit.counterpart());
+            format!("{} ({})", output, other.format(fraction_format))
+        } else {
+            output
+        }
+    }
+
+    /// Converts the size to `unit`, carrying whole feet out of the inches
+    pub fn to_unit(&self, unit: &SizeUnit) -> Self {
+        Self::from_meters_in(self.to_meters(), unit)
+    }
+
+    /// The same size with the minor part less than one major unit (e.g., 2 ft, 6 in for 0 ft, 30 in)
+    pub fn normalized(&self) -> Self {
+        self.to_unit(&self.unit)
+    }
+
     pub fn from_meters<T>(meters: T) -> Self
     where
         Fraction: From<T>,
@@ -87,6 +133,22 @@ impl Size {
         }
     }
 
+    /// Converts a length in meters to `unit`, splitting it into major and minor parts if needed
+    pub fn from_meters_in(meters: Fraction, unit: &SizeUnit) -> Self {
+        let feet = meters / Fraction::new(FEET_TO_METERS_NUM, FEET_TO_METERS_DEN);
+        let (major, minor) = match unit {
+            SizeUnit::FeetInches => (feet.trunc(), (feet - feet.trunc()) * 12),
+            SizeUnit::Inches => (feet * 12, Fraction::zero()),
+            SizeUnit::Meters => (meters, Fraction::zero()),
+            SizeUnit::Centimeters => (meters * 100, Fraction::zero()),
+        };
+        Self {
+            unit: unit.clone(),
+            major,
+            minor,
+        }
+    }
+
     pub fn to_meters(&self) -> Fraction {
         let feet_to_meters = Fraction::new(FEET_TO_METERS_NUM, FEET_TO_METERS_DEN);
         match self.unit {
@@ -102,6 +164,42 @@ impl Size {
     }
 }
 
+impl PartialEq for Size {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_meters() == other.to_meters()
+    }
+}
+
+impl Eq for Size {}
+
+impl PartialOrd for Size {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Size {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.to_meters().cmp(&other.to_meters())
+    }
+}
+
+impl Add for Size {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::from_meters_in(self.to_meters() + other.to_meters(), &self.unit)
+    }
+}
+
+impl Sub for Size {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::from_meters_in(self.to_meters() - other.to_meters(), &self.unit)
+    }
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub enum SizeUnit {
     FeetInches,
@@ -112,6 +210,19 @@ pub enum SizeUnit {
 }
 
 impl SizeUnit {
+    /// The unit used for the other measurement system in dual displays
+    pub fn counterpart(&self) -> Self {
+        if self.is_metric() {
+            Self::Inches
+        } else {
+            Self::Centimeters
+        }
+    }
+
+    pub fn is_metric(&self) -> bool {
+        matches!(*self, Self::Meters | Self::Centimeters)
+    }
+
     pub fn has_minor(&self) -> bool {
         match *self {
             Self::FeetInches => true,
@@ -150,4 +261,517 @@ impl SizeUnit {
             _ => "",
         }
     }
+
+    /// Parses a length typed in shop notation, returning it in this unit (or its minor unit)
+    ///
+    /// Accepts decimals, fractions and mixed numbers ("3 1/2" or "3-1/2"), unit marks that
+    /// override this unit (e.g., `3' 4-1/2"`, `96"`, `1.2m` or `2'6`, where a bare number after
+    /// feet is inches), sums and differences ("24 + 3/4") and multiplication by plain numbers.
+    /// Decimals are written with `separator` (see `normalize_decimal`).
+    pub fn parse(&self, text: &str, minor: bool, separator: char) -> Result<Fraction, LengthError> {
+        let field = match (self, minor) {
+            (Self::FeetInches, false) => Mark::Feet,
+            (Self::FeetInches, true) | (Self::Inches, _) => Mark::Inches,
+            (Self::Centimeters, _) => Mark::Centimeters,
+            (Self::Meters, _) => Mark::Meters,
+        };
+        let mut parser = LengthParser {
+            chars: text.trim().chars().collect(),
+            pos: 0,
+            field,
+            separator,
+        };
+        if parser.chars.is_empty() {
+            return Err(LengthError::Empty);
+        }
+        let meters = parser.expression()?;
+        if meters.is_sign_negative() && !meters.is_zero() {
+            Err(LengthError::Negative)
+        } else {
+            checked(meters.checked_div(&field.meters()))
+        }
+    }
+}
+
+/// Why text could not be parsed as a length (see `SizeUnit::parse`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum LengthError {
+    Empty,
+    Unexpected(String),
+    ExpectedNumber(String),
+    InvalidNumber(String),
+    WrongSeparator(String, char),
+    UnknownUnit(String),
+    MissingOperator(String),
+    ZeroDenominator,
+    Negative,
+    LengthTimesLength,
+    Overflow,
+}
+
+impl fmt::Display for LengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            Self::Empty => gettext("Enter a length"),
+            Self::Unexpected(s) => i18n_f("Unexpected \"{}\"", &[s]),
+            Self::ExpectedNumber(s) => i18n_f("Expected a number after \"{}\"", &[s]),
+            Self::InvalidNumber(s) => i18n_f("\"{}\" is not a number", &[s]),
+            Self::WrongSeparator(s, separator) => i18n_f(
+                "Write \"{}\" with \"{}\" as the decimal separator",
+                &[s, &separator.to_string()],
+            ),
+            Self::UnknownUnit(s) => {
+                i18n_f("Unknown unit \"{}\" (use ', \", ft, in, m, cm or mm)", &[s])
+            }
+            Self::MissingOperator(s) => i18n_f("Expected +, - or a unit mark before \"{}\"", &[s]),
+            Self::ZeroDenominator => gettext("A fraction can't have a denominator of zero"),
+            Self::Negative => gettext("Lengths can't be negative"),
+            Self::LengthTimesLength => gettext("Lengths can only be multiplied by plain numbers"),
+            Self::Overflow => gettext("The numbers are too large or too precise to calculate with"),
+        };
+        write!(f, "{}", message)
+    }
+}
+
+/// A unit written after a number
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mark {
+    Feet,
+    Inches,
+    Millimeters,
+    Centimeters,
+    Meters,
+}
+
+impl Mark {
+    fn meters(self) -> Fraction {
+        match self {
+            Self::Feet => Fraction::new(FEET_TO_METERS_NUM, FEET_TO_METERS_DEN),
+            Self::Inches => Fraction::new(FEET_TO_METERS_NUM, FEET_TO_METERS_DEN * 12),
+            Self::Millimeters => Fraction::new(1u64, 1000u64),
+            Self::Centimeters => Fraction::new(1u64, 100u64),
+            Self::Meters => Fraction::from(1),
+        }
+    }
+
+    fn from_word(word: &str) -> Option<Self> {
+        match word.to_lowercase().as_str() {
+            "'" | "′" | "ft" | "foot" | "feet" => Some(Self::Feet),
+            "\"" | "″" | "''" | "in" | "inch" | "inches" => Some(Self::Inches),
+            "mm" => Some(Self::Millimeters),
+            "cm" => Some(Self::Centimeters),
+            "m" => Some(Self::Meters),
+            _ => None,
+        }
+    }
+}
+
+/// A run of quantities added together without an operator (e.g., `3' 4"`)
+struct Length {
+    meters: Fraction,
+    /// The number itself, if it was written without a mark (it can then act as a factor)
+    bare: Option<Fraction>,
+    last_mark: Option<Mark>,
+}
+
+/// Recursive descent parser for `SizeUnit::parse`, working in meters
+struct LengthParser {
+    chars: Vec<char>,
+    pos: usize,
+    /// The unit of bare numbers
+    field: Mark,
+    separator: char,
+}
+
+impl LengthParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn starts_number(c: Option<char>) -> bool {
+        c.is_some_and(|c| c.is_ascii_digit() || c == '.' || c == ',')
+    }
+
+    /// The token at the current position, for error messages
+    fn token(&self) -> String {
+        let rest = &self.chars[self.pos..];
+        let length = rest
+            .iter()
+            .position(|c| !c.is_alphanumeric())
+            .unwrap_or(rest.len())
+            .max(1);
+        rest[..length].iter().collect()
+    }
+
+    /// Sums and differences of terms
+    fn expression(&mut self) -> Result<Fraction, LengthError> {
+        self.skip_spaces();
+        if matches!(self.peek(), Some('-' | '−')) {
+            return Err(LengthError::Negative);
+        }
+        let mut total = self.term()?;
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                None => return Ok(total),
+                Some('+') => {
+                    self.pos += 1;
+                    total = checked(total.checked_add(&self.term()?))?;
+                }
+                Some('-' | '−') => {
+                    self.pos += 1;
+                    total = checked(total.checked_sub(&self.term()?))?;
+                }
+                Some(_) => return Err(LengthError::Unexpected(self.token())),
+            }
+        }
+    }
+
+    /// Products of lengths and plain numbers
+    fn term(&mut self) -> Result<Fraction, LengthError> {
+        let mut value = self.length()?;
+        loop {
+            self.skip_spaces();
+            if !matches!(self.peek(), Some('*' | '×')) {
+                return Ok(value.meters);
+            }
+            self.pos += 1;
+            let factor = self.length()?;
+            value = match (value.bare, factor.bare) {
+                (Some(a), Some(b)) => {
+                    let product = checked(a.checked_mul(&b))?;
+                    Length {
+                        meters: checked(product.checked_mul(&self.field.meters()))?,
+                        bare: Some(product),
+                        last_mark: None,
+                    }
+                }
+                (Some(a), None) => Length {
+                    meters: checked(a.checked_mul(&factor.meters))?,
+                    ..factor
+                },
+                (None, Some(b)) => Length {
+                    meters: checked(value.meters.checked_mul(&b))?,
+                    ..value
+                },
+                (None, None) => return Err(LengthError::LengthTimesLength),
+            };
+        }
+    }
+
+    /// Quantities written one after another, like `3' 4-1/2"` or `2'6`
+    fn length(&mut self) -> Result<Length, LengthError> {
+        self.skip_spaces();
+        let (meters, mark, number) = self.quantity(false)?;
+        let mut length = Length {
+            meters,
+            bare: mark.is_none().then_some(number),
+            last_mark: mark,
+        };
+        loop {
+            // Allow a dash between feet and inches (e.g., `3'-4"`)
+            let start = self.pos;
+            if length.last_mark == Some(Mark::Feet)
+                && self.peek() == Some('-')
+                && Self::starts_number(self.peek_at(1))
+            {
+                self.pos += 1;
+            }
+            self.skip_spaces();
+            if !Self::starts_number(self.peek()) {
+                self.pos = start;
+                return Ok(length);
+            }
+            let token = self.token();
+            let after_feet = length.last_mark == Some(Mark::Feet);
+            let (meters, mark, _) = self.quantity(after_feet)?;
+            if length.last_mark.is_none() || (mark.is_none() && !after_feet) {
+                return Err(LengthError::MissingOperator(token));
+            }
+            length.meters = checked(length.meters.checked_add(&meters))?;
+            length.bare = None;
+            match mark {
+                Some(_) => length.last_mark = mark,
+                // A bare number after feet is the inches, which ends the length
+                None => return Ok(length),
+            }
+        }
+    }
+
+    /// A number and optional unit mark, returning meters, the mark and the number
+    fn quantity(
+        &mut self,
+        after_feet: bool,
+    ) -> Result<(Fraction, Option<Mark>, Fraction), LengthError> {
+        let number = self.number()?;
+        let start = self.pos;
+        self.skip_spaces();
+        let mark = match self.peek() {
+            Some('\'') if self.peek_at(1) == Some('\'') => {
+                self.pos += 2;
+                Some(Mark::Inches)
+            }
+            Some(c @ ('\'' | '′' | '"' | '″')) => {
+                self.pos += 1;
+                Mark::from_word(&c.to_string())
+            }
+            Some(c) if c.is_alphabetic() => {
+                let word: String = self.chars[self.pos..]
+                    .iter()
+                    .take_while(|c| c.is_alphabetic())
+                    .collect();
+                self.pos += word.chars().count();
+                Some(Mark::from_word(&word).ok_or(LengthError::UnknownUnit(word))?)
+            }
+            _ => {
+                self.pos = start;
+                None
+            }
+        };
+        let unit = match mark {
+            Some(mark) => mark,
+            None if after_feet => Mark::Inches,
+            None => self.field,
+        };
+        Ok((checked(number.checked_mul(&unit.meters()))?, mark, number))
+    }
+
+    /// A decimal, fraction or mixed number
+    fn number(&mut self) -> Result<Fraction, LengthError> {
+        let whole = self.digits()?;
+        if self.peek() == Some('/') {
+            self.pos += 1;
+            return self.fraction(whole);
+        }
+
+        // A mixed number continues with a fraction after a space or dash (e.g., "3 1/2" or "3-1/2")
+        let start = self.pos;
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        } else {
+            self.skip_spaces();
+        }
+        if self.pos > start && self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            let numerator_start = self.pos;
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+            if self.peek() == Some('/') && self.peek_at(1).is_some_and(|c| c.is_ascii_digit()) {
+                self.pos = numerator_start;
+                let numerator = self.digits()?;
+                self.pos += 1;
+                return checked(whole.checked_add(&self.fraction(numerator)?));
+            }
+        }
+        self.pos = start;
+        Ok(whole)
+    }
+
+    /// The denominator of a fraction, after the slash
+    fn fraction(&mut self, numerator: Fraction) -> Result<Fraction, LengthError> {
+        if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            return match self.peek() {
+                None => Err(LengthError::ExpectedNumber(String::from("/"))),
+                Some(_) => Err(LengthError::Unexpected(self.token())),
+            };
+        }
+        let denominator = self.digits()?;
+        if denominator.is_zero() {
+            Err(LengthError::ZeroDenominator)
+        } else {
+            checked(numerator.checked_div(&denominator))
+        }
+    }
+
+    /// Digits with an optional decimal separator
+    fn digits(&mut self) -> Result<Fraction, LengthError> {
+        let start = self.pos;
+        while Self::starts_number(self.peek()) {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        if text.is_empty() {
+            return match self.peek() {
+                None => {
+                    let previous = self.chars[..self.pos]
+                        .iter()
+                        .rfind(|c| !c.is_whitespace())
+                        .map(|c| c.to_string())
+                        .unwrap_or_default();
+                    Err(LengthError::ExpectedNumber(previous))
+                }
+                Some(_) => Err(LengthError::Unexpected(self.token())),
+            };
+        }
+        let Some(normalized) = normalize_decimal(&text, self.separator) else {
+            return Err(LengthError::WrongSeparator(text, self.separator));
+        };
+        match Fraction::from_str(&normalized) {
+            Ok(value) if normalized.matches('.').count() <= 1 && !value.is_nan() => Ok(value),
+            _ => Err(LengthError::InvalidNumber(text)),
+        }
+    }
+}
+
+/// Fails with `LengthError::Overflow` where the result of a checked operation doesn't fit
+fn checked(result: Option<Fraction>) -> Result<Fraction, LengthError> {
+    result.ok_or(LengthError::Overflow)
+}
+
+/// Writes a number with `separator` as the decimal separator (either '.' or ',') with a point, for
+/// parsing
+///
+/// Returns `None` if the number contains the other separator, which is rejected rather than
+/// guessed at: "1,250" is 1.25 with a decimal comma but would be 1250 where commas group thousands.
+pub fn normalize_decimal(text: &str, separator: char) -> Option<String> {
+    let other = if separator == ',' { '.' } else { ',' };
+    (!text.contains(other)).then(|| text.replace(separator, "."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_arithmetic() {
+        let size = |unit, major: u64, minor: u64| Size {
+            unit,
+            major: Fraction::from(major),
+            minor: Fraction::from(minor),
+        };
+        let feet_inches = |major, minor| size(SizeUnit::FeetInches, major, minor);
+
+        // Comparison ignores the unit
+        assert_eq!(feet_inches(1, 0), size(SizeUnit::Inches, 12, 0));
+        assert_eq!(
+            size(SizeUnit::Meters, 1, 0),
+            size(SizeUnit::Centimeters, 100, 0)
+        );
+        assert!(feet_inches(0, 13) > feet_inches(1, 0));
+        assert!(size(SizeUnit::Inches, 1, 0) < size(SizeUnit::Centimeters, 3, 0));
+
+        // Results are in the unit of the left operand, with inches carried into feet
+        let sum = feet_inches(1, 8) + size(SizeUnit::Inches, 10, 0);
+        assert_eq!(sum.major, Fraction::from(2));
+        assert_eq!(sum.minor, Fraction::from(6));
+        let difference = size(SizeUnit::Inches, 30, 0) - feet_inches(1, 0);
+        assert!(matches!(difference.unit, SizeUnit::Inches));
+        assert_eq!(difference.major, Fraction::from(18));
+
+        // Conversion normalizes
+        let normalized = feet_inches(0, 30).normalized();
+        assert_eq!(normalized.major, Fraction::from(2));
+        assert_eq!(normalized.minor, Fraction::from(6));
+        let centimeters = feet_inches(1, 0).to_unit(&SizeUnit::Centimeters);
+        assert_eq!(centimeters.major, Fraction::new(3048u64, 100u64));
+
+        let format = FractionFormat::Mixed;
+        assert_eq!(feet_inches(0, 30).format(&format), "2 ft, 6 in");
+        assert_eq!(
+            feet_inches(0, 30).format_in(Some(&SizeUnit::Inches), false, &format),
+            "30 in"
+        );
+        assert_eq!(
+            size(SizeUnit::Inches, 1, 0).format_in(None, true, &format),
+            "1 in (2 27/50 cm)"
+        );
+    }
+
+    #[test]
+    fn test_parse_length() {
+        let parse = |unit: SizeUnit, minor, text| unit.parse(text, minor, '.');
+        let inches = |text| parse(SizeUnit::Inches, false, text);
+
+        // Plain numbers are in the field's unit
+        assert_eq!(inches("24"), Ok(Fraction::from(24)));
+        assert_eq!(inches("2.5"), Ok(Fraction::new(5u64, 2u64)));
+        assert_eq!(inches("1200.5"), Ok(Fraction::new(2401u64, 2u64)));
+        assert_eq!(inches("3 1/2"), Ok(Fraction::new(7u64, 2u64)));
+        assert_eq!(inches("3-1/2"), Ok(Fraction::new(7u64, 2u64)));
+        assert_eq!(inches("24 + 3/4"), Ok(Fraction::new(99u64, 4u64)));
+        assert_eq!(inches("10-2"), Ok(Fraction::from(8)));
+        assert_eq!(inches("2 * 12"), Ok(Fraction::from(24)));
+
+        // Unit marks override the field's unit
+        assert_eq!(inches("96\""), Ok(Fraction::from(96)));
+        assert_eq!(inches("3' 4-1/2\""), Ok(Fraction::new(81u64, 2u64)));
+        assert_eq!(inches("3'-4\""), Ok(Fraction::from(40)));
+        assert_eq!(inches("2'6"), Ok(Fraction::from(30)));
+        assert_eq!(inches("2 ft 6 in"), Ok(Fraction::from(30)));
+        assert_eq!(inches("3 * 1'"), Ok(Fraction::from(36)));
+        assert_eq!(inches("25.4mm"), Ok(Fraction::from(1)));
+        assert_eq!(
+            parse(SizeUnit::Centimeters, false, "1.2m"),
+            Ok(Fraction::from(120))
+        );
+        assert_eq!(
+            parse(SizeUnit::FeetInches, false, "18\""),
+            Ok(Fraction::new(3u64, 2u64))
+        );
+        assert_eq!(
+            parse(SizeUnit::FeetInches, true, "1'"),
+            Ok(Fraction::from(12))
+        );
+
+        // Errors explain what was wrong
+        assert_eq!(inches(" "), Err(LengthError::Empty));
+        assert_eq!(inches("3 +"), Err(LengthError::ExpectedNumber("+".into())));
+        assert_eq!(inches("3 4"), Err(LengthError::MissingOperator("4".into())));
+        assert_eq!(inches("3yd"), Err(LengthError::UnknownUnit("yd".into())));
+        assert_eq!(inches("1/0"), Err(LengthError::ZeroDenominator));
+        assert_eq!(inches("2 - 3"), Err(LengthError::Negative));
+        assert_eq!(inches("-2"), Err(LengthError::Negative));
+        assert_eq!(inches("1' * 2'"), Err(LengthError::LengthTimesLength));
+        assert_eq!(
+            inches("1.2.3"),
+            Err(LengthError::InvalidNumber("1.2.3".into()))
+        );
+        assert_eq!(inches("3 # 4"), Err(LengthError::Unexpected("#".into())));
+        assert_eq!(
+            inches("0.1234567 * 0.7654321 * 0.1234567"),
+            Err(LengthError::Overflow)
+        );
+        assert_eq!(inches("9999999999999999999m"), Err(LengthError::Overflow));
+
+        // Only the given decimal separator is accepted
+        assert_eq!(
+            inches("1,200"),
+            Err(LengthError::WrongSeparator("1,200".into(), '.'))
+        );
+        assert_eq!(
+            inches("1,200.5"),
+            Err(LengthError::WrongSeparator("1,200.5".into(), '.'))
+        );
+        let comma = |unit: SizeUnit, text| unit.parse(text, false, ',');
+        assert_eq!(
+            comma(SizeUnit::Meters, "1,250 m"),
+            Ok(Fraction::new(5u64, 4u64))
+        );
+        assert_eq!(
+            comma(SizeUnit::Inches, "2,5"),
+            Ok(Fraction::new(5u64, 2u64))
+        );
+        assert_eq!(
+            comma(SizeUnit::Inches, "3' 4,5\""),
+            Ok(Fraction::new(81u64, 2u64))
+        );
+        assert_eq!(
+            comma(SizeUnit::Inches, "2.5"),
+            Err(LengthError::WrongSeparator("2.5".into(), ','))
+        );
+        assert_eq!(
+            comma(SizeUnit::Inches, "1,2,3"),
+            Err(LengthError::InvalidNumber("1,2,3".into()))
+        );
+    }
 }
@@ -1,63 +1,358 @@
+pub mod local_search;
 pub mod naive_solver;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use async_channel::Sender;
+use fraction::{Decimal, Fraction, Zero};
+use gettextrs::gettext;
+use serde::{Deserialize, Serialize};
 
-use crate::modeling::{CutList, Problem, Solution, SubProblem, SubSolution};
+use crate::advisor::StockAdvice;
+use crate::i18n::{gettext_noop, i18n_f};
+use crate::layout::{Packer, PartShape, used_length};
+use crate::modeling::{
+    CutList, EndAngles, Part, Problem, Segment, Solution, SubProblem, SubSolution,
+};
+use crate::size::Size;
+use crate::utils::{compute_total_price, compute_vendor_totals};
 
 /// Messages that the solver thread can send to the main (UI) thread
 pub enum Message {
     Progress(f64),
     SubProgress(f64),
     Results(Result<Solution, String>),
+    Advice(Vec<StockAdvice>),
+}
+
+/// What a solver minimizes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Objective {
+    #[default]
+    Cost,
+    Waste,
+}
+
+impl Objective {
+    /// Names in the order used by `Objective::from`
+    pub const NAMES: &[&str] = &[gettext_noop("Cost"), gettext_noop("Waste")];
+
+    pub fn from(index: usize) -> Self {
+        match index {
+            1 => Self::Waste,
+            _ => Self::Cost,
+        }
+    }
+}
+
+/// The kinds of problems a solver handles and the quality of its results
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    pub one_d: bool,
+    pub two_d: bool,
+
+    /// Whether results are guaranteed to be optimal (otherwise the solver is a heuristic)
+    pub optimal: bool,
+}
+
+/// The type and allowed values of a solver parameter
+#[derive(Debug, Clone)]
+pub enum ParameterKind {
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    Float {
+        min: f64,
+        max: f64,
+        digits: u32,
+    },
+
+    /// One of a list of named options (stored as an index)
+    Choice(&'static [&'static str]),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ParameterValue {
+    Integer(i64),
+    Float(f64),
+    Choice(usize),
+}
+
+/// Parameter values keyed by `ParameterSpec::key`
+pub type Parameters = BTreeMap<String, ParameterValue>;
+
+/// Describes a parameter that a solver accepts
+#[derive(Debug, Clone)]
+pub struct ParameterSpec {
+    /// Stable identifier used in project files
+    pub key: &'static str,
+
+    pub name: &'static str,
+    pub description: &'static str,
+    pub kind: ParameterKind,
+    pub default: ParameterValue,
+}
+
+impl ParameterSpec {
+    /// Returns the value in `parameters`, falling back to the default if it is missing or invalid
+    pub fn value(&self, parameters: &Parameters) -> ParameterValue {
+        let value = parameters.get(self.key);
+        let is_valid = match (&self.kind, value) {
+            (ParameterKind::Integer { min, max }, Some(ParameterValue::Integer(v))) => {
+                min <= v && v <= max
+            }
+            (ParameterKind::Float { min, max, .. }, Some(ParameterValue::Float(v))) => {
+                min <= v && v <= max
+            }
+            (ParameterKind::Choice(names), Some(ParameterValue::Choice(i))) => *i < names.len(),
+            _ => false,
+        };
+        if is_valid {
+            value.unwrap().clone()
+        } else {
+            self.default.clone()
+        }
+    }
+
+    pub fn integer(&self, parameters: &Parameters) -> i64 {
+        match self.value(parameters) {
+            ParameterValue::Integer(v) => v,
+            _ => panic!("Parameter \"{}\" is not an integer", self.key),
+        }
+    }
+
+    pub fn float(&self, parameters: &Parameters) -> f64 {
+        match self.value(parameters) {
+            ParameterValue::Float(v) => v,
+            _ => panic!("Parameter \"{}\" is not a float", self.key),
+        }
+    }
+
+    pub fn choice(&self, parameters: &Parameters) -> usize {
+        match self.value(parameters) {
+            ParameterValue::Choice(i) => i,
+            _ => panic!("Parameter \"{}\" is not a choice", self.key),
+        }
+    }
+}
+
+/// Registry entry describing a solver
+pub struct SolverInfo {
+    /// Stable identifier used in project files
+    pub id: &'static str,
+
+    pub name: &'static str,
+    pub description: &'static str,
+    pub capabilities: Capabilities,
+    pub parameters: &'static [ParameterSpec],
+
+    /// Builds the solver from parameter values (missing values take their defaults)
+    pub create: fn(&Parameters) -> Box<dyn Solver + Send>,
+}
+
+impl SolverInfo {
+    /// Short summary of the capabilities (e.g., "1D · Heuristic")
+    pub fn capability_summary(&self) -> String {
+        let mut parts = Vec::new();
+        match (self.capabilities.one_d, self.capabilities.two_d) {
+            (true, true) => parts.push(gettext("1D and 2D")),
+            (true, false) => parts.push(String::from("1D")),
+            (false, true) => parts.push(String::from("2D")),
+            (false, false) => (),
+        }
+        parts.push(if self.capabilities.optimal {
+            gettext("Optimal")
+        } else {
+            gettext("Heuristic")
+        });
+        parts.join(" · ")
+    }
+}
+
+/// All available solvers
+///
+/// Project files store the `SolverInfo::id` of the selected solver, but older ones store its index
+/// (see `SolverRef`), so the solvers listed here keep their order.
+pub const SOLVERS: &[SolverInfo] = &[naive_solver::INFO, local_search::INFO];
+
+/// A solver selected in a project file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SolverRef {
+    Id(String),
+
+    /// An index in `SOLVERS`, as stored before solvers had ids
+    Index(usize),
+}
+
+impl Default for SolverRef {
+    fn default() -> Self {
+        Self::Id(String::from(SOLVERS[0].id))
+    }
+}
+
+impl SolverRef {
+    /// The index in `SOLVERS` of the solver referred to, if this version has it
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            Self::Id(id) => SOLVERS.iter().position(|s| s.id == id),
+            Self::Index(i) => (*i < SOLVERS.len()).then_some(*i),
+        }
+    }
+
+    pub fn info(&self) -> Option<&'static SolverInfo> {
+        self.index().map(|i| &SOLVERS[i])
+    }
 }
 
 pub trait Solver {
-    /// Groups cut lists with matching `supply_index` and `part_indices`
+    /// Groups cut lists with matching `supply_index`, `part_indices`, and `locked`
     ///
     /// Consumes the old `SubSolution` to create the returned one
     fn group_cut_lists(&self, sub_solution: SubSolution) -> SubSolution {
-        let mut counts = HashMap::<(usize, Vec<usize>), usize>::new();
+        let mut counts = HashMap::<(usize, Vec<usize>, bool), usize>::new();
         for cut_list in sub_solution.cut_lists.into_iter() {
-            let key = (cut_list.supply_index, cut_list.part_indices);
+            let key = (
+                cut_list.supply_index,
+                cut_list.part_indices,
+                cut_list.locked,
+            );
             let count = counts.get(&key).unwrap_or(&0);
             counts.insert(key, count + cut_list.quantity);
         }
         let mut cut_lists = Vec::new();
-        for ((supply_index, part_indices), quantity) in counts.into_iter() {
+        for ((supply_index, part_indices, locked), quantity) in counts.into_iter() {
             cut_lists.push(CutList {
                 supply_index,
                 part_indices,
                 quantity,
+                locked,
             })
         }
         SubSolution {
             cut_lists,
             supplies: sub_solution.supplies,
             parts: sub_solution.parts,
+            blade_width: sub_solution.blade_width,
+            depth: sub_solution.depth,
+            end_trim: sub_solution.end_trim,
+            vendors: sub_solution.vendors,
+            lower_bound: sub_solution.lower_bound,
         }
     }
 
     fn solve(&self, problem: Problem, sender: Option<Sender<Message>>) -> Result<Solution, String> {
+        let result = self
+            .solve_materials(problem.clone(), &sender)
+            .map(|solution| self.drop_costly_vendors(problem, solution, &sender));
+        if result.is_ok() {
+            self.send_progress(&sender, 1.0);
+        }
+        self.send_result(&sender, result.clone());
+        result
+    }
+
+    /// Re-solves without each vendor that charges fees, for as long as that lowers the total price
+    ///
+    /// Delivery and small-order fees can cost more than buying the same supplies elsewhere. Only the
+    /// materials that have supplies from a vendor are re-solved without it.
+    fn drop_costly_vendors(
+        &self,
+        mut problem: Problem,
+        mut solution: Solution,
+        sender: &Option<Sender<Message>>,
+    ) -> Solution {
+        loop {
+            let mut best: Option<(Problem, Solution)> = None;
+            let mut best_price = compute_total_price(&solution);
+            let vendors: Vec<_> = compute_vendor_totals(&solution)
+                .into_iter()
+                .filter(|t| t.fees > Decimal::zero())
+                .map(|t| t.vendor)
+                .collect();
+            for vendor in vendors {
+                let mut candidate = problem.clone();
+                let mut affected = Problem::new();
+                for (material, sub_problem) in candidate.iter_mut() {
+                    let mut uses_vendor = false;
+                    for supply in sub_problem.supplies.iter_mut() {
+                        if supply.vendor == vendor {
+                            supply.max_quantity = 0;
+                            uses_vendor = true;
+                        }
+                    }
+                    if uses_vendor {
+                        affected.insert(material.clone(), sub_problem.clone());
+                    }
+                }
+                if let Ok(sub_solutions) = self.solve_materials(affected, sender) {
+                    let mut candidate_solution = solution.clone();
+                    candidate_solution.extend(sub_solutions);
+                    let price = compute_total_price(&candidate_solution);
+                    if price < best_price {
+                        best_price = price;
+                        best = Some((candidate, candidate_solution));
+                    }
+                }
+            }
+            match best {
+                Some((candidate, candidate_solution)) => {
+                    problem = candidate;
+                    solution = candidate_solution;
+                }
+                None => return solution,
+            }
+        }
+    }
+
+    /// Solves each material separately, failing if any material fails
+    fn solve_materials(
+        &self,
+        problem: Problem,
+        sender: &Option<Sender<Message>>,
+    ) -> Result<Solution, String> {
         let mut solution = Solution::new();
         for (material, sub_problem) in problem.into_iter() {
-            match self.solve_sub_problem(sub_problem, &sender) {
+            let result = split_long_parts(sub_problem).and_then(|mut sub_problem| {
+                let supplies = sub_problem.supplies.clone();
+                let parts = sub_problem.parts.clone();
+                trim_supplies(&mut sub_problem);
+                let lower_bound = self.lower_bound(&sub_problem);
+                let (sub_problem, locked) = remove_locked_cut_lists(sub_problem)?;
+                let mut sub_solution = self.solve_sub_problem(sub_problem, sender)?;
+
+                // Report the original quantities and lengths alongside the locked cut lists
+                sub_solution.supplies = supplies;
+                sub_solution.parts = parts;
+                fill_offcuts(&mut sub_solution);
+                sub_solution.cut_lists.extend(locked);
+                sub_solution.lower_bound = match sub_solution.lower_bound {
+                    Some(bound) if bound > lower_bound => Some(bound),
+                    _ => Some(lower_bound),
+                };
+                Ok(sub_solution)
+            });
+            match result {
                 Ok(sub_solution) => {
                     solution.insert(material, self.group_cut_lists(sub_solution));
                 }
                 Err(message) => {
-                    let message = format!("Error for material \"{}\": {}", material.name, message);
-                    let result = Err(message);
-                    self.send_result(&sender, result.clone());
-                    return result;
+                    return Err(i18n_f(
+                        "Error for material \"{}\": {}",
+                        &[&material.name, &message],
+                    ));
                 }
             };
         }
-        let result = Ok(solution);
-        self.send_progress(&sender, 1.0);
-        self.send_result(&sender, result.clone());
-        result
+        Ok(solution)
+    }
+
+    /// Returns a lower bound on the cost of any solution to the `SubProblem`
+    ///
+    /// Locked cut lists are ignored, since locking can only make solutions more expensive.
+    fn lower_bound(&self, sub_problem: &SubProblem) -> Decimal {
+        relaxation_lower_bound(sub_problem)
     }
 
     fn send_message(&self, sender: &Option<Sender<Message>>, message: Message) {
@@ -89,3 +384,673 @@ pub trait Solver {
         progress_sender: &Option<Sender<Message>>,
     ) -> Result<SubSolution, String>;
 }
+
+/// A lower bound on cost that any solver can use
+///
+/// Both relaxations let parts be bought by the meter (ignoring kerf and assuming angled ends nest
+/// perfectly, at each supply's best bundle price), and the larger is returned:
+/// - Each part at the lowest price per meter among the supplies long enough for it
+/// - All parts at the lowest prices per meter, using up limited supplies before moving on
+pub fn relaxation_lower_bound(sub_problem: &SubProblem) -> Decimal {
+    // Price per meter and available meters for each supply
+    let mut rates: Vec<(f64, f64, f64)> = Vec::new();
+    for supply in sub_problem.supplies.iter() {
+        let length = supply.length.to_meters_f64();
+        if length > 0.0 {
+            let price: f64 = supply.unit_price().try_into().unwrap();
+            let available = match supply.max_quantity {
+                -1 => f64::INFINITY,
+                n => n as f64 * length,
+            };
+            rates.push((price / length, length, available));
+        }
+    }
+    rates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut by_part = 0.0;
+    let mut needed = 0.0;
+    for part in sub_problem.parts.iter().filter(|p| p.quantity > 0) {
+        let length: f64 = PartShape::from(part, &sub_problem.depth)
+            .average_length()
+            .try_into()
+            .unwrap();
+        let meters = length * part.quantity as f64;
+        needed += meters;
+        if let Some((rate, _, _)) = rates.iter().find(|r| r.1 >= part.length.to_meters_f64()) {
+            by_part += rate * meters;
+        }
+    }
+
+    let mut by_supply = 0.0;
+    for (rate, _, available) in rates.iter() {
+        let used = needed.min(*available);
+        by_supply += rate * used;
+        needed -= used;
+    }
+
+    Decimal::from(by_part.max(by_supply))
+}
+
+/// Replaces spliceable parts that are longer than every supply with their segments
+///
+/// Segments are as long as the longest supply, except that the last two are balanced so neither
+/// is shorter than the minimum. Segments leave room for the end trim. Each join adds the allowance
+/// to the total length, and only the
+/// first and last segments keep the angles of the part. Parts that already fit are left alone, so
+/// splitting twice has no further effect. Locked cut lists refer to the parts after splitting.
+pub fn split_long_parts(mut sub_problem: SubProblem) -> Result<SubProblem, String> {
+    let longest = sub_problem
+        .supplies
+        .iter()
+        .filter(|s| s.max_quantity != 0)
+        .map(|s| s.length.to_meters() - sub_problem.end_trim.to_meters())
+        .max()
+        .unwrap_or_default()
+        .max(Fraction::zero());
+    let mut parts = Vec::new();
+    for part in std::mem::take(&mut sub_problem.parts) {
+        let length = part.length.to_meters();
+        let Some(splice) = part.splice.as_ref().filter(|_| length > longest) else {
+            parts.push(part);
+            continue;
+        };
+        let min_segment = splice.min_segment.to_meters();
+        let allowance = splice.allowance.to_meters();
+        if min_segment > longest || allowance >= longest {
+            return Err(i18n_f(
+                "Part \"{}\" can't be spliced from the available supplies",
+                &[&part.label()],
+            ));
+        }
+
+        let mut lengths = Vec::new();
+        let mut remaining = length;
+        while remaining > longest {
+            // Leave enough for the rest to be at least the minimum
+            let mut segment = longest;
+            if remaining - segment + allowance < min_segment {
+                segment = remaining + allowance - min_segment;
+            }
+            if segment < min_segment {
+                return Err(i18n_f(
+                    "Part \"{}\" can't be spliced without segments shorter than the minimum",
+                    &[&part.label()],
+                ));
+            }
+            lengths.push(segment);
+            remaining = remaining - segment + allowance;
+        }
+        lengths.push(remaining);
+
+        let count = lengths.len();
+        for (i, segment_length) in lengths.into_iter().enumerate() {
+            let mut angles = EndAngles::default();
+            if i == 0 {
+                angles.start = part.angles.start;
+            }
+            if i == count - 1 {
+                angles.end = part.angles.end;
+            }
+            parts.push(Part {
+                length: Size::from_meters_in(segment_length, &part.length.unit),
+                angles,
+                splice: None,
+                segment: Some(Segment {
+                    number: i + 1,
+                    count,
+                    part_length: part.length.clone(),
+                }),
+                ..part.clone()
+            });
+        }
+    }
+    sub_problem.parts = parts;
+    Ok(sub_problem)
+}
+
+/// Shortens every supply by the end trim, so solvers need not know about it
+///
+/// Supplies too short to trim are left with no length.
+fn trim_supplies(sub_problem: &mut SubProblem) {
+    let end_trim = sub_problem.end_trim.to_meters();
+    if end_trim == Fraction::zero() {
+        return;
+    }
+    for supply in sub_problem.supplies.iter_mut() {
+        let length = (supply.length.to_meters() - end_trim).max(Fraction::zero());
+        supply.length = Size::from_meters_in(length, &supply.length.unit);
+    }
+}
+
+/// Adds fill parts to the offcuts of cut lists that aren't locked
+///
+/// Fill parts are tried longest first, so each offcut takes the largest pieces it can and then
+/// any shorter ones that fit in what remains. No supplies are added.
+pub fn fill_offcuts(sub_solution: &mut SubSolution) {
+    let mut fill_indices: Vec<usize> = (0..sub_solution.parts.len())
+        .filter(|i| sub_solution.parts[*i].fill)
+        .collect();
+    if fill_indices.is_empty() {
+        return;
+    }
+    fill_indices.sort_by_key(|i| std::cmp::Reverse(&sub_solution.parts[*i].length));
+    let shapes: Vec<_> = sub_solution
+        .parts
+        .iter()
+        .map(|p| PartShape::from(p, &sub_solution.depth))
+        .collect();
+    let blade_width = sub_solution.blade_width.to_meters();
+    let end_trim = sub_solution.end_trim.to_meters();
+    for cut_list in sub_solution.cut_lists.iter_mut().filter(|c| !c.locked) {
+        let length = sub_solution.supplies[cut_list.supply_index]
+            .length
+            .to_meters();
+        let mut packer = Packer::with_trim(blade_width, end_trim);
+        for part_index in cut_list.part_indices.iter() {
+            packer.place(*part_index, &shapes[*part_index]);
+        }
+        for part_index in fill_indices.iter() {
+            loop {
+                let mut next = packer.clone();
+                next.place(*part_index, &shapes[*part_index]);
+                if next.used() > length {
+                    break;
+                }
+                packer = next;
+                cut_list.part_indices.push(*part_index);
+            }
+        }
+    }
+}
+
+/// Takes the parts and supplies used by locked cut lists out of a `SubProblem`
+///
+/// Returns the remaining problem and the locked cut lists (marked as locked). Fails if the locked
+/// cut lists use more parts or supplies than are available, or if their parts do not fit.
+fn remove_locked_cut_lists(
+    mut sub_problem: SubProblem,
+) -> Result<(SubProblem, Vec<CutList>), String> {
+    let mut locked = std::mem::take(&mut sub_problem.locked_cut_lists);
+    let blade_width = sub_problem.blade_width.to_meters();
+    for cut_list in locked.iter_mut() {
+        cut_list.locked = true;
+        let supply = sub_problem
+            .supplies
+            .get_mut(cut_list.supply_index)
+            .ok_or_else(|| gettext("Locked cut list refers to an unknown supply"))?;
+
+        let mut shapes = Vec::new();
+        for part_index in cut_list.part_indices.iter() {
+            let part = sub_problem
+                .parts
+                .get_mut(*part_index)
+                .ok_or_else(|| gettext("Locked cut list refers to an unknown part"))?;
+            shapes.push(PartShape::from(part, &sub_problem.depth));
+            if part.quantity != -1 {
+                part.quantity -= cut_list.quantity as i64;
+                if part.fill {
+                    // Any more than the minimum are extras
+                    part.quantity = part.quantity.max(0);
+                } else if part.quantity < 0 {
+                    return Err(i18n_f(
+                        "Locked cut lists contain more of part \"{}\" than requested",
+                        &[&part.label()],
+                    ));
+                }
+            }
+        }
+        if used_length(shapes.iter(), blade_width) > supply.length.to_meters() {
+            return Err(i18n_f(
+                "Locked parts do not fit on supply \"{}\"",
+                &[&supply.name],
+            ));
+        }
+
+        if supply.max_quantity != -1 {
+            supply.max_quantity -= cut_list.quantity as i64;
+            if supply.max_quantity < 0 {
+                return Err(i18n_f(
+                    "Locked cut lists use more of supply \"{}\" than available",
+                    &[&supply.name],
+                ));
+            }
+        }
+    }
+    Ok((sub_problem, locked))
+}
+
+#[cfg(test)]
+mod tests {
+    use fraction::Decimal;
+
+    use super::naive_solver::NaiveSolver;
+    use super::*;
+    use crate::layout::CutLayout;
+    use crate::modeling::{Bundle, EndAngles, Material, Part, Splice, Supply, Vendor};
+    use crate::size::Size;
+    use crate::utils::{compute_part_production, compute_supply_consumption};
+
+    #[test]
+    fn test_locked_cut_lists() {
+        let material = Material::default();
+        let supplies = vec![Supply {
+            name: String::new(),
+            length: Size::from_meters(8.0),
+            price: Decimal::from(3.5),
+            max_quantity: 2,
+            vendor: String::new(),
+            ..Default::default()
+        }];
+        let parts = vec![
+            Part {
+                name: String::from("Face"),
+                length: Size::from_meters(3.0),
+                quantity: 2,
+                assembly: String::new(),
+                angles: EndAngles::default(),
+                splice: None,
+                segment: None,
+                fill: false,
+            },
+            Part {
+                name: String::from("Back"),
+                length: Size::from_meters(5.0),
+                quantity: 2,
+                assembly: String::new(),
+                angles: EndAngles::default(),
+                splice: None,
+                segment: None,
+                fill: false,
+            },
+        ];
+
+        // Both faces must come from the same board
+        let locked_cut_lists = vec![CutList {
+            supply_index: 0,
+            part_indices: vec![0, 0],
+            quantity: 1,
+            locked: false,
+        }];
+        let mut problem = Problem::new();
+        problem.insert(
+            material.clone(),
+            SubProblem {
+                supplies,
+                parts,
+                blade_width: Size::from_meters(0.0),
+                depth: Size::default(),
+                end_trim: Size::default(),
+                vendors: Vec::new(),
+                locked_cut_lists,
+            },
+        );
+
+        // Only one board is left for the two backs, so the solver must fail
+        assert!(NaiveSolver::default().solve(problem.clone(), None).is_err());
+
+        let sub_problem = problem.get_mut(&material).unwrap();
+        sub_problem.supplies[0].max_quantity = 3;
+        let solution = NaiveSolver::default().solve(problem, None).unwrap();
+        let cut_lists = &solution[&material].cut_lists;
+        let locked: Vec<_> = cut_lists.iter().filter(|c| c.locked).collect();
+        assert_eq!(locked.len(), 1);
+        assert_eq!(locked[0].part_indices, vec![0, 0]);
+        assert!(
+            cut_lists
+                .iter()
+                .filter(|c| !c.locked)
+                .all(|c| c.part_indices == vec![1])
+        );
+        assert_eq!(solution[&material].parts[0].quantity, 2);
+    }
+
+    #[test]
+    fn test_relaxation_lower_bound() {
+        let supply = |length: f64, price: f64, max_quantity: i64| Supply {
+            name: String::new(),
+            length: Size::from_meters(length),
+            price: Decimal::from(price),
+            max_quantity,
+            vendor: String::new(),
+            ..Default::default()
+        };
+        let part = |length: f64, quantity: i64| Part {
+            name: String::new(),
+            length: Size::from_meters(length),
+            quantity,
+            assembly: String::new(),
+            angles: EndAngles::default(),
+            splice: None,
+            segment: None,
+            fill: false,
+        };
+
+        // Only the expensive supply is long enough for the part
+        let mut sub_problem = SubProblem {
+            supplies: vec![supply(2.0, 1.0, -1), supply(4.0, 4.0, -1)],
+            parts: vec![part(3.0, 1)],
+            ..Default::default()
+        };
+        assert_eq!(relaxation_lower_bound(&sub_problem), Decimal::from(3.0));
+
+        // Only 4m of the free supply is available
+        sub_problem.supplies = vec![supply(4.0, 0.0, 1), supply(4.0, 4.0, -1)];
+        sub_problem.parts = vec![part(3.0, 2)];
+        assert_eq!(relaxation_lower_bound(&sub_problem), Decimal::from(2.0));
+    }
+
+    #[test]
+    fn test_split_long_parts() {
+        let sub_problem = |min_segment: f64| SubProblem {
+            supplies: vec![Supply {
+                name: String::new(),
+                length: Size::from_meters(8.0),
+                price: Decimal::from(3.5),
+                max_quantity: -1,
+                vendor: String::new(),
+                ..Default::default()
+            }],
+            parts: vec![Part {
+                name: String::from("Baseboard"),
+                length: Size::from_meters(20.0),
+                quantity: 1,
+                assembly: String::new(),
+                angles: EndAngles::default(),
+                splice: Some(Splice {
+                    min_segment: Size::from_meters(min_segment),
+                    allowance: Size::from_meters(0.5),
+                }),
+                segment: None,
+                fill: false,
+            }],
+            ..Default::default()
+        };
+        let lengths = |sub_problem: &SubProblem| -> Vec<f64> {
+            sub_problem
+                .parts
+                .iter()
+                .map(|p| p.length.to_meters_f64())
+                .collect()
+        };
+
+        // Full-length segments, with the joins adding to the total
+        let split = split_long_parts(sub_problem(3.0)).unwrap();
+        assert_eq!(lengths(&split), vec![8.0, 8.0, 5.0]);
+        assert_eq!(split.parts[2].label(), "Baseboard, segment 3 of 3");
+
+        // The last two segments are balanced to respect the minimum
+        let split = split_long_parts(sub_problem(6.0)).unwrap();
+        assert_eq!(lengths(&split), vec![8.0, 7.0, 6.0]);
+        assert_eq!(
+            lengths(&split_long_parts(split).unwrap()),
+            vec![8.0, 7.0, 6.0]
+        );
+
+        assert!(split_long_parts(sub_problem(9.0)).is_err());
+
+        let mut problem = Problem::new();
+        problem.insert(Material::default(), sub_problem(3.0));
+        let solution = NaiveSolver::default().solve(problem, None).unwrap();
+        let boards: usize = solution[&Material::default()]
+            .cut_lists
+            .iter()
+            .map(|c| c.quantity)
+            .sum();
+        assert_eq!(boards, 3);
+    }
+
+    #[test]
+    fn test_fill_offcuts() {
+        let material = Material::default();
+        let part = |name: &str, length: f64, quantity: i64, fill: bool| Part {
+            name: String::from(name),
+            length: Size::from_meters(length),
+            quantity,
+            assembly: String::new(),
+            angles: EndAngles::default(),
+            splice: None,
+            segment: None,
+            fill,
+        };
+        let mut problem = Problem::new();
+        problem.insert(
+            material.clone(),
+            SubProblem {
+                supplies: vec![Supply {
+                    name: String::new(),
+                    length: Size::from_meters(2.0),
+                    price: Decimal::from(3.5),
+                    max_quantity: -1,
+                    vendor: String::new(),
+                    ..Default::default()
+                }],
+                parts: vec![part("Shelf", 1.5, 2, false), part("Shim", 0.2, 1, true)],
+                ..Default::default()
+            },
+        );
+
+        // Each shelf needs its own board, and the shims use up what's left without a third board
+        let solution = NaiveSolver::default().solve(problem, None).unwrap();
+        let boards: usize = solution[&material]
+            .cut_lists
+            .iter()
+            .map(|c| c.quantity)
+            .sum();
+        assert_eq!(boards, 2);
+        assert_eq!(compute_part_production(&solution)[&material], vec![2, 4]);
+    }
+
+    #[test]
+    fn test_end_trim() {
+        let material = Material::default();
+        let sub_problem = |end_trim: f64| SubProblem {
+            supplies: vec![Supply {
+                name: String::new(),
+                length: Size::from_meters(2.0),
+                price: Decimal::from(3.5),
+                max_quantity: -1,
+                vendor: String::new(),
+                ..Default::default()
+            }],
+            parts: vec![Part {
+                name: String::new(),
+                length: Size::from_meters(1.0),
+                quantity: 2,
+                assembly: String::new(),
+                angles: EndAngles::default(),
+                splice: None,
+                segment: None,
+                fill: false,
+            }],
+            end_trim: Size::from_meters(end_trim),
+            ..Default::default()
+        };
+        let solve = |sub_problem: SubProblem| {
+            let mut problem = Problem::new();
+            problem.insert(material.clone(), sub_problem);
+            NaiveSolver::default().solve(problem, None).unwrap()[&material].clone()
+        };
+
+        // Both parts fit on one board until the end is trimmed
+        assert_eq!(
+            solve(sub_problem(0.0)).cut_lists[0].part_indices,
+            vec![0, 0]
+        );
+        let sub_solution = solve(sub_problem(0.25));
+        assert_eq!(sub_solution.cut_lists[0].quantity, 2);
+        assert_eq!(sub_solution.supplies[0].length.to_meters_f64(), 2.0);
+        let layout = CutLayout::from(&sub_solution.cut_lists[0], &sub_solution);
+        assert_eq!(layout.placements[0].start, Fraction::new(1u64, 4u64));
+    }
+
+    #[test]
+    fn test_vendor_fees() {
+        let material = Material::default();
+        let supply = |price: f64, vendor: &str| Supply {
+            name: String::new(),
+            length: Size::from_meters(2.0),
+            price: Decimal::from(price),
+            max_quantity: -1,
+            vendor: String::from(vendor),
+            ..Default::default()
+        };
+        let mut problem = Problem::new();
+        problem.insert(
+            material.clone(),
+            SubProblem {
+                supplies: vec![supply(3.0, "Lumber Yard"), supply(4.0, "Hardware Store")],
+                parts: vec![Part {
+                    name: String::new(),
+                    length: Size::from_meters(1.5),
+                    quantity: 1,
+                    assembly: String::new(),
+                    angles: EndAngles::default(),
+                    splice: None,
+                    segment: None,
+                    fill: false,
+                }],
+                vendors: vec![
+                    Vendor {
+                        name: String::from("Lumber Yard"),
+                        delivery_fee: Decimal::from(10),
+                        ..Default::default()
+                    },
+                    Vendor {
+                        name: String::from("Hardware Store"),
+                        tax_rate: Decimal::from(0.1),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+        );
+
+        // The cheaper board isn't worth the delivery fee
+        let solution = NaiveSolver::default().solve(problem, None).unwrap();
+        assert_eq!(solution[&material].cut_lists[0].supply_index, 1);
+        assert_eq!(compute_total_price(&solution), Decimal::from(4.4));
+    }
+
+    #[test]
+    fn test_bundle_pricing() {
+        let material = Material::default();
+        let supply = Supply {
+            length: Size::from_meters(2.0),
+            price: Decimal::from(3.5),
+            max_quantity: -1,
+            pack_size: 2,
+            bundles: vec![Bundle {
+                quantity: 10,
+                price: Decimal::from(30),
+            }],
+            ..Default::default()
+        };
+        assert_eq!(supply.cost(3), Decimal::from(14));
+        assert_eq!(supply.purchase_quantity(3), 4);
+        assert_eq!(supply.cost(12), Decimal::from(37));
+        assert_eq!(supply.unit_price(), Decimal::from(3));
+
+        let mut problem = Problem::new();
+        problem.insert(
+            material.clone(),
+            SubProblem {
+                supplies: vec![supply],
+                parts: vec![Part {
+                    name: String::new(),
+                    length: Size::from_meters(1.5),
+                    quantity: 9,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        );
+
+        // Nine boards cost more than a bundle of ten
+        let solution = NaiveSolver::default().solve(problem, None).unwrap();
+        assert_eq!(compute_total_price(&solution), Decimal::from(30));
+    }
+
+    #[test]
+    fn test_limited_packs() {
+        let material = Material::default();
+        let packs = Supply {
+            length: Size::from_meters(2.0),
+            price: Decimal::from(1),
+            max_quantity: 5,
+            pack_size: 4,
+            ..Default::default()
+        };
+        assert_eq!(packs.purchase_quantity(4), 4);
+        assert_eq!(packs.shortfall(4), 0);
+
+        // A second pack would go over the stock of five
+        assert_eq!(packs.purchase_quantity(5), 4);
+        assert_eq!(packs.shortfall(5), 1);
+        assert_eq!(packs.cost(5), Decimal::from(4));
+
+        // Only three are left, so the bundle of ten can't be bought
+        let bundles = Supply {
+            length: Size::from_meters(2.0),
+            price: Decimal::from(3.5),
+            max_quantity: 3,
+            bundles: vec![Bundle {
+                quantity: 10,
+                price: Decimal::from(30),
+            }],
+            ..Default::default()
+        };
+        assert_eq!(bundles.cost(3), Decimal::from(10.5));
+        assert_eq!(bundles.purchase_quantity(3), 3);
+        assert_eq!(bundles.shortfall(4), 1);
+
+        // The solver takes the fifth board elsewhere
+        let mut problem = Problem::new();
+        problem.insert(
+            material.clone(),
+            SubProblem {
+                supplies: vec![
+                    packs,
+                    Supply {
+                        length: Size::from_meters(2.0),
+                        price: Decimal::from(5),
+                        max_quantity: -1,
+                        ..Default::default()
+                    },
+                ],
+                parts: vec![Part {
+                    name: String::new(),
+                    length: Size::from_meters(1.5),
+                    quantity: 5,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        );
+        let solution = NaiveSolver::default().solve(problem, None).unwrap();
+        let consumption = compute_supply_consumption(&solution);
+        assert_eq!(consumption[&material], vec![4, 1]);
+        assert_eq!(compute_total_price(&solution), Decimal::from(9));
+    }
+
+    #[test]
+    fn test_solver_ref() {
+        let read = |json| serde_json::from_str::<SolverRef>(json).unwrap();
+
+        // Project files store ids, or indices if they are older
+        assert_eq!(
+            read("\"naive-local-search\""),
+            SolverRef::Id(String::from("naive-local-search"))
+        );
+        assert_eq!(read("1"), SolverRef::Index(1));
+        assert_eq!(read("\"naive\"").info().map(|s| s.id), Some("naive"));
+        assert_eq!(read("1").info().map(|s| s.id), Some("naive-local-search"));
+        assert_eq!(
+            serde_json::to_string(&SolverRef::Id(String::from("naive"))).unwrap(),
+            "\"naive\""
+        );
+
+        // Solvers from newer versions aren't found
+        assert!(read("\"unknown\"").info().is_none());
+        assert!(read("99").info().is_none());
+    }
+}
@@ -0,0 +1,442 @@
+use std::time::{Duration, Instant};
+
+use async_channel::Sender;
+use fraction::{Decimal, Fraction, Zero};
+
+use crate::i18n::gettext_noop;
+use crate::layout::{self, PartShape};
+use crate::modeling::{CutList, SubProblem, SubSolution};
+use crate::solvers::naive_solver::{self, NaiveSolver};
+use crate::solvers::{
+    Capabilities, Message, Objective, ParameterKind, ParameterSpec, ParameterValue, Parameters,
+    Solver, SolverInfo,
+};
+
+const TIME_LIMIT: ParameterSpec = ParameterSpec {
+    key: "time-limit",
+    name: gettext_noop("Time Limit"),
+    description: gettext_noop("Seconds spent improving each material"),
+    kind: ParameterKind::Float {
+        min: 0.1,
+        max: 600.0,
+        digits: 1,
+    },
+    default: ParameterValue::Float(2.0),
+};
+
+const ITERATIONS: ParameterSpec = ParameterSpec {
+    key: "iterations",
+    name: gettext_noop("Iterations"),
+    description: gettext_noop("Maximum number of moves tried for each material"),
+    kind: ParameterKind::Integer {
+        min: 1,
+        max: 10_000_000,
+    },
+    default: ParameterValue::Integer(100_000),
+};
+
+const SEED: ParameterSpec = ParameterSpec {
+    key: "seed",
+    name: gettext_noop("Seed"),
+    description: gettext_noop(
+        "Change to explore different moves (the same seed gives the same results)",
+    ),
+    kind: ParameterKind::Integer {
+        min: 0,
+        max: 1_000_000,
+    },
+    default: ParameterValue::Integer(1),
+};
+
+pub const INFO: SolverInfo = SolverInfo {
+    id: "naive-local-search",
+    name: gettext_noop("Naive + Local Search"),
+    description: gettext_noop(
+        "Improves the naive result by moving and swapping parts and downgrading boards",
+    ),
+    capabilities: Capabilities {
+        one_d: true,
+        two_d: false,
+        optimal: false,
+    },
+    parameters: &[naive_solver::OBJECTIVE, TIME_LIMIT, ITERATIONS, SEED],
+    create,
+};
+
+fn create(parameters: &Parameters) -> Box<dyn Solver + Send> {
+    let objective = Objective::from(naive_solver::OBJECTIVE.choice(parameters));
+    Box::new(WithLocalSearch {
+        solver: NaiveSolver { objective },
+        local_search: LocalSearch {
+            objective,
+            time_limit: Duration::from_secs_f64(TIME_LIMIT.float(parameters)),
+            max_iterations: ITERATIONS.integer(parameters) as usize,
+            seed: SEED.integer(parameters) as u64,
+        },
+    })
+}
+
+/// Runs a solver, then improves each of its `SubSolution`s with local search
+pub struct WithLocalSearch<S: Solver> {
+    pub solver: S,
+    pub local_search: LocalSearch,
+}
+
+impl<S: Solver> Solver for WithLocalSearch<S> {
+    fn solve_sub_problem(
+        &self,
+        sub_problem: SubProblem,
+        sender: &Option<Sender<Message>>,
+    ) -> Result<SubSolution, String> {
+        let sub_solution = self.solver.solve_sub_problem(sub_problem, sender)?;
+        Ok(self.local_search.improve(sub_solution, |progress| {
+            self.send_sub_progress(sender, progress)
+        }))
+    }
+}
+
+/// Post-optimizer that improves any `SubSolution`
+///
+/// Tries random moves and keeps those that do not make the plan worse:
+/// - Draining a board by moving all of its parts onto other boards
+/// - Moving a single part to another board
+/// - Swapping two parts between boards
+///
+/// After every move, the affected boards are downgraded to the best supply that still fits.
+pub struct LocalSearch {
+    pub objective: Objective,
+    pub time_limit: Duration,
+    pub max_iterations: usize,
+    pub seed: u64,
+}
+
+/// A single item of a supply and the parts cut from it
+#[derive(Debug, Clone)]
+struct Board {
+    supply_index: usize,
+    part_indices: Vec<usize>,
+}
+
+/// Minimal xorshift generator, so results are reproducible for a given seed
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // The state must never be zero
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    /// Returns a number in `0..n` (`n` must be positive)
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
+
+/// Problem data needed to evaluate boards
+struct Context<'a> {
+    sub_solution: &'a SubSolution,
+    objective: Objective,
+    part_shapes: Vec<PartShape>,
+    supply_lengths: Vec<Fraction>,
+    blade_width: Fraction,
+}
+
+impl Context<'_> {
+    /// Length taken up by parts, with a kerf between each (but not after the last)
+    fn used_length(&self, part_indices: &[usize]) -> Fraction {
+        let shapes = part_indices.iter().map(|i| &self.part_shapes[*i]);
+        layout::used_length(shapes, self.blade_width)
+    }
+
+    fn fits(&self, supply_index: usize, part_indices: &[usize]) -> bool {
+        self.used_length(part_indices) <= self.supply_lengths[supply_index]
+    }
+
+    /// Primary and secondary costs of a supply item under the objective (at its best bundle price)
+    fn supply_score(&self, supply_index: usize) -> (f64, f64) {
+        let price: f64 = self.sub_solution.supplies[supply_index]
+            .unit_price()
+            .try_into()
+            .unwrap();
+        let length: f64 = self.supply_lengths[supply_index].try_into().unwrap();
+        match self.objective {
+            Objective::Cost => (price, length),
+            Objective::Waste => (length, price),
+        }
+    }
+
+    /// Prices the boards from each supply together, so moves that complete a bundle pay off
+    fn score(&self, boards: &[Board]) -> (f64, f64) {
+        let mut counts = vec![0; self.supply_lengths.len()];
+        let mut length = 0.0;
+        for board in boards {
+            counts[board.supply_index] += 1;
+            let board_length: f64 = self.supply_lengths[board.supply_index].try_into().unwrap();
+            length += board_length;
+        }
+        let price: f64 = self
+            .sub_solution
+            .supplies
+            .iter()
+            .zip(counts)
+            .map(|(supply, count)| supply.cost(count))
+            .fold(Decimal::zero(), |a, b| a + b)
+            .try_into()
+            .unwrap();
+        match self.objective {
+            Objective::Cost => (price, length),
+            Objective::Waste => (length, price),
+        }
+    }
+
+    fn is_available(&self, supply_index: usize, boards: &[Board]) -> bool {
+        let supply = &self.sub_solution.supplies[supply_index];
+        let count = boards
+            .iter()
+            .filter(|b| b.supply_index == supply_index)
+            .count();
+
+        // Packs and bundles may not fit in a limited stock (see `Supply::shortfall`)
+        (supply.max_quantity == -1 || (count as i64) < supply.max_quantity)
+            && supply.shortfall(count + 1) == 0
+    }
+
+    /// Removes empty boards and moves the given boards to the best supply that fits
+    fn tidy(&self, boards: &mut Vec<Board>, changed: &[usize]) {
+        for i in changed.iter().copied() {
+            if i >= boards.len() || boards[i].part_indices.is_empty() {
+                continue;
+            }
+            let mut best = boards[i].supply_index;
+            for j in 0..self.supply_lengths.len() {
+                if self.supply_score(j) < self.supply_score(best)
+                    && self.fits(j, &boards[i].part_indices)
+                    && self.is_available(j, boards)
+                {
+                    best = j;
+                }
+            }
+            boards[i].supply_index = best;
+        }
+        boards.retain(|b| !b.part_indices.is_empty());
+    }
+}
+
+impl LocalSearch {
+    /// Returns a solution that is at least as good as `sub_solution`
+    ///
+    /// `report_progress` is called with the fraction of the budget used.
+    pub fn improve(
+        &self,
+        sub_solution: SubSolution,
+        mut report_progress: impl FnMut(f64),
+    ) -> SubSolution {
+        let context = Context {
+            sub_solution: &sub_solution,
+            objective: self.objective,
+            part_shapes: sub_solution
+                .parts
+                .iter()
+                .map(|p| PartShape::from(p, &sub_solution.depth))
+                .collect(),
+            supply_lengths: sub_solution
+                .supplies
+                .iter()
+                .map(|s| s.length.to_meters())
+                .collect(),
+            blade_width: sub_solution.blade_width.to_meters(),
+        };
+
+        let mut boards = Vec::new();
+        for cut_list in sub_solution.cut_lists.iter() {
+            for _ in 0..cut_list.quantity {
+                boards.push(Board {
+                    supply_index: cut_list.supply_index,
+                    part_indices: cut_list.part_indices.clone(),
+                });
+            }
+        }
+        let all: Vec<usize> = (0..boards.len()).collect();
+        context.tidy(&mut boards, &all);
+        let mut score = context.score(&boards);
+
+        let start = Instant::now();
+        let mut rng = Rng::new(self.seed);
+        for iteration in 0..self.max_iterations {
+            if boards.len() < 2 {
+                break;
+            }
+
+            // Checking the clock is relatively slow
+            if iteration % 256 == 0 {
+                let elapsed = start.elapsed().as_secs_f64() / self.time_limit.as_secs_f64();
+                let iterations = iteration as f64 / self.max_iterations as f64;
+                if elapsed >= 1.0 {
+                    break;
+                }
+                report_progress(elapsed.max(iterations));
+            }
+
+            let mut candidate = boards.clone();
+            let a = rng.below(candidate.len());
+            let mut b = rng.below(candidate.len() - 1);
+            if b >= a {
+                b += 1;
+            }
+            let moved = match rng.below(3) {
+                0 => Self::drain(&context, &mut candidate, a),
+                1 => Self::move_part(&context, &mut candidate, a, b, &mut rng),
+                _ => Self::swap_parts(&context, &mut candidate, a, b, &mut rng),
+            };
+            if moved {
+                let all: Vec<usize> = (0..candidate.len()).collect();
+                context.tidy(&mut candidate, &all);
+                let candidate_score = context.score(&candidate);
+                if candidate_score <= score {
+                    boards = candidate;
+                    score = candidate_score;
+                }
+            }
+        }
+        report_progress(1.0);
+
+        let cut_lists = boards
+            .into_iter()
+            .map(|mut board| {
+                // Sorting lets identical boards be grouped into one cut list, but angled parts
+                // may only fit in their original order
+                let mut sorted = board.part_indices.clone();
+                sorted.sort();
+                if context.fits(board.supply_index, &sorted) {
+                    board.part_indices = sorted;
+                }
+                CutList {
+                    supply_index: board.supply_index,
+                    part_indices: board.part_indices,
+                    quantity: 1,
+                    locked: false,
+                }
+            })
+            .collect();
+        SubSolution {
+            cut_lists,
+            ..sub_solution
+        }
+    }
+
+    /// Moves every part on board `a` to the first other board with room
+    fn drain(context: &Context, boards: &mut [Board], a: usize) -> bool {
+        let parts = std::mem::take(&mut boards[a].part_indices);
+        for part_index in parts {
+            let target = (0..boards.len()).find(|i| {
+                let mut part_indices = boards[*i].part_indices.clone();
+                part_indices.push(part_index);
+                *i != a && context.fits(boards[*i].supply_index, &part_indices)
+            });
+            match target {
+                Some(i) => boards[i].part_indices.push(part_index),
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Moves a random part from board `a` to board `b`
+    fn move_part(
+        context: &Context,
+        boards: &mut [Board],
+        a: usize,
+        b: usize,
+        rng: &mut Rng,
+    ) -> bool {
+        let i = rng.below(boards[a].part_indices.len());
+        let part_index = boards[a].part_indices.remove(i);
+        boards[b].part_indices.push(part_index);
+        context.fits(boards[b].supply_index, &boards[b].part_indices)
+    }
+
+    /// Swaps a random part on board `a` with a random part on board `b`
+    fn swap_parts(
+        context: &Context,
+        boards: &mut [Board],
+        a: usize,
+        b: usize,
+        rng: &mut Rng,
+    ) -> bool {
+        let i = rng.below(boards[a].part_indices.len());
+        let j = rng.below(boards[b].part_indices.len());
+        let part_a = boards[a].part_indices[i];
+        boards[a].part_indices[i] = boards[b].part_indices[j];
+        boards[b].part_indices[j] = part_a;
+        context.fits(boards[a].supply_index, &boards[a].part_indices)
+            && context.fits(boards[b].supply_index, &boards[b].part_indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fraction::Decimal;
+
+    use super::*;
+    use crate::modeling::{EndAngles, Part, Supply};
+    use crate::size::Size;
+
+    #[test]
+    fn test_local_search() {
+        let supply = |length: f64, price: f64, max_quantity: i64| Supply {
+            name: String::new(),
+            length: Size::from_meters(length),
+            price: Decimal::from(price),
+            max_quantity,
+            vendor: String::new(),
+            ..Default::default()
+        };
+        let part = Part {
+            name: String::new(),
+            length: Size::from_meters(3.0),
+            quantity: 3,
+            assembly: String::new(),
+            angles: EndAngles::default(),
+            splice: None,
+            segment: None,
+            fill: false,
+        };
+
+        // One part per board, with room for two on each 8m board
+        let cut_list = CutList {
+            supply_index: 0,
+            part_indices: vec![0],
+            quantity: 3,
+            locked: false,
+        };
+        let sub_solution = SubSolution {
+            supplies: vec![supply(8.0, 10.0, -1), supply(3.0, 4.0, 1)],
+            parts: vec![part],
+            cut_lists: vec![cut_list],
+            blade_width: Size::from_meters(0.5),
+            depth: Size::default(),
+            end_trim: Size::default(),
+            vendors: Vec::new(),
+            lower_bound: None,
+        };
+        let local_search = LocalSearch {
+            objective: Objective::Cost,
+            time_limit: Duration::from_secs(10),
+            max_iterations: 1000,
+            seed: 1,
+        };
+        let improved = local_search.improve(sub_solution, |_| ());
+
+        // Two parts share an 8m board and the third moves to the only 3m board
+        let mut boards: Vec<_> = improved
+            .cut_lists
+            .iter()
+            .map(|c| (c.supply_index, c.part_indices.clone(), c.quantity))
+            .collect();
+        boards.sort();
+        assert_eq!(boards, vec![(0, vec![0, 0], 1), (1, vec![0], 1)]);
+    }
+}
@@ -1,10 +1,47 @@
 use async_channel::Sender;
-use fraction::Decimal;
+use fraction::{Decimal, Fraction};
+use gettextrs::gettext;
 
+use crate::i18n::gettext_noop;
+use crate::layout::{Packer, PartShape};
 use crate::modeling::{CutList, SubProblem, SubSolution};
-use crate::solvers::{Message, Solver};
+use crate::solvers::{
+    Capabilities, Message, Objective, ParameterKind, ParameterSpec, ParameterValue, Parameters,
+    Solver, SolverInfo,
+};
 
-pub struct NaiveSolver {}
+pub const OBJECTIVE: ParameterSpec = ParameterSpec {
+    key: "objective",
+    name: gettext_noop("Minimize"),
+    description: gettext_noop("Which supply to start when a part does not fit on any started one"),
+    kind: ParameterKind::Choice(Objective::NAMES),
+    default: ParameterValue::Choice(0),
+};
+
+pub const INFO: SolverInfo = SolverInfo {
+    id: "naive",
+    name: gettext_noop("Naive"),
+    description: gettext_noop("Places each part on the first item with room, in entry order"),
+    capabilities: Capabilities {
+        one_d: true,
+        two_d: false,
+        optimal: false,
+    },
+    parameters: &[OBJECTIVE],
+    create,
+};
+
+fn create(parameters: &Parameters) -> Box<dyn Solver + Send> {
+    Box::new(NaiveSolver {
+        objective: Objective::from(OBJECTIVE.choice(parameters)),
+    })
+}
+
+#[derive(Default)]
+pub struct NaiveSolver {
+    /// `Cost` starts the cheapest supply that fits, `Waste` starts the shortest
+    pub objective: Objective,
+}
 
 impl Solver for NaiveSolver {
     fn solve_sub_problem(
@@ -16,58 +53,80 @@ impl Solver for NaiveSolver {
             parts,
             supplies,
             blade_width,
+            depth,
+            end_trim,
+            vendors,
+            ..
         } = sub_problem;
         let mut cut_lists = Vec::<CutList>::new();
         let mut supply_consumption = vec![0; supplies.len()];
-        let mut partial_lengths = Vec::new();
+
+        // Tracks the room left on the item of each cut list
+        let mut packers = Vec::<Packer>::new();
 
         let mut progress = 0.0;
         let total_count = parts.iter().map(|p| p.quantity).sum::<i64>();
 
         for (i, part) in parts.iter().enumerate() {
-            let part_meters = part.length.to_meters();
+            let shape = PartShape::from(part, &depth);
             for _ in 0..part.quantity {
                 let mut done = false;
 
                 // Prioritize cutting from objects already in the cut list
                 for (j, cut_item) in cut_lists.iter_mut().enumerate() {
-                    if part.length.to_meters() <= partial_lengths[j] {
+                    let mut packer = packers[j].clone();
+                    packer.place(i, &shape);
+                    if packer.used() <= supplies[cut_item.supply_index].length.to_meters() {
                         cut_item.part_indices.push(i);
-                        partial_lengths[j] -= part_meters + blade_width.to_meters();
+                        packers[j] = packer;
                         done = true;
                         break;
                     }
                 }
 
-                // Then pull from the cheapest supply with large-enough items
+                // Then pull from the best supply with large-enough items
                 if !done {
+                    let mut packer = Packer::new(blade_width.to_meters());
+                    packer.place(i, &shape);
                     let mut best_supply = 0;
                     let mut best_price = Decimal::infinity();
+                    let mut best_length = Fraction::infinity();
                     for (i, supply) in supplies.iter().enumerate() {
-                        if (part_meters <= supply.length.to_meters())
+                        let length = supply.length.to_meters();
+
+                        // Price of one more item, which can be lower once a bundle is worth buying
+                        let bought = supply_consumption[i] as usize;
+                        let price = supply.cost(bought + 1) - supply.cost(bought);
+                        let is_better = match self.objective {
+                            Objective::Cost => price < best_price,
+                            Objective::Waste => {
+                                (length < best_length)
+                                    || (length == best_length && price < best_price)
+                            }
+                        };
+                        if (packer.used() <= length)
                             && ((supply_consumption[i] < supply.max_quantity)
                                 || (supply.max_quantity == -1))
-                            && (supply.price < best_price)
+                            && supply.shortfall(bought + 1) == 0
+                            && is_better
                         {
                             best_supply = i;
-                            best_price = supply.price;
+                            best_price = price;
+                            best_length = length;
                         }
                     }
                     if best_price == Decimal::infinity() {
                         // May be triggered even if valid solutions exist
-                        return Err(String::from("No materials available with sufficient size"));
+                        return Err(gettext("No materials available with sufficient size"));
                     } else {
                         cut_lists.push(CutList {
                             supply_index: best_supply,
                             part_indices: vec![i],
                             quantity: 1,
+                            locked: false,
                         });
                         supply_consumption[best_supply] += 1;
-                        partial_lengths.push(
-                            supplies[best_supply].length.to_meters()
-                                - part_meters
-                                - blade_width.to_meters(),
-                        );
+                        packers.push(packer);
                     }
                 }
 
@@ -80,6 +139,11 @@ impl Solver for NaiveSolver {
             cut_lists,
             supplies,
             parts,
+            blade_width,
+            depth,
+            end_trim,
+            vendors,
+            lower_bound: None,
         })
     }
 }
@@ -90,7 +154,7 @@ mod tests {
     use fraction::Zero;
 
     use super::*;
-    use crate::modeling::{Dimension, Material, Part, Problem, Supply};
+    use crate::modeling::{Dimension, EndAngles, Material, Part, Problem, Supply};
     use crate::size::Size;
     use crate::utils::compute_total_price;
 
@@ -106,12 +170,16 @@ mod tests {
                 length: Size::from_meters(8.0),
                 price: Decimal::zero(),
                 max_quantity: 1,
+                vendor: String::new(),
+                ..Default::default()
             },
             Supply {
                 name: String::new(),
                 length: Size::from_meters(8.0),
                 price: Decimal::from(3.5),
                 max_quantity: -1,
+                vendor: String::new(),
+                ..Default::default()
             },
         ];
         let parts = vec![
@@ -119,11 +187,21 @@ mod tests {
                 name: String::new(),
                 length: Size::from_meters(3.0),
                 quantity: 3,
+                assembly: String::new(),
+                angles: EndAngles::default(),
+                splice: None,
+                segment: None,
+                fill: false,
             },
             Part {
                 name: String::new(),
                 length: Size::from_meters(1.5),
                 quantity: 1,
+                assembly: String::new(),
+                angles: EndAngles::default(),
+                splice: None,
+                segment: None,
+                fill: false,
             },
         ];
         let blade_width = Size::from_meters(0.0);
@@ -134,9 +212,13 @@ mod tests {
                 supplies,
                 parts,
                 blade_width,
+                depth: Size::default(),
+                end_trim: Size::default(),
+                vendors: Vec::new(),
+                locked_cut_lists: Vec::new(),
             },
         );
-        let solution = NaiveSolver {}.solve(problem, None).unwrap();
+        let solution = NaiveSolver::default().solve(problem, None).unwrap();
         assert_eq!(compute_total_price(&solution), Decimal::from(3.5));
     }
 }
@@ -1,11 +1,31 @@
+pub mod advisor_dialog;
+pub mod assemblies_dialog;
+pub mod assembly;
+pub mod csv_import;
+pub mod cut_plan;
+pub mod cut_plan_dialog;
 pub mod dialogs;
 pub mod display;
 pub mod entry;
 pub mod entry_pane;
 pub mod font_row;
+pub mod labels;
+pub mod library;
+pub mod library_dialog;
+pub mod material_settings;
+pub mod materials_dialog;
+pub mod number_format;
+pub mod number_format_dialog;
 pub mod preferences_dialog;
+pub mod project;
+pub mod project_details;
+pub mod project_details_dialog;
+pub mod recent_projects;
 pub mod solver_overlay;
 pub mod solver_pane;
+pub mod start_page;
 pub mod unit_row;
 pub mod utils;
+pub mod vendor_settings;
+pub mod vendors_dialog;
 pub mod window;
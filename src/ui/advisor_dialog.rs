@@ -0,0 +1,244 @@
+use std::cell::{Cell, RefCell};
+use std::sync::OnceLock;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use gtk::glib::{Object, clone, subclass::InitializingObject, subclass::Signal};
+use gtk::{CompositeTemplate, glib};
+
+use super::utils::format_price;
+use crate::advisor::{MAX_CANDIDATES, StockAdvice, StockMix, rank_mixes};
+use crate::i18n::i18n_f;
+use crate::modeling::Material;
+use crate::size::{FractionFormat, Size};
+use crate::solvers::Objective;
+
+mod imp {
+    use super::*;
+
+    // Object holding the state
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/com/ohmm-software/Chop-Chop/advisor_dialog.ui")]
+    pub struct AdvisorDialog {
+        #[template_child]
+        pub(super) page: TemplateChild<adw::PreferencesPage>,
+        #[template_child]
+        pub(super) rank_field: TemplateChild<adw::ComboRow>,
+
+        // Mixes tried for each material
+        pub(super) advice: RefCell<Vec<StockAdvice>>,
+
+        // Materials whose supplies were already replaced (their indices are no longer valid)
+        pub(super) used: RefCell<Vec<Material>>,
+
+        // Supplies to keep (read back by the window on "mix-chosen")
+        pub(super) chosen: RefCell<Option<(Material, Vec<usize>)>>,
+
+        // Used to format lengths and prices
+        pub(super) format: RefCell<Option<FractionFormat>>,
+        pub(super) price_precision: Cell<u32>,
+
+        // One group per material (removed whenever the groups are rebuilt)
+        pub(super) groups: RefCell<Vec<adw::PreferencesGroup>>,
+    }
+
+    // The central trait for subclassing a GObject
+    #[glib::object_subclass]
+    impl ObjectSubclass for AdvisorDialog {
+        const NAME: &'static str = "ChopChopAdvisorDialog";
+        type Type = super::AdvisorDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            // Required for CompositeTemplate
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            // Required for CompositeTemplate
+            obj.init_template();
+        }
+    }
+
+    // Trait shared by all GObjects
+    impl ObjectImpl for AdvisorDialog {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+
+            // The parent window watches this signal to write back the chosen supplies
+            SIGNALS.get_or_init(|| vec![Signal::builder("mix-chosen").build()])
+        }
+    }
+
+    // Trait shared by all widgets
+    impl WidgetImpl for AdvisorDialog {}
+
+    // Trait shared by all Adwaita dialogs
+    impl AdwDialogImpl for AdvisorDialog {}
+}
+
+glib::wrapper! {
+    pub struct AdvisorDialog(ObjectSubclass<imp::AdvisorDialog>)
+        @extends adw::Dialog, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl AdvisorDialog {
+    pub fn new(advice: Vec<StockAdvice>, format: FractionFormat, price_precision: u32) -> Self {
+        let dialog: Self = Object::builder().build();
+        let imp = dialog.imp();
+        imp.advice.replace(advice);
+        imp.format.replace(Some(format));
+        imp.price_precision.set(price_precision);
+        let names: Vec<String> = Objective::NAMES.iter().map(|n| gettext(*n)).collect();
+        let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+        imp.rank_field
+            .set_model(Some(&gtk::StringList::new(&names)));
+        imp.rank_field.connect_selected_notify(clone!(
+            #[weak]
+            dialog,
+            move |rank_field| {
+                let objective = Objective::from(rank_field.selected() as usize);
+                for advice in dialog.imp().advice.borrow_mut().iter_mut() {
+                    rank_mixes(&mut advice.mixes, objective);
+                }
+                dialog.update_groups();
+            }
+        ));
+        dialog.update_groups();
+        dialog
+    }
+
+    /// The material whose supplies to replace, and the indices of its supplies to keep
+    pub fn chosen_mix(&self) -> Option<(Material, Vec<usize>)> {
+        self.imp().chosen.borrow().clone()
+    }
+
+    /// Names a mix by its supply lengths (e.g., "All 12 ft" or "8 ft + 12 ft")
+    fn describe_mix(&self, advice: &StockAdvice, mix: &StockMix) -> String {
+        let format = self.format();
+        let lengths: Vec<_> = mix
+            .supply_indices
+            .iter()
+            .map(|i| advice.supplies[*i].length.format(&format))
+            .collect();
+        if lengths.len() == 1 {
+            i18n_f("All {}", &[&lengths[0]])
+        } else {
+            lengths.join(" + ")
+        }
+    }
+
+    fn format(&self) -> FractionFormat {
+        self.imp().format.borrow().clone().unwrap()
+    }
+
+    fn signal_mix_chosen(&self) {
+        self.emit_by_name::<()>("mix-chosen", &[]);
+    }
+
+    /// Keeps the supplies in the mix and those used in every mix
+    fn use_mix(&self, i: usize, j: usize) {
+        let imp = self.imp();
+        let (material, keep) = {
+            let advice = &imp.advice.borrow()[i];
+            let mix = &advice.mixes[j];
+            let keep: Vec<usize> = (0..advice.supplies.len())
+                .filter(|k| mix.supply_indices.contains(k) || advice.kept.contains(k))
+                .collect();
+            (advice.material.clone(), keep)
+        };
+        imp.used.borrow_mut().push(material.clone());
+        imp.chosen.replace(Some((material, keep)));
+        self.signal_mix_chosen();
+        self.update_groups();
+    }
+
+    fn update_groups(&self) {
+        let imp = self.imp();
+        for group in imp.groups.take() {
+            imp.page.remove(&group);
+        }
+        let format = self.format();
+        let precision = imp.price_precision.get();
+        let advice = imp.advice.borrow().clone();
+        if advice.is_empty() {
+            let group = adw::PreferencesGroup::new();
+            let row = adw::ActionRow::builder()
+                .title(gettext("No 1D materials in this project"))
+                .build();
+            row.add_css_class("dim-label");
+            group.add(&row);
+            imp.page.add(&group);
+            imp.groups.borrow_mut().push(group);
+        }
+
+        for (i, advice) in advice.iter().enumerate() {
+            let group = adw::PreferencesGroup::builder()
+                .title(glib::markup_escape_text(&advice.material.name))
+                .build();
+            let is_used = imp.used.borrow().contains(&advice.material);
+            if !advice.left_out.is_empty() {
+                group.set_description(Some(&i18n_f(
+                    "Only the {} supplies with the lowest price per length were compared",
+                    &[&MAX_CANDIDATES.to_string()],
+                )));
+            }
+            if is_used {
+                group.set_description(Some(&gettext("Supplies replaced with the chosen mix")));
+                group.set_sensitive(false);
+            }
+            if advice.mixes.is_empty() {
+                let has_candidates = advice.kept.len() < advice.supplies.len();
+                let row = adw::ActionRow::builder()
+                    .title(if has_candidates {
+                        gettext("No mix of supplies can make every part")
+                    } else {
+                        gettext("No purchased supplies to compare")
+                    })
+                    .build();
+                row.add_css_class("dim-label");
+                group.add(&row);
+            }
+
+            for (j, mix) in advice.mixes.iter().enumerate() {
+                let unit = &advice.supplies[mix.supply_indices[0]].length.unit;
+                let waste = Size::from_meters_in(mix.waste, unit);
+                let mut subtitle = i18n_f(
+                    "{} · {} of offcuts",
+                    &[&format_price(mix.price, precision), &waste.format(&format)],
+                );
+                if j == 0 {
+                    subtitle = i18n_f("Recommended · {}", &[&subtitle]);
+                }
+                let row = adw::ActionRow::builder()
+                    .title(format!("{}. {}", j + 1, self.describe_mix(advice, mix)))
+                    .subtitle(subtitle)
+                    .use_markup(false)
+                    .build();
+                let use_button = gtk::Button::builder()
+                    .label(gettext("Use"))
+                    .tooltip_text(gettext(
+                        "Replace the supplies of this material with this mix",
+                    ))
+                    .valign(gtk::Align::Center)
+                    .build();
+                if j == 0 {
+                    use_button.add_css_class("suggested-action");
+                }
+                use_button.connect_clicked(clone!(
+                    #[weak(rename_to = dialog)]
+                    self,
+                    move |_| {
+                        dialog.use_mix(i, j);
+                    }
+                ));
+                row.add_suffix(&use_button);
+                group.add(&row);
+            }
+            imp.page.add(&group);
+            imp.groups.borrow_mut().push(group);
+        }
+    }
+}
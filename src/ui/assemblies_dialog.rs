@@ -0,0 +1,295 @@
+use std::cell::{Cell, RefCell};
+use std::sync::OnceLock;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib::{Object, clone, subclass::InitializingObject, subclass::Signal};
+use gtk::{CompositeTemplate, glib};
+
+use super::assembly::{Assembly, assembly_instances};
+use super::entry_pane::EntryPane;
+use super::utils::*;
+use crate::i18n::{i18n_f, ni18n_f};
+
+mod imp {
+    use super::*;
+
+    // Object holding the state
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/com/ohmm-software/Chop-Chop/assemblies_dialog.ui")]
+    pub struct AssembliesDialog {
+        // Entry fields
+        #[template_child]
+        pub(super) name_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) parent_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) quantity_field: TemplateChild<adw::SpinRow>,
+
+        // Used to switch between the assembly list and a placeholder
+        #[template_child]
+        pub(super) content_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) assembly_list: TemplateChild<gtk::ListBox>,
+
+        // Assemblies being edited (read back by the window on "assemblies-updated")
+        pub(super) assemblies: RefCell<Vec<Assembly>>,
+
+        // Index of the assembly being edited
+        pub(super) selection: Cell<Option<usize>>,
+
+        // Pane holding the parts that belong to the assemblies
+        pub(super) parts_pane: RefCell<Option<EntryPane>>,
+    }
+
+    // The central trait for subclassing a GObject
+    #[glib::object_subclass]
+    impl ObjectSubclass for AssembliesDialog {
+        const NAME: &'static str = "ChopChopAssembliesDialog";
+        type Type = super::AssembliesDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            // Required for CompositeTemplate
+            klass.bind_template();
+
+            // Set up widget actions
+            klass.install_action("assemblies.save", None, |dialog, _, _| {
+                dialog.save_assembly()
+            });
+            klass.install_action("assemblies.delete", None, |dialog, _, _| {
+                dialog.delete_assembly()
+            });
+            klass.install_action("assemblies.new", None, |dialog, _, _| {
+                dialog.set_selection(None)
+            });
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            // Required for CompositeTemplate
+            obj.init_template();
+        }
+    }
+
+    // Trait shared by all GObjects
+    impl ObjectImpl for AssembliesDialog {
+        // Called when the object is constructed
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+            obj.setup_callbacks();
+        }
+
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+
+            // The parent window watches this signal to store the new assemblies
+            SIGNALS.get_or_init(|| vec![Signal::builder("assemblies-updated").build()])
+        }
+    }
+
+    // Trait shared by all widgets
+    impl WidgetImpl for AssembliesDialog {}
+
+    // Trait shared by all Adwaita dialogs
+    impl AdwDialogImpl for AssembliesDialog {}
+}
+
+glib::wrapper! {
+    pub struct AssembliesDialog(ObjectSubclass<imp::AssembliesDialog>)
+        @extends adw::Dialog, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl AssembliesDialog {
+    pub fn new(assemblies: Vec<Assembly>, parts_pane: &EntryPane) -> Self {
+        let dialog: Self = Object::builder().build();
+        let imp = dialog.imp();
+        imp.assemblies.replace(assemblies);
+        imp.parts_pane.replace(Some(parts_pane.clone()));
+        dialog.update_list();
+        dialog.set_selection(None);
+        dialog
+    }
+
+    pub fn assemblies(&self) -> Vec<Assembly> {
+        self.imp().assemblies.borrow().clone()
+    }
+
+    /// Returns the assembly described by the entry fields
+    fn assembly(&self) -> Assembly {
+        let imp = self.imp();
+        Assembly {
+            name: imp.name_field.text().trim().to_string(),
+            parent: imp.parent_field.text().trim().to_string(),
+            quantity: imp.quantity_field.value() as u32,
+        }
+    }
+
+    /// Deletes the selected assembly, moving its parts and sub-assemblies to its parent
+    fn delete_assembly(&self) {
+        let imp = self.imp();
+        if let Some(i) = imp.selection.get() {
+            let removed = imp.assemblies.borrow_mut().remove(i);
+            for assembly in imp.assemblies.borrow_mut().iter_mut() {
+                if assembly.parent == removed.name {
+                    assembly.parent = removed.parent.clone();
+                }
+            }
+            self.parts_pane()
+                .rename_assembly(&removed.name, &removed.parent);
+            self.signal_assemblies_updated();
+            self.update_list();
+            self.set_selection(None);
+        }
+    }
+
+    fn parts_pane(&self) -> EntryPane {
+        self.imp().parts_pane.borrow().clone().unwrap()
+    }
+
+    fn save_assembly(&self) {
+        let imp = self.imp();
+        let assembly = self.assembly();
+        let i = {
+            let mut assemblies = imp.assemblies.borrow_mut();
+            match imp.selection.get() {
+                Some(i) => {
+                    // Carry a rename over to sub-assemblies and parts
+                    let old_name = assemblies[i].name.clone();
+                    if old_name != assembly.name {
+                        for other in assemblies.iter_mut() {
+                            if other.parent == old_name {
+                                other.parent = assembly.name.clone();
+                            }
+                        }
+                        self.parts_pane().rename_assembly(&old_name, &assembly.name);
+                    }
+                    assemblies[i] = assembly;
+                    i
+                }
+                None => {
+                    assemblies.push(assembly);
+                    assemblies.len() - 1
+                }
+            }
+        };
+        self.signal_assemblies_updated();
+        self.update_list();
+        self.set_selection(Some(i));
+    }
+
+    fn set_selection(&self, selection: Option<usize>) {
+        let imp = self.imp();
+        imp.selection.set(selection);
+        match selection.and_then(|i| imp.assembly_list.row_at_index(i as i32)) {
+            Some(row) => imp.assembly_list.select_row(Some(&row)),
+            None => imp.assembly_list.unselect_all(),
+        }
+        self.update_fields();
+        self.action_set_enabled("assemblies.delete", selection.is_some());
+    }
+
+    fn setup_callbacks(&self) {
+        let imp = self.imp();
+        for field in [&imp.name_field, &imp.parent_field] {
+            field.connect_changed(clone!(
+                #[weak(rename_to = dialog)]
+                self,
+                move |_| {
+                    dialog.validate_all_entries();
+                }
+            ));
+        }
+        imp.assembly_list.connect_row_activated(clone!(
+            #[weak(rename_to = dialog)]
+            self,
+            move |_, row| {
+                dialog.set_selection(Some(row.index() as usize));
+            }
+        ));
+    }
+
+    fn signal_assemblies_updated(&self) {
+        self.emit_by_name::<()>("assemblies-updated", &[]);
+    }
+
+    fn update_fields(&self) {
+        let imp = self.imp();
+        let assemblies = imp.assemblies.borrow();
+        let default_assembly = Assembly {
+            quantity: 1,
+            ..Default::default()
+        };
+        let assembly = match imp.selection.get() {
+            Some(i) => &assemblies[i],
+            None => &default_assembly,
+        };
+        imp.name_field.set_text(&assembly.name);
+        imp.parent_field.set_text(&assembly.parent);
+        imp.quantity_field.set_value(assembly.quantity as f64);
+    }
+
+    fn update_list(&self) {
+        let imp = self.imp();
+        let part_entries = self.parts_pane().entry_data();
+
+        imp.assembly_list.remove_all();
+        for assembly in imp.assemblies.borrow().iter() {
+            let mut details = vec![i18n_f("Quantity {}", &[&assembly.quantity.to_string()])];
+            if !assembly.parent.is_empty() {
+                details.push(i18n_f("Part of {}", &[&assembly.parent]));
+            }
+            let n_parts = part_entries
+                .iter()
+                .filter(|e| e.assembly == assembly.name)
+                .count();
+            details.push(ni18n_f(
+                "{} part",
+                "{} parts",
+                n_parts as u32,
+                &[&n_parts.to_string()],
+            ));
+
+            let row = adw::ActionRow::builder()
+                .title(&assembly.name)
+                .subtitle(details.join(" · "))
+                .use_markup(false)
+                .activatable(true)
+                .build();
+            imp.assembly_list.append(&row);
+        }
+
+        let name = if imp.assemblies.borrow().is_empty() {
+            "placeholder"
+        } else {
+            "nonempty"
+        };
+        imp.content_stack.set_visible_child_name(name);
+    }
+
+    fn validate_all_entries(&self) {
+        let mut all_valid = true;
+        let imp = self.imp();
+        let selection = imp.selection.get();
+
+        // The edited assembly must have a unique name and must not end up inside itself
+        let assembly = self.assembly();
+        let mut assemblies = imp.assemblies.borrow().clone();
+        let name_taken = assemblies
+            .iter()
+            .enumerate()
+            .any(|(i, a)| Some(i) != selection && a.name == assembly.name);
+        match selection {
+            Some(i) => assemblies[i] = assembly.clone(),
+            None => assemblies.push(assembly.clone()),
+        }
+        all_valid &= validate_entry(&imp.name_field.get(), None, |e| {
+            !e.text().trim().is_empty() && !name_taken
+        });
+        all_valid &= validate_entry(&imp.parent_field.get(), None, |_| {
+            assembly.name.is_empty() || assembly_instances(&assemblies, &assembly.name).is_ok()
+        });
+        self.action_set_enabled("assemblies.save", all_valid);
+    }
+}
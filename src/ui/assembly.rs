@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::i18n_f;
+
+/// A named group of parts (and nested assemblies) that is built `quantity` times
+///
+/// Examples:
+/// - "Drawer", built 3 times within "Cabinet"
+/// - "Cabinet", built twice (top level)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Assembly {
+    pub name: String,
+
+    /// The name of the enclosing assembly (empty for a top-level assembly)
+    pub parent: String,
+
+    /// The number of times the assembly is built within each instance of its parent
+    pub quantity: u32,
+}
+
+/// Lists a label for each instance of an assembly (e.g., "Cabinet 1 / Drawer 2")
+///
+/// Instance numbers are omitted for assemblies that are only built once.
+pub fn assembly_instances(assemblies: &[Assembly], name: &str) -> Result<Vec<String>, String> {
+    // Walk up to the top-level assembly
+    let mut chain: Vec<&Assembly> = Vec::new();
+    let mut current = name;
+    loop {
+        if chain.iter().any(|a| a.name == current) {
+            return Err(i18n_f("Assembly \"{}\" is contained in itself", &[current]));
+        }
+        let assembly = assemblies
+            .iter()
+            .find(|a| a.name == current)
+            .ok_or_else(|| i18n_f("Unknown assembly \"{}\"", &[current]))?;
+        chain.push(assembly);
+        if assembly.parent.is_empty() {
+            break;
+        }
+        current = &assembly.parent;
+    }
+
+    // Expand from the top down
+    let mut instances = vec![String::new()];
+    for assembly in chain.iter().rev() {
+        instances = instances
+            .iter()
+            .flat_map(|prefix| {
+                (1..=assembly.quantity).map(move |i| {
+                    let label = if assembly.quantity == 1 {
+                        assembly.name.clone()
+                    } else {
+                        format!("{} {}", assembly.name, i)
+                    };
+                    if prefix.is_empty() {
+                        label
+                    } else {
+                        format!("{} / {}", prefix, label)
+                    }
+                })
+            })
+            .collect();
+    }
+    Ok(instances)
+}
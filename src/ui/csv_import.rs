@@ -0,0 +1,107 @@
+use gettextrs::gettext;
+
+use super::entry::EntryData;
+use super::number_format::number_format;
+use super::utils::{parse_price, parse_quantity, parse_size_field};
+use crate::i18n::{gettext_noop, i18n_f};
+use crate::size::SizeUnit;
+use crate::utils::parse_csv;
+
+/// Columns read from a CSV file, matched to the header in English or the current language
+const COLUMNS: &[&str] = &[
+    gettext_noop("Name"),
+    gettext_noop("Material"),
+    gettext_noop("Quantity"),
+    gettext_noop("Length"),
+    gettext_noop("Width"),
+    gettext_noop("Price"),
+    gettext_noop("Vendor"),
+];
+
+/// Rows read from a CSV file, for the supplies if the file has a price column (else the parts)
+pub struct CsvImport {
+    pub supplies: bool,
+    pub entries: Vec<EntryData>,
+}
+
+/// Reads supplies or parts from CSV text with a header row
+///
+/// Columns are matched by name in any order (see `COLUMNS`), and others are ignored. Material and
+/// length are required. Sizes may be written in any notation accepted by `SizeUnit::parse`, with
+/// bare numbers in the unit selected by `unit`. Decimals are written with the active separator,
+/// as in a spreadsheet exported in the same language. Rows with a width are 2D.
+pub fn import_csv(text: &str, unit: u32) -> Result<CsvImport, String> {
+    let rows = parse_csv(text);
+    let Some((header, rows)) = rows.split_first() else {
+        return Err(gettext("The file is empty"));
+    };
+    let index: Vec<_> = COLUMNS
+        .iter()
+        .map(|name| {
+            header.iter().position(|h| {
+                let h = h.trim();
+                h.eq_ignore_ascii_case(name) || h == gettext(*name)
+            })
+        })
+        .collect();
+    for required in ["Material", "Length"] {
+        if index[COLUMNS.iter().position(|c| *c == required).unwrap()].is_none() {
+            return Err(i18n_f("Missing a \"{}\" column", &[&gettext(required)]));
+        }
+    }
+    let supplies = index[5].is_some();
+    let size_unit = SizeUnit::from(unit);
+    let number_format = number_format();
+    let separator = number_format.separator();
+
+    let mut entries = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let field = |column: usize| -> String {
+            index[column]
+                .and_then(|j| row.get(j))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default()
+        };
+        // Rows are numbered as in a spreadsheet, after the header
+        let error = |message: String| i18n_f("Row {}: {}", &[&(i + 2).to_string(), &message]);
+
+        let material = field(1);
+        if material.is_empty() {
+            return Err(error(gettext("The material is empty")));
+        }
+        let quantity = field(2);
+        if parse_quantity(&quantity, true).is_err() {
+            return Err(error(i18n_f("\"{}\" is not a quantity", &[&quantity])));
+        }
+        let length = field(3);
+        parse_size_field(&length, &size_unit, false, false, separator)
+            .map_err(|e| error(e.to_string()))?;
+        let width = field(4);
+        parse_size_field(&width, &size_unit, false, true, separator)
+            .map_err(|e| error(e.to_string()))?;
+        let price = field(5);
+        if parse_price(&price, true, separator).is_err() {
+            return Err(error(i18n_f("\"{}\" is not a price", &[&price])));
+        }
+
+        entries.push(EntryData {
+            dimension: if width.is_empty() { 0 } else { 1 },
+            name: field(0),
+            material,
+            price: number_format.to_stored(&price),
+            // Parts need a quantity, while supplies without one are unlimited
+            quantity: if quantity.is_empty() && !supplies {
+                String::from("1")
+            } else {
+                quantity
+            },
+            length_unit: unit,
+            major_length: number_format.to_stored(&length),
+            width_unit: unit,
+            major_width: number_format.to_stored(&width),
+            vendor: field(6),
+            ..Default::default()
+        });
+    }
+    Ok(CsvImport { supplies, entries })
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::i18n_f;
+use crate::modeling::{CutList, Material, Part, SubProblem, SubSolution, Supply};
+
+/// A cut list pinned by the user
+///
+/// The supply and parts are stored by value rather than by index, so the pattern survives edits to
+/// the supplies and parts panes as long as matching entries still exist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedPattern {
+    pub material: Material,
+    pub supply: Supply,
+    pub parts: Vec<Part>,
+
+    /// The number of times the pattern is cut
+    pub quantity: usize,
+}
+
+impl LockedPattern {
+    pub fn from(material: &Material, cut_list: &CutList, sub_solution: &SubSolution) -> Self {
+        Self {
+            material: material.clone(),
+            supply: sub_solution.supplies[cut_list.supply_index].clone(),
+            parts: cut_list
+                .part_indices
+                .iter()
+                .map(|i| sub_solution.parts[*i].clone())
+                .collect(),
+            quantity: cut_list.quantity,
+        }
+    }
+
+    /// The pattern of a cut list from the results, unless the cut list is already locked
+    ///
+    /// Results from before a cut list was locked still show it unlocked, so they are also checked
+    /// against `locked_patterns` (ignoring how often each pattern is cut).
+    pub fn lockable(
+        material: &Material,
+        cut_list: &CutList,
+        sub_solution: &SubSolution,
+        locked_patterns: &[LockedPattern],
+    ) -> Option<Self> {
+        if cut_list.locked {
+            return None;
+        }
+        let pattern = Self::from(material, cut_list, sub_solution);
+        let is_locked = locked_patterns.iter().any(|p| {
+            p.material == pattern.material
+                && same_supply(&p.supply, &pattern.supply)
+                && p.parts.len() == pattern.parts.len()
+                && p.parts
+                    .iter()
+                    .zip(&pattern.parts)
+                    .all(|(a, b)| same_part(a, b))
+        });
+        (!is_locked).then_some(pattern)
+    }
+
+    /// Finds the supply and parts of the pattern in a `SubProblem`
+    pub fn resolve(&self, sub_problem: &SubProblem) -> Result<CutList, String> {
+        let supply_index = sub_problem
+            .supplies
+            .iter()
+            .position(|s| same_supply(s, &self.supply))
+            .ok_or_else(|| {
+                i18n_f(
+                    "Locked cut list refers to a missing supply \"{}\"",
+                    &[&self.supply.name],
+                )
+            })?;
+        let mut part_indices = Vec::new();
+        for part in self.parts.iter() {
+            let i = sub_problem
+                .parts
+                .iter()
+                .position(|p| same_part(p, part))
+                .ok_or_else(|| {
+                    i18n_f(
+                        "Locked cut list refers to a missing part \"{}\"",
+                        &[&part.label()],
+                    )
+                })?;
+            part_indices.push(i);
+        }
+        Ok(CutList {
+            supply_index,
+            part_indices,
+            quantity: self.quantity,
+            locked: true,
+        })
+    }
+}
+
+fn same_supply(a: &Supply, b: &Supply) -> bool {
+    a.name == b.name && a.length == b.length
+}
+
+fn same_part(a: &Part, b: &Part) -> bool {
+    a.name == b.name && a.assembly == b.assembly && a.length == b.length
+}
+
+#[cfg(test)]
+mod tests {
+    use fraction::Decimal;
+
+    use super::*;
+    use crate::size::Size;
+
+    #[test]
+    fn test_lockable() {
+        let material = Material::default();
+        let part = |name: &str, length: f64, quantity: i64| Part {
+            name: String::from(name),
+            length: Size::from_meters(length),
+            quantity,
+            ..Default::default()
+        };
+        let sub_solution = SubSolution {
+            supplies: vec![Supply {
+                length: Size::from_meters(8.0),
+                price: Decimal::from(3.5),
+                max_quantity: -1,
+                ..Default::default()
+            }],
+            parts: vec![part("Shelf", 3.0, 2), part("Leg", 1.0, 4)],
+            cut_lists: vec![CutList {
+                supply_index: 0,
+                part_indices: vec![0, 0, 1],
+                quantity: 1,
+                locked: false,
+            }],
+            blade_width: Size::default(),
+            depth: Size::default(),
+            end_trim: Size::default(),
+            vendors: Vec::new(),
+            lower_bound: None,
+        };
+        let cut_list = &sub_solution.cut_lists[0];
+        let mut locked_patterns = Vec::new();
+        let pattern = LockedPattern::lockable(&material, cut_list, &sub_solution, &locked_patterns);
+        locked_patterns.push(pattern.unwrap());
+
+        // The results haven't been updated, but the cut list can't be locked twice
+        locked_patterns[0].quantity = 2;
+        assert!(
+            LockedPattern::lockable(&material, cut_list, &sub_solution, &locked_patterns).is_none()
+        );
+
+        // Once the locked pattern is edited, the cut list is a different pattern again
+        locked_patterns[0].parts.pop();
+        assert!(
+            LockedPattern::lockable(&material, cut_list, &sub_solution, &locked_patterns).is_some()
+        );
+    }
+}
@@ -0,0 +1,360 @@
+use std::cell::RefCell;
+use std::sync::OnceLock;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use gtk::glib::{Object, clone, subclass::InitializingObject, subclass::Signal};
+use gtk::{CompositeTemplate, glib};
+
+use super::cut_plan::LockedPattern;
+use super::labels::sorted_materials;
+use crate::i18n::i18n_f;
+use crate::modeling::{Part, Solution};
+use crate::size::FractionFormat;
+
+mod imp {
+    use super::*;
+
+    // Object holding the state
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/com/ohmm-software/Chop-Chop/cut_plan_dialog.ui")]
+    pub struct CutPlanDialog {
+        #[template_child]
+        pub(super) locked_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub(super) results_group: TemplateChild<adw::PreferencesGroup>,
+
+        // Patterns being edited (read back by the window on "cut-plan-updated")
+        pub(super) locked_patterns: RefCell<Vec<LockedPattern>>,
+
+        // Latest solver results, which cut lists can be locked from
+        pub(super) solution: RefCell<Option<Solution>>,
+
+        // Used to format part and supply lengths
+        pub(super) format: RefCell<Option<FractionFormat>>,
+
+        // Rows added to each group (removed whenever the rows are rebuilt)
+        pub(super) rows: RefCell<Vec<(adw::PreferencesGroup, gtk::Widget)>>,
+    }
+
+    // The central trait for subclassing a GObject
+    #[glib::object_subclass]
+    impl ObjectSubclass for CutPlanDialog {
+        const NAME: &'static str = "ChopChopCutPlanDialog";
+        type Type = super::CutPlanDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            // Required for CompositeTemplate
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            // Required for CompositeTemplate
+            obj.init_template();
+        }
+    }
+
+    // Trait shared by all GObjects
+    impl ObjectImpl for CutPlanDialog {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+
+            // The parent window watches this signal to store the new locked patterns
+            SIGNALS.get_or_init(|| vec![Signal::builder("cut-plan-updated").build()])
+        }
+    }
+
+    // Trait shared by all widgets
+    impl WidgetImpl for CutPlanDialog {}
+
+    // Trait shared by all Adwaita dialogs
+    impl AdwDialogImpl for CutPlanDialog {}
+}
+
+glib::wrapper! {
+    pub struct CutPlanDialog(ObjectSubclass<imp::CutPlanDialog>)
+        @extends adw::Dialog, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl CutPlanDialog {
+    pub fn new(
+        locked_patterns: Vec<LockedPattern>,
+        solution: Option<Solution>,
+        format: FractionFormat,
+    ) -> Self {
+        let dialog: Self = Object::builder().build();
+        let imp = dialog.imp();
+        imp.locked_patterns.replace(locked_patterns);
+        imp.solution.replace(solution);
+        imp.format.replace(Some(format));
+        dialog.update_rows(None);
+        dialog
+    }
+
+    pub fn locked_patterns(&self) -> Vec<LockedPattern> {
+        self.imp().locked_patterns.borrow().clone()
+    }
+
+    fn add_part(&self, i: usize, part: Part) {
+        self.imp().locked_patterns.borrow_mut()[i].parts.push(part);
+        self.signal_cut_plan_updated();
+        self.update_rows(Some(i));
+    }
+
+    /// Lists parts with their lengths (e.g., "Shelf (36 in), Shelf (36 in)")
+    fn describe_parts(&self, parts: &[&Part]) -> String {
+        let format = self.format();
+        let parts: Vec<_> = parts
+            .iter()
+            .map(|p| format!("{} ({})", p.label(), p.length.format(&format)))
+            .collect();
+        parts.join(", ")
+    }
+
+    fn format(&self) -> FractionFormat {
+        self.imp().format.borrow().clone().unwrap()
+    }
+
+    /// Adds a row to a group, tracking it so it can be removed later
+    fn add_row(&self, group: &adw::PreferencesGroup, row: &impl IsA<gtk::Widget>) {
+        group.add(row);
+        self.imp()
+            .rows
+            .borrow_mut()
+            .push((group.clone(), row.clone().upcast()));
+    }
+
+    fn lock_cut_list(&self, pattern: LockedPattern) {
+        self.imp().locked_patterns.borrow_mut().push(pattern);
+        self.signal_cut_plan_updated();
+        self.update_rows(None);
+    }
+
+    /// Removes a part from a locked pattern, unlocking the pattern if no parts remain
+    fn remove_part(&self, i: usize, j: usize) {
+        let is_empty = {
+            let mut locked_patterns = self.imp().locked_patterns.borrow_mut();
+            locked_patterns[i].parts.remove(j);
+            locked_patterns[i].parts.is_empty()
+        };
+        if is_empty {
+            self.unlock(i);
+        } else {
+            self.signal_cut_plan_updated();
+            self.update_rows(Some(i));
+        }
+    }
+
+    fn set_quantity(&self, i: usize, quantity: usize) {
+        self.imp().locked_patterns.borrow_mut()[i].quantity = quantity;
+        self.signal_cut_plan_updated();
+    }
+
+    fn signal_cut_plan_updated(&self) {
+        self.emit_by_name::<()>("cut-plan-updated", &[]);
+    }
+
+    fn unlock(&self, i: usize) {
+        self.imp().locked_patterns.borrow_mut().remove(i);
+        self.signal_cut_plan_updated();
+        self.update_rows(None);
+    }
+
+    /// Rebuilds both groups, expanding the locked pattern at index `expanded` (if any)
+    fn update_rows(&self, expanded: Option<usize>) {
+        let imp = self.imp();
+        for (group, row) in imp.rows.take() {
+            group.remove(&row);
+        }
+        self.update_locked_rows(expanded);
+        self.update_result_rows();
+    }
+
+    fn update_locked_rows(&self, expanded: Option<usize>) {
+        let imp = self.imp();
+        let format = self.format();
+        let locked_patterns = imp.locked_patterns.borrow().clone();
+        if locked_patterns.is_empty() {
+            let row = adw::ActionRow::builder()
+                .title(gettext("No locked cut lists"))
+                .build();
+            row.add_css_class("dim-label");
+            self.add_row(&imp.locked_group, &row);
+        }
+
+        for (i, pattern) in locked_patterns.iter().enumerate() {
+            let row = adw::ExpanderRow::builder()
+                .title(format!(
+                    "{} ({})",
+                    pattern.supply.name,
+                    pattern.supply.length.format(&format)
+                ))
+                .subtitle(format!(
+                    "{} · {}",
+                    pattern.material.name,
+                    self.describe_parts(&pattern.parts.iter().collect::<Vec<_>>())
+                ))
+                .use_markup(false)
+                .expanded(expanded == Some(i))
+                .build();
+
+            let unlock_button = gtk::Button::builder()
+                .icon_name("changes-allow-symbolic")
+                .tooltip_text(gettext("Unlock this cut list"))
+                .valign(gtk::Align::Center)
+                .build();
+            unlock_button.add_css_class("flat");
+            unlock_button.connect_clicked(clone!(
+                #[weak(rename_to = dialog)]
+                self,
+                move |_| {
+                    dialog.unlock(i);
+                }
+            ));
+            row.add_suffix(&unlock_button);
+
+            let quantity_row = adw::SpinRow::with_range(1.0, 1000.0, 1.0);
+            quantity_row.set_title(&gettext("Repeats"));
+            quantity_row.set_value(pattern.quantity as f64);
+            quantity_row.connect_value_notify(clone!(
+                #[weak(rename_to = dialog)]
+                self,
+                move |spin_row| {
+                    dialog.set_quantity(i, spin_row.value() as usize);
+                }
+            ));
+            row.add_row(&quantity_row);
+
+            for (j, part) in pattern.parts.iter().enumerate() {
+                let part_row = adw::ActionRow::builder()
+                    .title(format!("#{} {}", j + 1, part.label()))
+                    .subtitle(part.length.format(&format))
+                    .use_markup(false)
+                    .build();
+                let remove_button = gtk::Button::builder()
+                    .icon_name("list-remove-symbolic")
+                    .tooltip_text(gettext("Remove this part from the cut list"))
+                    .valign(gtk::Align::Center)
+                    .build();
+                remove_button.add_css_class("flat");
+                remove_button.connect_clicked(clone!(
+                    #[weak(rename_to = dialog)]
+                    self,
+                    move |_| {
+                        dialog.remove_part(i, j);
+                    }
+                ));
+                part_row.add_suffix(&remove_button);
+                row.add_row(&part_row);
+            }
+
+            // Parts can only be added from the latest results, which list every part
+            let candidates: Vec<Part> = match imp.solution.borrow().as_ref() {
+                Some(solution) => match solution.get(&pattern.material) {
+                    Some(sub_solution) => sub_solution.parts.clone(),
+                    None => Vec::new(),
+                },
+                None => Vec::new(),
+            };
+            if !candidates.is_empty() {
+                let names: Vec<String> = candidates
+                    .iter()
+                    .map(|p| format!("{} ({})", p.label(), p.length.format(&format)))
+                    .collect();
+                let names: Vec<&str> = names.iter().map(|s| s.as_str()).collect();
+                let dropdown = gtk::DropDown::from_strings(&names);
+                dropdown.set_valign(gtk::Align::Center);
+                let add_button = gtk::Button::builder()
+                    .icon_name("list-add-symbolic")
+                    .tooltip_text(gettext("Add the part to the cut list"))
+                    .valign(gtk::Align::Center)
+                    .build();
+                add_button.add_css_class("flat");
+                add_button.connect_clicked(clone!(
+                    #[weak(rename_to = dialog)]
+                    self,
+                    #[weak]
+                    dropdown,
+                    move |_| {
+                        let part = candidates[dropdown.selected() as usize].clone();
+                        dialog.add_part(i, part);
+                    }
+                ));
+                let add_row = adw::ActionRow::builder().title(gettext("Add Part")).build();
+                add_row.add_suffix(&dropdown);
+                add_row.add_suffix(&add_button);
+                row.add_row(&add_row);
+            }
+
+            self.add_row(&imp.locked_group, &row);
+        }
+    }
+
+    fn update_result_rows(&self) {
+        let imp = self.imp();
+        let format = self.format();
+        let solution = imp.solution.borrow().clone();
+        let locked_patterns = imp.locked_patterns.borrow().clone();
+        let Some(solution) = solution else {
+            let row = adw::ActionRow::builder()
+                .title(gettext("Run the solver to choose cut lists to lock"))
+                .build();
+            row.add_css_class("dim-label");
+            self.add_row(&imp.results_group, &row);
+            return;
+        };
+
+        // Cut lists are numbered as in the results view
+        let mut i = 1;
+        for material in sorted_materials(&solution) {
+            let sub_solution = &solution[material];
+            for cut_list in sub_solution.cut_lists.iter() {
+                if let Some(pattern) =
+                    LockedPattern::lockable(material, cut_list, sub_solution, &locked_patterns)
+                {
+                    let supply = &sub_solution.supplies[cut_list.supply_index];
+                    let parts: Vec<_> = cut_list
+                        .part_indices
+                        .iter()
+                        .map(|j| &sub_solution.parts[*j])
+                        .collect();
+                    let row = adw::ActionRow::builder()
+                        .title(i18n_f(
+                            "Cut List {} ({}, {} {})",
+                            &[
+                                &i.to_string(),
+                                &material.name,
+                                &supply.name,
+                                &supply.length.format(&format),
+                            ],
+                        ))
+                        .subtitle(i18n_f(
+                            "Repeats {} · {}",
+                            &[&cut_list.quantity.to_string(), &self.describe_parts(&parts)],
+                        ))
+                        .use_markup(false)
+                        .build();
+                    let lock_button = gtk::Button::builder()
+                        .icon_name("changes-prevent-symbolic")
+                        .tooltip_text(gettext("Lock this cut list"))
+                        .valign(gtk::Align::Center)
+                        .build();
+                    lock_button.add_css_class("flat");
+                    lock_button.connect_clicked(clone!(
+                        #[weak(rename_to = dialog)]
+                        self,
+                        move |_| {
+                            dialog.lock_cut_list(pattern.clone());
+                        }
+                    ));
+                    row.add_suffix(&lock_button);
+                    self.add_row(&imp.results_group, &row);
+                }
+                i += 1;
+            }
+        }
+    }
+}
@@ -1,9 +1,14 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use adw::prelude::*;
+use gettextrs::gettext;
+
+use crate::i18n::{i18n_f, ni18n_f};
 
 // TODO: Update with real "Support Us" link
 pub fn about_dialog() -> adw::AboutDialog {
+    // Translators: Replace with your names, one per line
+    let translator_credits = gettext("translator-credits");
     let dialog = adw::AboutDialog::builder()
         .application_icon("tree-circle-symbolic")
         .application_name("Chop-Chop")
@@ -17,18 +22,38 @@ pub fn about_dialog() -> adw::AboutDialog {
         ])
         .copyright("Copyright © 2025 Matthew Dutson and Samuel Dutson")
         .license_type(gtk::License::Gpl30)
-        .comments("Thanks to (the lovely) Itzel Estrella for the name. And thanks as always to Lex de Azevedo and Uwe Rosenberg.")
+        .comments(gettext("Thanks to (the lovely) Itzel Estrella for the name. And thanks as always to Lex de Azevedo and Uwe Rosenberg."))
         .build();
-    dialog.add_link("Support Us", "https://ohmm-software.com");
+    if translator_credits != "translator-credits" {
+        dialog.set_translator_credits(&translator_credits);
+    }
+    dialog.add_link(&gettext("Support Us"), "https://ohmm-software.com");
     dialog
 }
 
 pub fn open_failed_dialog(file_path: &PathBuf) -> adw::AlertDialog {
     let dialog = adw::AlertDialog::builder()
-        .heading("Open Failed")
-        .body(&format!("Failed to open \"{}\"", file_path.display()))
+        .heading(gettext("Open Failed"))
+        .body(i18n_f(
+            "Failed to open \"{}\"",
+            &[&file_path.display().to_string()],
+        ))
         .build();
-    dialog.add_response("okay", "Okay");
+    dialog.add_response("okay", &gettext("Okay"));
+    dialog.set_default_response(Some("okay"));
+    dialog.set_close_response("okay");
+    dialog
+}
+
+pub fn import_failed_dialog(file_path: &Path, message: &str) -> adw::AlertDialog {
+    let dialog = adw::AlertDialog::builder()
+        .heading(gettext("Import Failed"))
+        .body(i18n_f(
+            "Failed to import \"{}\"\n\n{}",
+            &[&file_path.display().to_string(), message],
+        ))
+        .build();
+    dialog.add_response("okay", &gettext("Okay"));
     dialog.set_default_response(Some("okay"));
     dialog.set_close_response("okay");
     dialog
@@ -36,10 +61,47 @@ pub fn open_failed_dialog(file_path: &PathBuf) -> adw::AlertDialog {
 
 pub fn save_failed_dialog(file_path: &PathBuf) -> adw::AlertDialog {
     let dialog = adw::AlertDialog::builder()
-        .heading("Save Failed")
-        .body(&format!("Failed to save to \"{}\"", file_path.display()))
+        .heading(gettext("Save Failed"))
+        .body(i18n_f(
+            "Failed to save to \"{}\"",
+            &[&file_path.display().to_string()],
+        ))
+        .build();
+    dialog.add_response("okay", &gettext("Okay"));
+    dialog.set_default_response(Some("okay"));
+    dialog.set_close_response("okay");
+    dialog
+}
+
+pub fn scale_quantities_dialog(n_rows: usize) -> adw::AlertDialog {
+    let factor = gtk::SpinButton::with_range(1.0, 1000.0, 1.0);
+    factor.set_halign(gtk::Align::Center);
+    let dialog = adw::AlertDialog::builder()
+        .heading(gettext("Scale Quantities"))
+        .body(ni18n_f(
+            "Multiply the quantities of {} row by:",
+            "Multiply the quantities of {} rows by:",
+            n_rows as u32,
+            &[&n_rows.to_string()],
+        ))
+        .extra_child(&factor)
+        .build();
+    dialog.add_response("cancel", &gettext("Cancel"));
+    dialog.add_response("scale", &gettext("Scale"));
+    dialog.set_default_response(Some("scale"));
+    dialog.set_close_response("cancel");
+    dialog.set_response_appearance("scale", adw::ResponseAppearance::Suggested);
+    dialog
+}
+
+pub fn scale_failed_dialog() -> adw::AlertDialog {
+    let dialog = adw::AlertDialog::builder()
+        .heading(gettext("Scale Failed"))
+        .body(gettext(
+            "Some quantities would become too large, so none were changed",
+        ))
         .build();
-    dialog.add_response("okay", "Okay");
+    dialog.add_response("okay", &gettext("Okay"));
     dialog.set_default_response(Some("okay"));
     dialog.set_close_response("okay");
     dialog
@@ -47,12 +109,14 @@ pub fn save_failed_dialog(file_path: &PathBuf) -> adw::AlertDialog {
 
 pub fn unsaved_changes_dialog() -> adw::AlertDialog {
     let dialog = adw::AlertDialog::builder()
-        .heading("Discard Changes?")
-        .body("You have unsaved changes. Do you want to exit without saving?")
+        .heading(gettext("Discard Changes?"))
+        .body(gettext(
+            "You have unsaved changes. Do you want to exit without saving?",
+        ))
         .build();
-    dialog.add_response("cancel", "Cancel");
-    dialog.add_response("discard", "Discard");
-    dialog.add_response("save", "Save");
+    dialog.add_response("cancel", &gettext("Cancel"));
+    dialog.add_response("discard", &gettext("Discard"));
+    dialog.add_response("save", &gettext("Save"));
     dialog.set_default_response(Some("save"));
     dialog.set_close_response("cancel");
     dialog.set_response_appearance("cancel", adw::ResponseAppearance::Default);
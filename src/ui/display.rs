@@ -1,11 +1,17 @@
 use adw::prelude::*;
+use fraction::{Fraction, Zero};
+use gettextrs::gettext;
 use gtk::cairo;
-use gtk::glib::clone;
+use gtk::glib::{self, clone};
 use pango::{FontDescription, units_from_double, units_to_double};
 use pangocairo::functions::{create_layout, show_layout};
 
+use super::labels::{LabelLayout, PartLabel};
+use super::utils::DisplayUnits;
+use crate::i18n::i18n_f;
+use crate::layout::{CutLayout, Placement};
 use crate::modeling::{CutList, Part, SubSolution, Supply};
-use crate::size::FractionFormat;
+use crate::size::{FractionFormat, Size};
 
 pub trait DisplayBlock {
     /// Displays contents within a widget
@@ -18,7 +24,6 @@ pub trait DisplayBlock {
     fn height(&self, c: &cairo::Context, f: &FontDescription, w: f64) -> f64;
 }
 
-#[derive(Default)]
 pub struct DisplayEngine {
     /// A list of blocks to draw
     blocks: Vec<Box<dyn DisplayBlock>>,
@@ -31,6 +36,31 @@ pub struct DisplayEngine {
 
     /// Block indices where page breaks are needed
     pagination: Option<Vec<usize>>,
+
+    /// Height of the pages passed to `paginate`
+    page_height: f64,
+
+    /// Text repeated on each page (only when printing)
+    page_decorations: Option<PageDecorations>,
+
+    /// Space around the drawn content
+    margin_horizontal: f64,
+    margin_vertical: f64,
+}
+
+impl Default for DisplayEngine {
+    fn default() -> Self {
+        Self {
+            blocks: Vec::new(),
+            sections: Vec::new(),
+            open_section: None,
+            pagination: None,
+            page_height: 0.0,
+            page_decorations: None,
+            margin_horizontal: Self::MARGIN_HORIZONTAL,
+            margin_vertical: Self::MARGIN_VERTICAL,
+        }
+    }
 }
 
 impl DisplayEngine {
@@ -110,7 +140,21 @@ impl DisplayEngine {
         } else {
             self.blocks.len()
         };
+
+        // Blocks start below the page header
+        let mut top = 0.0;
+        if let Some(decorations) = &self.page_decorations {
+            c.save().unwrap();
+            c.translate(self.margin_horizontal, self.margin_vertical);
+            let inner_height = self.page_height - 2.0 * self.margin_vertical;
+            let inner_width = w - 2.0 * self.margin_horizontal;
+            top = decorations.draw(c, f, inner_width, inner_height, i, self.n_pages());
+            c.restore().unwrap();
+        }
+        c.save().unwrap();
+        c.translate(0.0, top);
         self.draw_blocks(&self.blocks[j..k], c, f, w);
+        c.restore().unwrap();
     }
 
     pub fn end_section(&mut self) {
@@ -128,11 +172,16 @@ impl DisplayEngine {
     }
 
     pub fn paginate(&mut self, c: &cairo::Context, f: &FontDescription, w: f64, h: f64) {
+        self.page_height = h;
+        let h = match &self.page_decorations {
+            Some(decorations) => h - decorations.height(c, f, w - 2.0 * self.margin_horizontal),
+            None => h,
+        };
         let mut pagination = Vec::new();
         let mut y = 0.0;
         for (i, block) in self.blocks.iter().enumerate() {
             let b = block.height(c, f, w);
-            if (y != 0.0) && (y + b + 2.0 * Self::MARGIN_VERTICAL > h) {
+            if (y != 0.0) && (y + b + 2.0 * self.margin_vertical > h) {
                 pagination.push(i);
                 y = 0.0;
             } else {
@@ -142,6 +191,17 @@ impl DisplayEngine {
         self.pagination = Some(pagination);
     }
 
+    /// Sets the text repeated at the top and bottom of each page when printing
+    pub fn set_page_decorations(&mut self, page_decorations: Option<PageDecorations>) {
+        self.page_decorations = page_decorations;
+    }
+
+    /// Sets the space around the drawn content (blocks that fill a page should use zero)
+    pub fn set_margins(&mut self, horizontal: f64, vertical: f64) {
+        self.margin_horizontal = horizontal;
+        self.margin_vertical = vertical;
+    }
+
     pub fn start_section(&mut self) {
         self.open_section = Some(self.blocks.len());
     }
@@ -152,11 +212,15 @@ impl DisplayEngine {
         &mut self,
         cut_list: &CutList,
         sub_solution: &SubSolution,
+        format: &FractionFormat,
+        units: &DisplayUnits,
         max_length: Option<f64>,
     ) {
         self.append_block(Box::new(CutDiagram::from(
             cut_list,
             sub_solution,
+            format,
+            units,
             max_length,
         )))
     }
@@ -173,6 +237,10 @@ impl DisplayEngine {
         self.append_block(Box::new(Header3::from(&text)))
     }
 
+    pub fn append_label_sheet(&mut self, layout: &LabelLayout, labels: &[PartLabel], qr: bool) {
+        self.append_block(Box::new(LabelSheet::from(layout, labels, qr)));
+    }
+
     pub fn append_paragraph(&mut self, text: &str) {
         self.append_block(Box::new(Paragraph::from(&text)))
     }
@@ -189,12 +257,95 @@ impl DisplayEngine {
         f: &FontDescription,
         w: f64,
     ) -> f64 {
-        let mut y = Self::MARGIN_VERTICAL;
+        let mut y = self.margin_vertical;
         for block in blocks {
-            c.move_to(Self::MARGIN_HORIZONTAL, y);
-            y += block.draw(c, f, w - Self::MARGIN_HORIZONTAL * 2.0);
+            c.move_to(self.margin_horizontal, y);
+            y += block.draw(c, f, w - self.margin_horizontal * 2.0);
+        }
+        y + self.margin_vertical
+    }
+}
+
+/// Text repeated at the top and bottom of each printed page
+#[derive(Clone, Default)]
+pub struct PageDecorations {
+    /// Shown at the top left (e.g., the project title)
+    pub header_start: String,
+
+    /// Shown at the top right (e.g., the due date)
+    pub header_end: String,
+
+    /// Shown at the bottom before the page number (e.g., the job number)
+    pub footer: String,
+}
+
+impl PageDecorations {
+    const SPACING: f64 = 12.0;
+
+    fn footer_markup(&self, i: usize, n_pages: usize) -> String {
+        let page = (i + 1).to_string();
+        let n_pages = n_pages.to_string();
+        let text = if self.footer.is_empty() {
+            i18n_f("Page {} of {}", &[&page, &n_pages])
+        } else {
+            // Translators: The job (e.g., "Job 1234"), then the page number and page count
+            i18n_f("{} — page {} of {}", &[&self.footer, &page, &n_pages])
+        };
+        small_markup(&text)
+    }
+
+    /// Returns the widths given to the left and right parts of the header
+    fn header_widths(&self, c: &cairo::Context, f: &FontDescription, w: f64) -> (f64, f64) {
+        let end = text_width(c, f, &small_markup(&self.header_end)).min(w / 2.0);
+        (w - end - Self::SPACING, end)
+    }
+
+    /// Draws the header at the origin and the footer ending at height `h`, returning the header height
+    fn draw(
+        &self,
+        c: &cairo::Context,
+        f: &FontDescription,
+        w: f64,
+        h: f64,
+        i: usize,
+        n_pages: usize,
+    ) -> f64 {
+        let header_height = self.header_height(c, f, w);
+        if header_height > 0.0 {
+            let (start, end) = self.header_widths(c, f, w);
+            c.move_to(0.0, 0.0);
+            draw_text(c, f, start, &small_markup(&self.header_start), true);
+            c.move_to(w - end, 0.0);
+            let markup = small_markup(&self.header_end);
+            draw_aligned_text(c, f, end, &markup, pango::Alignment::Right, true);
+            let y = header_height - Self::SPACING / 2.0;
+            c.move_to(0.0, y);
+            c.line_to(w, y);
+            c.set_line_width(0.5);
+            c.stroke().unwrap();
+        }
+        let markup = self.footer_markup(i, n_pages);
+        let footer_height = draw_text(c, f, w, &markup, false);
+        c.move_to(0.0, h - footer_height);
+        draw_aligned_text(c, f, w, &markup, pango::Alignment::Center, true);
+        header_height
+    }
+
+    fn header_height(&self, c: &cairo::Context, f: &FontDescription, w: f64) -> f64 {
+        if self.header_start.is_empty() && self.header_end.is_empty() {
+            return 0.0;
         }
-        y + Self::MARGIN_VERTICAL
+        let (start, end) = self.header_widths(c, f, w);
+        let start_height = draw_text(c, f, start, &small_markup(&self.header_start), false);
+        let end_height = draw_text(c, f, end, &small_markup(&self.header_end), false);
+        start_height.max(end_height) + Self::SPACING
+    }
+
+    /// Returns the height taken from each page by the header and footer
+    fn height(&self, c: &cairo::Context, f: &FontDescription, w: f64) -> f64 {
+        self.header_height(c, f, w)
+            + draw_text(c, f, w, &self.footer_markup(0, 1), false)
+            + Self::SPACING
     }
 }
 
@@ -202,6 +353,9 @@ impl DisplayEngine {
 pub struct CutDiagram {
     supply: Supply,
     parts: Vec<Part>,
+    layout: CutLayout,
+    format: FractionFormat,
+    units: DisplayUnits,
     max_length: Option<f64>,
 }
 
@@ -209,22 +363,158 @@ impl CutDiagram {
     const MARGIN_TOP: f64 = 12.0;
     const MARGIN_LABEL: f64 = 8.0;
     const MARGIN_LENGTH: f64 = 8.0;
+    const MARGIN_MARKS: f64 = 4.0;
     const MARGIN_BOTTOM: f64 = 24.0;
     const TICK_SIZE: f64 = 8.0;
+    const KERF_MIN_WIDTH: f64 = 2.0;
+    const OFFCUT_DASH: f64 = 4.0;
 
-    pub fn from(cut_list: &CutList, sub_solution: &SubSolution, max_length: Option<f64>) -> Self {
+    pub fn from(
+        cut_list: &CutList,
+        sub_solution: &SubSolution,
+        format: &FractionFormat,
+        units: &DisplayUnits,
+        max_length: Option<f64>,
+    ) -> Self {
         let supply = sub_solution.supplies[cut_list.supply_index].clone();
-        let parts = cut_list
-            .part_indices
-            .iter()
-            .map(|i| sub_solution.parts[*i].clone())
-            .collect();
         CutDiagram {
             supply,
-            parts,
+            parts: sub_solution.parts.clone(),
+            layout: CutLayout::from(cut_list, sub_solution),
+            format: format.clone(),
+            // Dual sizes are too wide to fit under the parts
+            units: DisplayUnits {
+                dual: false,
+                ..units.clone()
+            },
             max_length,
         }
     }
+
+    /// How far the bottom of each end of a part is set in from the top, in pixels
+    ///
+    /// Drawn at the true angle, so a 45° end is set in by the height of the tick.
+    fn slants(&self, placement: &Placement) -> (f64, f64) {
+        let angles = &self.parts[placement.part_index].angles;
+        let sign = if placement.flipped { -1.0 } else { 1.0 };
+        let slant = |angle: Fraction| -> f64 {
+            let angle: f64 = angle.try_into().unwrap();
+            let slant = sign * Self::TICK_SIZE * angle.to_radians().tan();
+            slant.clamp(-Self::TICK_SIZE * 2.0, Self::TICK_SIZE * 2.0)
+        };
+        (slant(angles.start), slant(angles.end))
+    }
+
+    /// Formats a length in meters using the display unit of the supply
+    fn format_length(&self, meters: Fraction) -> String {
+        let size = Size::from_meters_in(meters, &self.supply.length.unit);
+        self.units.format_supply(&size, &self.format)
+    }
+
+    /// Draws (or measures, if `show` is false) the diagram, returning its height
+    fn render(&self, c: &cairo::Context, f: &FontDescription, w: f64, show: bool) -> f64 {
+        let (x_0, y_0) = if show {
+            if !c.has_current_point().unwrap() {
+                c.move_to(0.0, 0.0);
+            }
+            c.current_point().unwrap()
+        } else {
+            (0.0, 0.0)
+        };
+        let supply_length: f64 = self.supply.length.to_meters_f64();
+        let w = match self.max_length {
+            Some(length) => w * supply_length / length,
+            None => w,
+        };
+        let x = |meters: Fraction| -> f64 {
+            let meters: f64 = meters.try_into().unwrap();
+            x_0 + meters / supply_length * w
+        };
+        let layout = &self.layout;
+        let has_offcut = layout.offcut() > Fraction::zero();
+
+        // Each part (and the offcut) gets a column for its name and length
+        let mut names = Vec::new();
+        let mut lengths = Vec::new();
+        if layout.end_trim > Fraction::zero() {
+            let (x_1, x_2) = (x_0, x(layout.end_trim));
+            names.push((x_1, x_2, format!("<i>{}</i>", gettext("Trim"))));
+            lengths.push((x_1, x_2, self.format_length(layout.end_trim)));
+        }
+        for placement in layout.placements.iter() {
+            let part = &self.parts[placement.part_index];
+            let (x_1, x_2) = (x(placement.start), x(placement.end));
+            let label = glib::markup_escape_text(&part.label()).to_string();
+            names.push((x_1, x_2, label));
+            lengths.push((x_1, x_2, self.units.format_part(&part.length, &self.format)));
+        }
+        if has_offcut {
+            let (x_1, x_2) = (x(layout.offcut_start), x_0 + w);
+            names.push((x_1, x_2, format!("<i>{}</i>", gettext("Offcut"))));
+            lengths.push((x_1, x_2, self.format_length(layout.offcut())));
+        }
+
+        // Marks are right-aligned so they end at the cut they refer to
+        let mut marks = Vec::new();
+        let mut x_1 = x_0;
+        for mark in layout.marks() {
+            let x_2 = x(mark);
+            marks.push((x_1, x_2, format!("<i>{}</i>", self.format_length(mark))));
+            x_1 = x_2;
+        }
+
+        let mut y = y_0 + Self::MARGIN_TOP;
+        y += draw_row(c, f, y, &names, pango::Alignment::Left, show);
+        y += Self::MARGIN_LABEL;
+
+        if show {
+            // Ticks at both ends of the supply and of every part, slanted for angled ends
+            let mut edges = vec![(x_0, 0.0), (x_0 + w, 0.0), (x(layout.end_trim), 0.0)];
+            for placement in layout.placements.iter() {
+                let (start, end) = self.slants(placement);
+                edges.push((x(placement.start), start));
+                edges.push((x(placement.end), -end));
+            }
+            for (edge, slant) in edges {
+                c.move_to(edge, y);
+                c.rel_line_to(slant, Self::TICK_SIZE);
+            }
+
+            // Solid line for the used length, dashed for the offcut
+            c.move_to(x_0, y + Self::TICK_SIZE / 2.0);
+            c.line_to(x(layout.offcut_start), y + Self::TICK_SIZE / 2.0);
+            c.stroke().unwrap();
+            if has_offcut {
+                c.set_dash(&[Self::OFFCUT_DASH], 0.0);
+                c.move_to(x(layout.offcut_start), y + Self::TICK_SIZE / 2.0);
+                c.line_to(x_0 + w, y + Self::TICK_SIZE / 2.0);
+                c.stroke().unwrap();
+                c.set_dash(&[], 0.0);
+            }
+
+            // Kerfs are usually too thin to see at scale
+            for kerf in layout.kerfs.iter() {
+                let kerf_width = (x(kerf.end) - x(kerf.start)).max(Self::KERF_MIN_WIDTH);
+                let (_, slant) = self.slants(&layout.placements[kerf.placement_index]);
+                c.move_to(x(kerf.start), y);
+                c.rel_line_to(kerf_width, 0.0);
+                c.rel_line_to(-slant, Self::TICK_SIZE);
+                c.rel_line_to(-kerf_width, 0.0);
+                c.close_path();
+            }
+            c.fill().unwrap();
+        }
+        y += Self::TICK_SIZE + Self::MARGIN_LENGTH;
+
+        y += draw_row(c, f, y, &lengths, pango::Alignment::Left, show);
+        y += Self::MARGIN_MARKS;
+        y += draw_row(c, f, y, &marks, pango::Alignment::Right, show);
+
+        if show {
+            c.move_to(x_0, y);
+        }
+        y - y_0 + Self::MARGIN_BOTTOM
+    }
 }
 
 impl DisplayBlock for CutDiagram {
@@ -250,94 +540,11 @@ impl DisplayBlock for CutDiagram {
     }
 
     fn draw(&self, c: &cairo::Context, f: &FontDescription, w: f64) -> f64 {
-        if !c.has_current_point().unwrap() {
-            c.move_to(0.0, 0.0);
-        }
-        let supply_length: f64 = self.supply.length.to_meters_f64();
-        let w = match self.max_length {
-            Some(length) => w * supply_length / length,
-            None => w,
-        };
-
-        c.rel_move_to(0.0, Self::MARGIN_TOP);
-
-        // Draw labels
-        let (x, y) = c.current_point().unwrap();
-        let mut h_1 = 0.0;
-        for part in self.parts.iter() {
-            let dx = part.length.to_meters_f64() / supply_length * w;
-            let h = draw_text(c, f, dx, &part.name, true);
-            h_1 = if h > h_1 { h } else { h_1 };
-            c.rel_move_to(dx, 0.0);
-        }
-        c.move_to(x, y + h_1);
-
-        c.rel_move_to(0.0, Self::MARGIN_LABEL);
-
-        // Draw ticks
-        let (x, y) = c.current_point().unwrap();
-        c.rel_line_to(0.0, Self::TICK_SIZE);
-        for part in self.parts.iter() {
-            let dx = part.length.to_meters_f64() / supply_length * w;
-            c.rel_move_to(dx, -Self::TICK_SIZE);
-            c.rel_line_to(0.0, Self::TICK_SIZE);
-        }
-        c.move_to(x + w, y);
-        c.rel_line_to(0.0, Self::TICK_SIZE);
-        c.stroke().unwrap();
-        c.move_to(x, y + Self::TICK_SIZE);
-
-        // Draw line
-        let (x, y) = c.current_point().unwrap();
-        c.rel_move_to(0.0, -Self::TICK_SIZE / 2.0);
-        c.rel_line_to(w, 0.0);
-        c.stroke().unwrap();
-        c.move_to(x, y);
-
-        c.rel_move_to(0.0, Self::MARGIN_LENGTH);
-
-        // Draw lengths
-        let (x, y) = c.current_point().unwrap();
-        let mut h_2 = 0.0;
-        for part in self.parts.iter() {
-            // TODO: Use correct fraction format here
-            let l = part.length.format(&FractionFormat::Mixed);
-            let dx = part.length.to_meters_f64() / supply_length * w;
-            let h = draw_text(c, f, dx, &l, true);
-            h_2 = if h > h_2 { h } else { h_2 };
-            c.rel_move_to(dx, 0.0);
-        }
-        c.move_to(x, y + h_2);
-
-        Self::MARGIN_TOP
-            + h_1
-            + Self::MARGIN_LABEL
-            + Self::TICK_SIZE
-            + Self::MARGIN_LENGTH
-            + h_2
-            + Self::MARGIN_BOTTOM
+        self.render(c, f, w, true)
     }
 
     fn height(&self, c: &cairo::Context, f: &FontDescription, w: f64) -> f64 {
-        let mut h_1 = 0.0;
-        for part in self.parts.iter() {
-            let h = draw_text(c, f, w, &part.name, false);
-            h_1 = if h > h_1 { h } else { h_1 };
-        }
-        let mut h_2 = 0.0;
-        for part in self.parts.iter() {
-            // TODO: Use correct fraction format here
-            let l = part.length.format(&FractionFormat::Mixed);
-            let h = draw_text(c, f, w, &l, true);
-            h_2 = if h > h_2 { h } else { h_2 };
-        }
-        Self::MARGIN_TOP
-            + h_1
-            + Self::MARGIN_LABEL
-            + Self::TICK_SIZE
-            + Self::MARGIN_LENGTH
-            + h_2
-            + Self::MARGIN_BOTTOM
+        self.render(c, f, w, false)
     }
 }
 
@@ -361,7 +568,7 @@ impl Header1 {
         format!(
             "<span size='{}'><b>{}</b></span>",
             Self::TEXT_SIZE,
-            self.text
+            glib::markup_escape_text(&self.text)
         )
     }
 }
@@ -412,7 +619,7 @@ impl Header2 {
         format!(
             "<span size='{}'><b>{}</b></span>",
             Self::TEXT_SIZE,
-            self.text
+            glib::markup_escape_text(&self.text)
         )
     }
 }
@@ -457,7 +664,7 @@ impl Header3 {
         format!(
             "<span size='{}'><b>{}</b></span>",
             Self::TEXT_SIZE,
-            self.text
+            glib::markup_escape_text(&self.text)
         )
     }
 }
@@ -483,6 +690,124 @@ impl DisplayBlock for Header3 {
     }
 }
 
+/// One page of part labels on a sticker sheet
+#[derive(Clone)]
+pub struct LabelSheet {
+    layout: LabelLayout,
+    labels: Vec<PartLabel>,
+    qr: bool,
+}
+
+impl LabelSheet {
+    const PADDING: f64 = 6.0;
+    const MAX_FONT_SIZE: f64 = 10.0;
+
+    pub fn from(layout: &LabelLayout, labels: &[PartLabel], qr: bool) -> Self {
+        Self {
+            layout: layout.clone(),
+            labels: labels.to_vec(),
+            qr,
+        }
+    }
+
+    fn draw_label(&self, c: &cairo::Context, f: &FontDescription, label: &PartLabel) {
+        let (x, y) = c.current_point().unwrap();
+        let w = self.layout.label_width - 2.0 * Self::PADDING;
+        let h = self.layout.label_height - 2.0 * Self::PADDING;
+
+        // Keep long names from spilling onto neighboring labels
+        c.save().unwrap();
+        c.rectangle(x, y, self.layout.label_width, self.layout.label_height);
+        c.clip();
+
+        let mut text_x = x + Self::PADDING;
+        if self.qr
+            && let Ok(code) = qrcode::QrCode::new(label.qr_data())
+        {
+            draw_qr_code(c, &code, x + Self::PADDING, y + Self::PADDING, h);
+            text_x += h + Self::PADDING;
+        }
+
+        // Shrink the font so every line fits
+        let lines = label.lines();
+        let mut f = f.clone();
+        let font_size = (h / lines.len() as f64 / 1.4).min(Self::MAX_FONT_SIZE);
+        f.set_size(units_from_double(font_size));
+
+        let text_w = w - (text_x - x - Self::PADDING);
+        let mut text_y = y + Self::PADDING;
+        for (i, line) in lines.iter().enumerate() {
+            let line = glib::markup_escape_text(line);
+            let markup = if i == 0 {
+                format!("<b>{}</b>", line)
+            } else {
+                line.to_string()
+            };
+            c.move_to(text_x, text_y);
+            text_y += draw_text(c, &f, text_w, &markup, true);
+        }
+        c.restore().unwrap();
+    }
+}
+
+impl DisplayBlock for LabelSheet {
+    fn display(&self, b: &gtk::Box) {
+        let drawing_area = gtk::DrawingArea::builder().build();
+        drawing_area.set_draw_func(clone!(
+            #[strong(rename_to = block)]
+            self.clone(),
+            move |d, c, w, _| {
+                let style = adw::StyleManager::default();
+                let font = FontDescription::from_string(&style.document_font_name());
+                c.set_source_color(&d.color());
+                let h = block.draw(c, &font, w as f64);
+                d.set_height_request(h as i32);
+            },
+        ));
+        b.append(&drawing_area);
+        drawing_area.queue_draw();
+    }
+
+    fn draw(&self, c: &cairo::Context, f: &FontDescription, w: f64) -> f64 {
+        if !c.has_current_point().unwrap() {
+            c.move_to(0.0, 0.0);
+        }
+        let (x, y) = c.current_point().unwrap();
+
+        // Layouts are in points, so scale to the available width
+        let scale = w / self.layout.page_width;
+        c.save().unwrap();
+        c.translate(x, y);
+        c.scale(scale, scale);
+        for (i, label) in self.labels.iter().enumerate() {
+            let (label_x, label_y) = self.layout.position(i);
+            c.move_to(label_x, label_y);
+            self.draw_label(c, f, label);
+        }
+        c.restore().unwrap();
+        self.layout.page_height * scale
+    }
+
+    fn height(&self, _c: &cairo::Context, _f: &FontDescription, w: f64) -> f64 {
+        self.layout.page_height * w / self.layout.page_width
+    }
+}
+
+/// Draws a square QR code (including a quiet zone) with its top-left corner at (x, y)
+fn draw_qr_code(c: &cairo::Context, code: &qrcode::QrCode, x: f64, y: f64, size: f64) {
+    const QUIET_ZONE: usize = 2;
+    let n = code.width();
+    let module = size / (n + 2 * QUIET_ZONE) as f64;
+    for (i, color) in code.to_colors().iter().enumerate() {
+        if *color == qrcode::Color::Dark {
+            let column = (i % n + QUIET_ZONE) as f64;
+            let row = (i / n + QUIET_ZONE) as f64;
+            c.rectangle(x + column * module, y + row * module, module, module);
+        }
+    }
+    c.fill().unwrap();
+}
+
 pub struct Paragraph {
     text: String,
 }
@@ -510,20 +835,52 @@ impl DisplayBlock for Paragraph {
 
     fn draw(&self, c: &cairo::Context, f: &FontDescription, w: f64) -> f64 {
         c.rel_move_to(0.0, Self::MARGIN_TOP);
-        let h = draw_text(c, f, w, &self.text, true);
+        let h = draw_text(c, f, w, &glib::markup_escape_text(&self.text), true);
         h + Self::MARGIN_TOP + Self::MARGIN_BOTTOM
     }
 
     fn height(&self, c: &cairo::Context, f: &FontDescription, w: f64) -> f64 {
-        draw_text(c, f, w, &self.text, false) + Self::MARGIN_TOP + Self::MARGIN_BOTTOM
+        let markup = glib::markup_escape_text(&self.text);
+        draw_text(c, f, w, &markup, false) + Self::MARGIN_TOP + Self::MARGIN_BOTTOM
+    }
+}
+
+/// Draws each (left, right, markup) cell at height `y`, returning the height of the tallest
+fn draw_row(
+    c: &cairo::Context,
+    f: &FontDescription,
+    y: f64,
+    cells: &[(f64, f64, String)],
+    alignment: pango::Alignment,
+    show: bool,
+) -> f64 {
+    let mut h: f64 = 0.0;
+    for (x_1, x_2, markup) in cells {
+        if show {
+            c.move_to(*x_1, y);
+        }
+        h = h.max(draw_aligned_text(c, f, x_2 - x_1, markup, alignment, show));
     }
+    h
 }
 
 fn draw_text(c: &cairo::Context, f: &FontDescription, w: f64, s: &str, show: bool) -> f64 {
+    draw_aligned_text(c, f, w, s, pango::Alignment::Left, show)
+}
+
+fn draw_aligned_text(
+    c: &cairo::Context,
+    f: &FontDescription,
+    w: f64,
+    s: &str,
+    alignment: pango::Alignment,
+    show: bool,
+) -> f64 {
     let p = create_layout(c);
     p.set_font_description(Some(f));
     p.set_width(units_from_double(w));
     p.set_wrap(pango::WrapMode::Word);
+    p.set_alignment(alignment);
     p.set_markup(&s);
     if show {
         show_layout(c, &p);
@@ -533,15 +890,86 @@ fn draw_text(c: &cairo::Context, f: &FontDescription, w: f64, s: &str, show: boo
     units_to_double(p.extents().1.height())
 }
 
+fn small_markup(text: &str) -> String {
+    format!("<small>{}</small>", glib::markup_escape_text(text))
+}
+
+/// Returns the width of the text without wrapping
+fn text_width(c: &cairo::Context, f: &FontDescription, s: &str) -> f64 {
+    let p = create_layout(c);
+    p.set_font_description(Some(f));
+    p.set_markup(s);
+    units_to_double(p.extents().1.width())
+}
+
 pub struct Table {
     rows: Vec<Vec<String>>,
     alignment: Vec<gtk::Align>,
 }
 
 impl Table {
+    const MARGIN_TOP: f64 = 12.0;
+    const MARGIN_BOTTOM: f64 = 0.0;
+    const COLUMN_SPACING: f64 = 32.0;
+    const ROW_SPACING: f64 = 8.0;
+
     pub fn from(rows: Vec<Vec<String>>, alignment: Vec<gtk::Align>) -> Self {
         Self { rows, alignment }
     }
+
+    /// Fits each column to its widest cell, shrinking them all if the table would be too wide
+    fn column_widths(&self, c: &cairo::Context, f: &FontDescription, w: f64) -> Vec<f64> {
+        let mut widths = vec![0.0; self.alignment.len()];
+        for row in self.rows.iter() {
+            for (j, value) in row.iter().enumerate() {
+                widths[j] = f64::max(widths[j], text_width(c, f, value));
+            }
+        }
+        let n_spaces = widths.len().saturating_sub(1) as f64;
+        let available = w - n_spaces * Self::COLUMN_SPACING;
+        let total: f64 = widths.iter().sum();
+        if total > available && total > 0.0 {
+            for width in widths.iter_mut() {
+                *width *= available / total;
+            }
+        }
+        widths
+    }
+
+    /// Draws (or measures, if `show` is false) the table, returning its height
+    fn render(&self, c: &cairo::Context, f: &FontDescription, w: f64, show: bool) -> f64 {
+        let (x_0, y_0) = if show {
+            c.current_point().unwrap()
+        } else {
+            (0.0, 0.0)
+        };
+        let widths = self.column_widths(c, f, w);
+        let mut y = y_0 + Self::MARGIN_TOP;
+        for (i, row) in self.rows.iter().enumerate() {
+            if i > 0 {
+                y += Self::ROW_SPACING;
+            }
+            let mut x = x_0;
+            let mut h: f64 = 0.0;
+            for (j, value) in row.iter().enumerate() {
+                let alignment = match self.alignment[j] {
+                    gtk::Align::End => pango::Alignment::Right,
+                    gtk::Align::Center => pango::Alignment::Center,
+                    _ => pango::Alignment::Left,
+                };
+                if show {
+                    c.move_to(x, y);
+                }
+                h = h.max(draw_aligned_text(c, f, widths[j], value, alignment, show));
+                x += widths[j] + Self::COLUMN_SPACING;
+            }
+            y += h;
+        }
+        if show {
+            c.move_to(x_0, y);
+        }
+        y - y_0 + Self::MARGIN_BOTTOM
+    }
 }
 
 impl DisplayBlock for Table {
@@ -564,13 +992,11 @@ impl DisplayBlock for Table {
         b.append(&grid);
     }
 
-    fn draw(&self, _c: &cairo::Context, _f: &FontDescription, _w: f64) -> f64 {
-        // TODO
-        0.0
+    fn draw(&self, c: &cairo::Context, f: &FontDescription, w: f64) -> f64 {
+        self.render(c, f, w, true)
     }
 
-    fn height(&self, _c: &cairo::Context, _f: &FontDescription, _w: f64) -> f64 {
-        // TODO
-        0.0
+    fn height(&self, c: &cairo::Context, f: &FontDescription, w: f64) -> f64 {
+        self.render(c, f, w, false)
     }
 }
@@ -1,19 +1,23 @@
 use std::cell::{Cell, RefCell};
 
+use gettextrs::gettext;
 use gtk::glib;
 use gtk::glib::{Object, Properties};
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::number_format::STORED_SEPARATOR;
 use super::utils::*;
-use crate::size::{FractionFormat, Size};
+use crate::i18n::i18n_f;
+use crate::size::{FractionFormat, Size, SizeUnit};
 
 /// Represents the user-entered data for a supply row
 ///
 /// Stores user-entered strings instead of parsed numeric types (fraction::Decimal or
 /// fraction::Fraction). This allows editing the original text when a row is re-selected, and avoids
-/// headaches with storing custom types in a GObject.
+/// headaches with storing custom types in a GObject. Decimals are stored with a point (see
+/// `STORED_SEPARATOR`), and shown in fields with the active separator.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EntryData {
     pub dimension: u32,
@@ -27,6 +31,73 @@ pub struct EntryData {
     pub width_unit: u32,
     pub major_width: String,
     pub minor_width: String,
+
+    /// The assembly a part belongs to (empty if none)
+    #[serde(default)]
+    pub assembly: String,
+
+    /// Cut angles of a 1D part's ends in degrees (empty for square)
+    #[serde(default)]
+    pub start_angle: String,
+    #[serde(default)]
+    pub end_angle: String,
+
+    /// Whether a 1D part may be spliced, with limits in the smaller length unit (see `parse_splice`)
+    #[serde(default)]
+    pub splice: bool,
+    #[serde(default)]
+    pub min_segment: String,
+    #[serde(default)]
+    pub join_allowance: String,
+
+    /// Whether to make as many of a 1D part as fit in offcuts (the quantity is then a minimum)
+    #[serde(default)]
+    pub fill: bool,
+
+    /// The store a supply is bought from (empty if unspecified)
+    #[serde(default)]
+    pub vendor: String,
+
+    /// Multiple that a supply is sold in (empty if sold individually)
+    #[serde(default)]
+    pub pack_size: String,
+
+    /// Bundle prices of a supply (see `parse_bundles`)
+    #[serde(default)]
+    pub bundles: String,
+}
+
+impl EntryData {
+    /// Whether every field can be parsed (data read from a file might have been edited by hand)
+    pub fn is_valid(&self) -> bool {
+        let size_valid = |unit: u32, major: &str, minor: &str| match SizeUnit::checked_from(unit) {
+            Some(unit) => {
+                parse_size_field(major, &unit, false, true, STORED_SEPARATOR).is_ok()
+                    && parse_size_field(minor, &unit, true, true, STORED_SEPARATOR).is_ok()
+            }
+            None => false,
+        };
+        // Splice limits are in the smaller unit of the length (see `parse_splice`)
+        let splice_valid = !self.splice
+            || SizeUnit::checked_from(self.length_unit).is_some_and(|unit| {
+                [&self.min_segment, &self.join_allowance]
+                    .iter()
+                    .all(|text| {
+                        parse_size_field(text, &unit, unit.has_minor(), true, STORED_SEPARATOR)
+                            .is_ok()
+                    })
+            });
+        self.dimension <= 1
+            && size_valid(self.length_unit, &self.major_length, &self.minor_length)
+            && size_valid(self.width_unit, &self.major_width, &self.minor_width)
+            && parse_price(&self.price, true, STORED_SEPARATOR).is_ok()
+            && parse_quantity(&self.quantity, true).is_ok()
+            && parse_quantity(&self.pack_size, true).is_ok()
+            && parse_bundles(&self.bundles, STORED_SEPARATOR).is_ok()
+            && parse_angle(&self.start_angle, STORED_SEPARATOR).is_ok()
+            && parse_angle(&self.end_angle, STORED_SEPARATOR).is_ok()
+            && splice_valid
+    }
 }
 
 // Wrap SupplyData in a GObject so it can be used in a gtk::ListStore
@@ -52,6 +123,16 @@ mod imp {
         #[property(name = "width-unit", get, set, type = u32, member = width_unit)]
         #[property(name = "major-width", get, set, type = String, member = major_width)]
         #[property(name = "minor-width", get, set, type = String, member = minor_width)]
+        #[property(name = "assembly", get, set, type = String, member = assembly)]
+        #[property(name = "start-angle", get, set, type = String, member = start_angle)]
+        #[property(name = "end-angle", get, set, type = String, member = end_angle)]
+        #[property(name = "splice", get, set, type = bool, member = splice)]
+        #[property(name = "min-segment", get, set, type = String, member = min_segment)]
+        #[property(name = "join-allowance", get, set, type = String, member = join_allowance)]
+        #[property(name = "fill", get, set, type = bool, member = fill)]
+        #[property(name = "vendor", get, set, type = String, member = vendor)]
+        #[property(name = "pack-size", get, set, type = String, member = pack_size)]
+        #[property(name = "bundles", get, set, type = String, member = bundles)]
         pub entry_data: RefCell<EntryData>,
 
         // Strings for direct binding to display columns
@@ -64,6 +145,8 @@ mod imp {
         pub length_display: RefCell<String>,
         #[property(get, set)]
         pub width_display: RefCell<String>,
+        #[property(get, set)]
+        pub angles_display: RefCell<String>,
 
         // Values used for sorting in the column view
         // Not intended for serde
@@ -76,6 +159,11 @@ mod imp {
         #[property(get, set)]
         pub width_sort: Cell<f64>,
 
+        // Whether the material has no matching supply
+        // Not intended for serde
+        #[property(get, set)]
+        pub material_warning: Cell<bool>,
+
         // Properties that determine formatting
         #[property(get, set)]
         pub price_precision: Cell<u32>,
@@ -83,6 +171,10 @@ mod imp {
         pub(super) size_format: Cell<u32>,
         #[property(get, set)]
         pub(super) size_precision: Cell<u32>,
+        #[property(get, set)]
+        pub(super) display_unit: Cell<u32>,
+        #[property(get, set)]
+        pub(super) dual_units: Cell<bool>,
     }
 
     // The central trait for subclassing a GObject
@@ -130,27 +222,49 @@ impl EntryObject {
             "width-unit",
             "major-width",
             "minor-width",
+            "assembly",
+            "start-angle",
+            "end-angle",
+            "splice",
+            "min-segment",
+            "join-allowance",
+            "fill",
+            "vendor",
+            "pack-size",
+            "bundles",
         ] {
             self.notify(property_name);
         }
     }
 
+    /// Reformats the display strings, e.g., after the `NumberFormat` changes
+    pub fn refresh_display(&self) {
+        self.update_price_display();
+        self.update_length_display();
+        self.update_width_display();
+        self.update_angles_display();
+    }
+
     fn setup_callbacks(&self) {
         // Callbacks for updating display strings
-        for property_name in ["price", "price-precision"] {
+        for property_name in ["price", "pack-size", "bundles", "price-precision"] {
             self.connect_notify(Some(property_name), |entry_object, _| {
                 entry_object.update_price_display();
             });
         }
-        self.connect_notify(Some("quantity"), |entry_object, _| {
-            entry_object.update_quantity_display();
-        });
+        for property_name in ["quantity", "fill"] {
+            self.connect_notify(Some(property_name), |entry_object, _| {
+                entry_object.update_quantity_display();
+            });
+        }
         for property_name in [
             "length-unit",
             "major-length",
             "minor-length",
             "size-format",
             "size-precision",
+            "display-unit",
+            "dual-units",
         ] {
             self.connect_notify(Some(property_name), |entry_object, _| {
                 entry_object.update_length_display();
@@ -162,11 +276,18 @@ impl EntryObject {
             "minor-width",
             "size-format",
             "size-precision",
+            "display-unit",
+            "dual-units",
         ] {
             self.connect_notify(Some(property_name), |entry_object, _| {
                 entry_object.update_width_display();
             });
         }
+        for property_name in ["start-angle", "end-angle"] {
+            self.connect_notify(Some(property_name), |entry_object, _| {
+                entry_object.update_angles_display();
+            });
+        }
 
         // Callbacks for updating sort properties
         self.connect_notify(Some("price"), |entry_object, _| {
@@ -188,13 +309,33 @@ impl EntryObject {
     }
 
     fn update_price_display(&self) {
-        let price = parse_price(&self.price(), true).unwrap();
-        self.set_price_display(format_price(price, self.price_precision()));
+        let price = parse_price(&self.price(), true, STORED_SEPARATOR).unwrap();
+        let mut price_display = format_price(price, self.price_precision());
+        let mut terms = Vec::new();
+        let pack_size = parse_quantity(&self.pack_size(), true).unwrap();
+        if pack_size > 1 {
+            terms.push(i18n_f("packs of {}", &[&pack_size.to_string()]));
+        }
+        let bundles = parse_bundles(&self.bundles(), STORED_SEPARATOR).unwrap();
+        if !bundles.is_empty() {
+            terms.push(format_bundles(&bundles, self.price_precision()));
+        }
+        if !terms.is_empty() {
+            price_display = format!("{} ({})", price_display, terms.join(" · "));
+        }
+        self.set_price_display(price_display);
     }
 
     fn update_quantity_display(&self) {
         let quantity = parse_quantity(&self.quantity(), true).unwrap();
-        self.set_quantity_display(format_quantity(quantity));
+        if self.fill() {
+            self.set_quantity_display(match quantity {
+                -1 | 0 => gettext("Fill"),
+                n => i18n_f("Fill (at least {})", &[&n.to_string()]),
+            });
+        } else {
+            self.set_quantity_display(format_quantity(quantity));
+        }
     }
 
     fn update_length_display(&self) {
@@ -204,21 +345,32 @@ impl EntryObject {
             &self.minor_length(),
         );
         let format = FractionFormat::from(self.size_format(), self.size_precision());
-        self.set_length_display(length.format(&format));
+        let unit = SizeUnit::from_display(self.display_unit());
+        self.set_length_display(length.format_in(unit.as_ref(), self.dual_units(), &format));
+    }
+
+    fn update_angles_display(&self) {
+        let angles = parse_angles(&self.start_angle(), &self.end_angle());
+        if angles.is_square() {
+            self.set_angles_display("");
+        } else {
+            self.set_angles_display(format_angles(&angles));
+        }
     }
 
     fn update_width_display(&self) {
         if self.dimension() == 1 {
             let width = Size::from(self.width_unit(), &self.major_width(), &self.minor_width());
             let format = FractionFormat::from(self.size_format(), self.size_precision());
-            self.set_width_display(width.format(&format));
+            let unit = SizeUnit::from_display(self.display_unit());
+            self.set_width_display(width.format_in(unit.as_ref(), self.dual_units(), &format));
         } else {
             self.set_width_display("");
         }
     }
 
     fn update_price_sort(&self) {
-        let price = parse_price(&self.price(), true).unwrap();
+        let price = parse_price(&self.price(), true, STORED_SEPARATOR).unwrap();
         let price_sort: f64 = price.try_into().unwrap();
         self.set_price_sort(price_sort);
     }
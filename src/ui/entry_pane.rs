@@ -1,15 +1,20 @@
 use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
 use std::sync::OnceLock;
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
+use gettextrs::gettext;
 use gtk::gdk::{Key, ModifierType};
 use gtk::glib::{Properties, clone, subclass::InitializingObject, subclass::Signal};
 use gtk::{CompositeTemplate, gio::ListStore, glib};
 
+use super::dialogs::{scale_failed_dialog, scale_quantities_dialog};
 use super::entry::{EntryData, EntryObject};
+use super::number_format::{NumberFormat, number_format};
 use super::unit_row::UnitRow;
 use super::utils::*;
+use crate::i18n::ni18n_f;
 use crate::size::SizeUnit;
 
 mod imp {
@@ -28,10 +33,26 @@ mod imp {
         #[template_child]
         pub(super) material_field: TemplateChild<adw::EntryRow>,
         #[template_child]
+        pub(super) material_warning: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub(super) material_menu_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub(super) material_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
         pub(super) price_field: TemplateChild<adw::EntryRow>,
         #[template_child]
+        pub(super) pack_size_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) bundles_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) vendor_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) assembly_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
         pub(super) quantity_field: TemplateChild<adw::EntryRow>,
         #[template_child]
+        pub(super) fill_field: TemplateChild<adw::SwitchRow>,
+        #[template_child]
         pub(super) length_unit_field: TemplateChild<UnitRow>,
         #[template_child]
         pub(super) major_length_field: TemplateChild<adw::EntryRow>,
@@ -43,6 +64,16 @@ mod imp {
         pub(super) major_width_field: TemplateChild<adw::EntryRow>,
         #[template_child]
         pub(super) minor_width_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) start_angle_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) end_angle_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) splice_field: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) min_segment_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) join_allowance_field: TemplateChild<adw::EntryRow>,
 
         // Used to change the title of the property entry fields
         #[template_child]
@@ -52,6 +83,14 @@ mod imp {
         #[template_child]
         pub(super) width_group: TemplateChild<adw::PreferencesGroup>,
 
+        // Used to toggle visibility of angle entry fields
+        #[template_child]
+        pub(super) angles_group: TemplateChild<adw::PreferencesGroup>,
+
+        // Used to toggle visibility of splice entry fields
+        #[template_child]
+        pub(super) splice_group: TemplateChild<adw::PreferencesGroup>,
+
         // Used to switch between the column view and a placeholder
         #[template_child]
         pub(super) content_stack: TemplateChild<gtk::Stack>,
@@ -64,14 +103,34 @@ mod imp {
         #[property(get, set)]
         pub(super) entry_descriptor: RefCell<String>,
 
-        // Whether to enable the price field
+        // Whether to enable the price, pack size, bundle, and vendor fields
         #[property(get, set)]
         pub(super) allow_price: Cell<bool>,
 
-        // Whether to require a non-empty value for the quantity
+        // Whether to enable the assembly field
+        #[property(get, set)]
+        pub(super) allow_assembly: Cell<bool>,
+
+        // Whether to enable the angle fields (for 1D entries)
+        #[property(get, set)]
+        pub(super) allow_angles: Cell<bool>,
+
+        // Whether to enable the splice fields (for 1D entries)
+        #[property(get, set)]
+        pub(super) allow_splice: Cell<bool>,
+
+        // Whether to enable the fill field (for 1D entries)
+        #[property(get, set)]
+        pub(super) allow_fill: Cell<bool>,
+
+        // Whether to require a non-empty value for the quantity (unless filling offcuts)
         #[property(get, set)]
         pub(super) require_quantity: Cell<bool>,
 
+        // Whether to warn about materials that have no supply
+        #[property(get, set)]
+        pub(super) check_supply: Cell<bool>,
+
         // Whether all entry fields are valid
         #[property(get, set)]
         pub(super) all_entries_valid: Cell<bool>,
@@ -82,6 +141,10 @@ mod imp {
         #[property(get, set)]
         pub(super) size_precision: Cell<u32>,
         #[property(get, set)]
+        pub(super) display_unit: Cell<u32>,
+        #[property(get, set)]
+        pub(super) dual_units: Cell<bool>,
+        #[property(get, set)]
         pub(super) price_precision: Cell<u32>,
         #[property(get, set)]
         pub(super) default_unit: Cell<u32>,
@@ -94,6 +157,21 @@ mod imp {
 
         // Data model
         pub(super) entries: RefCell<Option<ListStore>>,
+
+        // One representative entry for each material used in the project (set by the window)
+        pub(super) known_materials: RefCell<Vec<EntryData>>,
+
+        // Materials (name and dimension) that have at least one supply
+        pub(super) supplied_materials: RefCell<HashSet<(String, u32)>>,
+
+        // Names of the assemblies defined in the project (set by the window)
+        pub(super) known_assemblies: RefCell<Vec<String>>,
+
+        // Entries currently listed in the material drop-down
+        pub(super) material_suggestions: RefCell<Vec<EntryData>>,
+
+        // Set while update_fields() runs so field callbacks don't modify the selected rows
+        pub(super) updating_fields: Cell<bool>,
     }
 
     // The central trait for subclassing a GObject
@@ -110,6 +188,10 @@ mod imp {
             // Set up widget actions
             klass.install_action("pane.add", None, |pane, _, _| pane.add_entry());
             klass.install_action("pane.delete", None, |pane, _, _| pane.delete_entry());
+            klass.install_action("pane.duplicate", None, |pane, _, _| {
+                pane.duplicate_entries()
+            });
+            klass.install_action("pane.scale", None, |pane, _, _| pane.scale_quantities());
             klass.install_action("pane.next", None, |pane, _, _| pane.next_row());
             klass.install_action("pane.prev", None, |pane, _, _| pane.prev_row());
             klass.install_action("pane.unsort", None, |pane, _, _| pane.unsort());
@@ -117,6 +199,12 @@ mod imp {
             // Set up keybindings for widget actions
             klass.add_binding_action(Key::Return, ModifierType::CONTROL_MASK, "pane.add");
             klass.add_binding_action(Key::D, ModifierType::CONTROL_MASK, "pane.delete");
+            klass.add_binding_action(
+                Key::D,
+                ModifierType::CONTROL_MASK | ModifierType::SHIFT_MASK,
+                "pane.duplicate",
+            );
+            klass.add_binding_action(Key::M, ModifierType::CONTROL_MASK, "pane.scale");
             klass.add_binding_action(Key::J, ModifierType::CONTROL_MASK, "pane.next");
             klass.add_binding_action(Key::K, ModifierType::CONTROL_MASK, "pane.prev");
             klass.add_binding_action(Key::U, ModifierType::CONTROL_MASK, "pane.unsort");
@@ -167,6 +255,13 @@ glib::wrapper! {
 }
 
 impl EntryPane {
+    pub fn append_entry_data(&self, entry_data: EntryData) {
+        let entry_object = self.new_entry_object();
+        entry_object.replace_data(entry_data);
+        self.entries().append(&entry_object);
+        self.signal_entries_updated();
+    }
+
     pub fn entry_data(&self) -> Vec<EntryData> {
         self.entries()
             .iter::<EntryObject>()
@@ -175,6 +270,54 @@ impl EntryPane {
             .collect()
     }
 
+    pub fn selected_entry_data(&self) -> Option<EntryData> {
+        self.selected_entry_object().map(|x| x.entry_data())
+    }
+
+    /// Updates the assembly names accepted by the assembly field
+    pub fn update_assemblies(&self, names: Vec<String>) {
+        self.imp().known_assemblies.replace(names);
+        self.validate_all_entries();
+    }
+
+    /// Updates material suggestions and missing-supply warnings
+    pub fn update_materials(&self, known: Vec<EntryData>, supplied: HashSet<(String, u32)>) {
+        let imp = self.imp();
+        imp.known_materials.replace(known);
+        imp.supplied_materials.replace(supplied);
+        self.update_material_list();
+        self.update_material_warnings();
+    }
+
+    /// Reformats the displayed values, e.g., after the `NumberFormat` changes
+    pub fn refresh_display(&self) {
+        for entry_object in self.entries().iter::<EntryObject>().filter_map(Result::ok) {
+            entry_object.refresh_display();
+        }
+    }
+
+    /// Rewrites the decimal fields written in the `previous` format in the active one
+    pub fn reformat_fields(&self, previous: &NumberFormat) {
+        for field in self.decimal_fields() {
+            field.set_text(&number_format().to_field(&previous.to_stored(&field.text())));
+        }
+    }
+
+    /// Moves every entry in assembly `old` to assembly `new`
+    pub fn rename_assembly(&self, old: &str, new: &str) {
+        let mut renamed = false;
+        for entry_object in self.entries().iter::<EntryObject>().filter_map(Result::ok) {
+            if entry_object.assembly() == old {
+                entry_object.set_assembly(new);
+                renamed = true;
+            }
+        }
+        if renamed {
+            self.signal_entries_updated();
+            self.update_fields();
+        }
+    }
+
     pub fn replace_entry_data(&self, entry_data: Vec<EntryData>) {
         let entries = self.entries();
         entries.remove_all();
@@ -203,6 +346,18 @@ impl EntryPane {
         }
     }
 
+    /// Matches `text` to a known material, ignoring case and surrounding whitespace
+    fn canonical_material(&self, text: &str) -> String {
+        let text = text.trim();
+        self.imp()
+            .known_materials
+            .borrow()
+            .iter()
+            .find(|e| e.material.trim().to_lowercase() == text.to_lowercase())
+            .map(|e| e.material.clone())
+            .unwrap_or_else(|| text.to_string())
+    }
+
     fn column(&self, column_index: u32) -> gtk::ColumnViewColumn {
         self.imp()
             .column_view
@@ -212,16 +367,40 @@ impl EntryPane {
             .unwrap()
     }
 
+    /// Returns the value shared by all selected rows, or None if it differs between them
+    fn common_value<F>(&self, value: F) -> Option<String>
+    where
+        F: Fn(&EntryObject) -> String,
+    {
+        let selected = self.selected_entry_objects();
+        let first = value(selected.first()?);
+        selected.iter().all(|x| value(x) == first).then_some(first)
+    }
+
+    /// Fields holding decimals, which are stored with `STORED_SEPARATOR` but edited in the active
+    /// format
+    fn decimal_fields(&self) -> [&adw::EntryRow; 10] {
+        let imp = self.imp();
+        [
+            &imp.price_field,
+            &imp.bundles_field,
+            &imp.major_length_field,
+            &imp.minor_length_field,
+            &imp.major_width_field,
+            &imp.minor_width_field,
+            &imp.start_angle_field,
+            &imp.end_angle_field,
+            &imp.min_segment_field,
+            &imp.join_allowance_field,
+        ]
+    }
+
     fn delete_entry(&self) {
         if let Some(i) = self.selection() {
-            // TODO: Looping is not optimal - we could avoid this if we knew the sort permutation
-            // Maybe unsort -> delete -> sort?
-            let entry_object = self.selection_model_item(i);
             let entries = self.entries();
-            for j in 0..self.n_items() {
-                if entry_object == entries.item(j).and_downcast::<EntryObject>().unwrap() {
+            for entry_object in self.selected_entry_objects() {
+                if let Some(j) = entries.find(&entry_object) {
                     entries.remove(j);
-                    break;
                 }
             }
 
@@ -239,6 +418,32 @@ impl EntryPane {
         self.update_fields();
     }
 
+    fn duplicate_entries(&self) {
+        let duplicates: Vec<_> = self
+            .selected_entry_objects()
+            .iter()
+            .map(|x| {
+                let entry_object = self.new_entry_object();
+                entry_object.replace_data(x.entry_data());
+                self.entries().append(&entry_object);
+                entry_object
+            })
+            .collect();
+        if !duplicates.is_empty() {
+            self.select_entry_objects(&duplicates);
+            self.signal_entries_updated();
+        }
+    }
+
+    /// Stored text as it is edited in `field` (see `stored_text`)
+    fn field_text(&self, field: &adw::EntryRow, text: &str) -> String {
+        if self.decimal_fields().contains(&field) {
+            number_format().to_field(text)
+        } else {
+            text.to_string()
+        }
+    }
+
     fn entries(&self) -> ListStore {
         self.imp().entries.borrow().clone().unwrap()
     }
@@ -255,12 +460,27 @@ impl EntryPane {
         self.bind_property("size-precision", &entry_object, "size-precision")
             .sync_create()
             .build();
+        self.bind_property("display-unit", &entry_object, "display-unit")
+            .sync_create()
+            .build();
+        self.bind_property("dual-units", &entry_object, "dual-units")
+            .sync_create()
+            .build();
         self.bind_property("price-precision", &entry_object, "price-precision")
             .sync_create()
             .build();
         entry_object
     }
 
+    fn is_supplied(&self, material: &str, dimension: u32) -> bool {
+        !self.check_supply()
+            || self
+                .imp()
+                .supplied_materials
+                .borrow()
+                .contains(&(material.to_string(), dimension))
+    }
+
     fn next_row(&self) {
         match self.selection() {
             None => self.set_selection(Some(0)),
@@ -277,11 +497,96 @@ impl EntryPane {
         }
     }
 
+    fn scale_quantities(&self) {
+        // Scale the whole list if nothing is selected
+        let mut entry_objects = self.selected_entry_objects();
+        if entry_objects.is_empty() {
+            entry_objects = self.entries().iter().filter_map(Result::ok).collect();
+        }
+        if entry_objects.is_empty() {
+            return;
+        }
+
+        let dialog = scale_quantities_dialog(entry_objects.len());
+        dialog.connect_response(
+            Some("scale"),
+            clone!(
+                #[weak(rename_to = pane)]
+                self,
+                move |dialog, _| {
+                    let factor = dialog
+                        .extra_child()
+                        .and_downcast::<gtk::SpinButton>()
+                        .unwrap()
+                        .value_as_int() as i64;
+                    // Unlimited quantities are left as they are
+                    let mut scaled = Vec::new();
+                    for entry_object in entry_objects.iter() {
+                        if let Ok(quantity) = parse_quantity(&entry_object.quantity(), false) {
+                            match quantity.checked_mul(factor) {
+                                Some(quantity) => scaled.push((entry_object, quantity)),
+                                None => {
+                                    scale_failed_dialog().present(Some(&pane));
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                    for (entry_object, quantity) in scaled {
+                        entry_object.set_quantity(quantity.to_string());
+                    }
+                    pane.signal_entries_updated();
+                    pane.update_fields();
+                }
+            ),
+        );
+        dialog.present(Some(self));
+    }
+
+    fn select_entry_objects(&self, entry_objects: &[EntryObject]) {
+        let selection_model = self.selection_model();
+        selection_model.unselect_all();
+        for j in 0..self.n_items() {
+            if entry_objects.contains(&self.selection_model_item(j)) {
+                selection_model.select_item(j, false);
+            }
+        }
+    }
+
+    fn select_material(&self, template: &EntryData) {
+        let imp = self.imp();
+        imp.material_menu_button.popdown();
+        let entry_objects = self.selected_entry_objects();
+        if entry_objects.is_empty() {
+            imp.dimension_field.set_active(template.dimension);
+            imp.length_unit_field.set_selected(template.length_unit);
+            imp.width_unit_field.set_selected(template.width_unit);
+        } else {
+            // Don't touch the units of existing entries (that would change their size)
+            for entry_object in entry_objects {
+                entry_object.set_material(template.material.clone());
+            }
+            self.signal_entries_updated();
+        }
+        imp.material_field.set_text(&template.material);
+    }
+
     fn selected_entry_object(&self) -> Option<EntryObject> {
         self.selection()
             .and_then(|i| self.selection_model().item(i).and_downcast::<EntryObject>())
     }
 
+    fn selected_entry_objects(&self) -> Vec<EntryObject> {
+        let bitset = self.selection_model().selection();
+        (0..bitset.size() as u32)
+            .filter_map(|i| {
+                self.selection_model()
+                    .item(bitset.nth(i))
+                    .and_downcast::<EntryObject>()
+            })
+            .collect()
+    }
+
     fn set_selection(&self, selection: Option<u32>) {
         match selection {
             Some(i) => self.selection_model().select_item(i, true),
@@ -309,6 +614,15 @@ impl EntryPane {
             .unwrap()
     }
 
+    /// The text of `field` as it is stored in an `EntryObject`
+    fn stored_text(&self, field: &adw::EntryRow) -> String {
+        if self.decimal_fields().contains(&field) {
+            number_format().to_stored(&field.text())
+        } else {
+            field.text().to_string()
+        }
+    }
+
     fn setup_bindings(&self) {
         let imp = self.imp();
 
@@ -327,15 +641,35 @@ impl EntryPane {
         // Set the title of the length field based on the unit type
         imp.length_unit_field
             .bind_property("selected", &imp.major_length_field.get(), "title")
-            .transform_to(|_, i| Some(SizeUnit::from(i).major_name()))
+            .transform_to(|_, i| Some(major_unit_name(&SizeUnit::from(i))))
             .sync_create()
             .build();
         imp.width_unit_field
             .bind_property("selected", &imp.major_width_field.get(), "title")
-            .transform_to(|_, i| Some(SizeUnit::from(i).major_name()))
+            .transform_to(|_, i| Some(major_unit_name(&SizeUnit::from(i))))
             .sync_create()
             .build();
 
+        // Splice limits are entered in the smaller length unit
+        for (field, title) in [
+            (&imp.min_segment_field, gettext("Minimum Segment")),
+            (&imp.join_allowance_field, gettext("Joint Allowance")),
+        ] {
+            imp.length_unit_field
+                .bind_property("selected", &field.get(), "title")
+                .transform_to(move |_, i| {
+                    let unit = SizeUnit::from(i);
+                    let name = if unit.has_minor() {
+                        minor_unit_name(&unit)
+                    } else {
+                        major_unit_name(&unit)
+                    };
+                    Some(format!("{} ({})", title, name))
+                })
+                .sync_create()
+                .build();
+        }
+
         imp.dimension_field
             .bind_property("active", &imp.width_group.get(), "visible")
             .transform_to(|_, i: u32| Some(i == 1))
@@ -352,6 +686,27 @@ impl EntryPane {
         self.bind_property("allow-price", &self.column(2), "visible")
             .sync_create()
             .build();
+        self.bind_property("allow-price", &imp.pack_size_field.get(), "visible")
+            .sync_create()
+            .build();
+        self.bind_property("allow-price", &imp.bundles_field.get(), "visible")
+            .sync_create()
+            .build();
+        self.bind_property("allow-price", &imp.vendor_field.get(), "visible")
+            .sync_create()
+            .build();
+        self.bind_property("allow-price", &self.column(8), "visible")
+            .sync_create()
+            .build();
+        self.bind_property("allow-assembly", &imp.assembly_field.get(), "visible")
+            .sync_create()
+            .build();
+        self.bind_property("allow-assembly", &self.column(6), "visible")
+            .sync_create()
+            .build();
+        self.bind_property("allow-angles", &self.column(7), "visible")
+            .sync_create()
+            .build();
     }
 
     fn setup_callbacks(&self) {
@@ -359,11 +714,18 @@ impl EntryPane {
         for field in [
             &imp.material_field,
             &imp.price_field,
+            &imp.pack_size_field,
+            &imp.bundles_field,
+            &imp.assembly_field,
             &imp.quantity_field,
             &imp.major_length_field,
             &imp.minor_length_field,
             &imp.major_width_field,
             &imp.minor_width_field,
+            &imp.start_angle_field,
+            &imp.end_angle_field,
+            &imp.min_segment_field,
+            &imp.join_allowance_field,
         ] {
             field.connect_changed(clone!(
                 #[weak(rename_to = pane)]
@@ -374,22 +736,32 @@ impl EntryPane {
             ));
         }
 
-        // Callback for major-width defined below
+        // Callbacks for material and major-width defined below
         for (field, property_name) in [
-            (&imp.material_field, "material"),
             (&imp.name_field, "name"),
             (&imp.price_field, "price"),
+            (&imp.pack_size_field, "pack-size"),
+            (&imp.bundles_field, "bundles"),
+            (&imp.vendor_field, "vendor"),
+            (&imp.assembly_field, "assembly"),
             (&imp.quantity_field, "quantity"),
             (&imp.major_length_field, "major-length"),
             (&imp.minor_length_field, "minor-length"),
             (&imp.minor_width_field, "minor-width"),
+            (&imp.start_angle_field, "start-angle"),
+            (&imp.end_angle_field, "end-angle"),
+            (&imp.min_segment_field, "min-segment"),
+            (&imp.join_allowance_field, "join-allowance"),
         ] {
             field.connect_apply(clone!(
                 #[weak(rename_to = pane)]
                 self,
                 move |entry| {
-                    if let Some(entry_object) = pane.selected_entry_object() {
-                        entry_object.set_property(property_name, entry.text());
+                    let entry_objects = pane.selected_entry_objects();
+                    for entry_object in entry_objects.iter() {
+                        entry_object.set_property(property_name, pane.stored_text(entry));
+                    }
+                    if !entry_objects.is_empty() {
                         pane.signal_entries_updated();
                     }
                 }
@@ -404,19 +776,63 @@ impl EntryPane {
                 #[weak(rename_to = pane)]
                 self,
                 move |entry| {
-                    if let Some(entry_object) = pane.selected_entry_object() {
+                    if pane.imp().updating_fields.get() {
+                        return;
+                    }
+                    let entry_objects = pane.selected_entry_objects();
+                    for entry_object in entry_objects.iter() {
                         entry_object.set_property(property_name, entry.selected());
+                    }
+                    if !entry_objects.is_empty() {
                         pane.signal_entries_updated();
                     }
                 }
             ));
         }
+        imp.fill_field.connect_active_notify(clone!(
+            #[weak(rename_to = pane)]
+            self,
+            move |entry| {
+                if pane.imp().updating_fields.get() {
+                    return;
+                }
+                let entry_objects = pane.selected_entry_objects();
+                for entry_object in entry_objects.iter() {
+                    entry_object.set_fill(entry.is_active());
+                }
+                if !entry_objects.is_empty() {
+                    pane.signal_entries_updated();
+                }
+                pane.validate_all_entries();
+            }
+        ));
+        imp.splice_field.connect_active_notify(clone!(
+            #[weak(rename_to = pane)]
+            self,
+            move |entry| {
+                if pane.imp().updating_fields.get() {
+                    return;
+                }
+                let entry_objects = pane.selected_entry_objects();
+                for entry_object in entry_objects.iter() {
+                    entry_object.set_splice(entry.is_active());
+                }
+                if !entry_objects.is_empty() {
+                    pane.signal_entries_updated();
+                }
+                pane.validate_all_entries();
+            }
+        ));
         imp.dimension_field.connect_active_notify(clone!(
             #[weak(rename_to = pane)]
             self,
             move |entry| {
-                if let Some(entry_object) = pane.selected_entry_object() {
-                    if entry.active() == 0 {
+                if pane.imp().updating_fields.get() {
+                    return;
+                }
+                let entry_objects = pane.selected_entry_objects();
+                if !entry_objects.is_empty() && entry.active() == 0 {
+                    for entry_object in entry_objects.iter() {
                         // Don't update the dimension to 2D until a width has been set
                         entry_object.set_dimension(0);
 
@@ -424,29 +840,74 @@ impl EntryPane {
                         entry_object.set_width_unit(pane.default_unit());
                         entry_object.set_major_width("");
                         entry_object.set_minor_width("");
+                    }
 
-                        pane.signal_entries_updated();
+                    pane.signal_entries_updated();
 
-                        // Clear hidden width fields
-                        pane.update_fields();
-                    }
+                    // Clear hidden width fields
+                    pane.update_fields();
                 }
                 pane.validate_all_entries();
+                pane.update_material_warnings();
+            }
+        ));
+        imp.material_field.connect_apply(clone!(
+            #[weak(rename_to = pane)]
+            self,
+            move |entry| {
+                let entry_objects = pane.selected_entry_objects();
+                if !entry_objects.is_empty() {
+                    let material = pane.canonical_material(&entry.text());
+                    for entry_object in entry_objects.iter() {
+                        entry_object.set_material(material.clone());
+                    }
+                    pane.signal_entries_updated();
+                    if entry.text() != material {
+                        entry.set_text(&material);
+                    }
+                }
+            }
+        ));
+        imp.material_field.connect_changed(clone!(
+            #[weak(rename_to = pane)]
+            self,
+            move |_| {
+                pane.update_material_list();
+                pane.update_material_warnings();
+            }
+        ));
+        imp.material_list.connect_row_activated(clone!(
+            #[weak(rename_to = pane)]
+            self,
+            move |_, row| {
+                let suggestion =
+                    pane.imp().material_suggestions.borrow()[row.index() as usize].clone();
+                pane.select_material(&suggestion);
             }
         ));
         imp.major_width_field.connect_apply(clone!(
             #[weak(rename_to = pane)]
             self,
             move |entry| {
-                if let Some(entry_object) = pane.selected_entry_object() {
-                    let imp = pane.imp();
-                    if imp.dimension_field.active() == 1 {
+                let entry_objects = pane.selected_entry_objects();
+                if !entry_objects.is_empty() && pane.imp().dimension_field.active() == 1 {
+                    for entry_object in entry_objects.iter() {
                         // Update the dimension to 2D once we have a valid width
-                        entry_object.set_dimension(imp.dimension_field.active());
+                        entry_object.set_dimension(1);
 
-                        entry_object.set_major_width(entry.text());
-                        pane.signal_entries_updated();
+                        // Angled ends only apply to 1D parts
+                        entry_object.set_start_angle("");
+                        entry_object.set_end_angle("");
+
+                        // So do splicing and filling offcuts
+                        entry_object.set_fill(false);
+                        entry_object.set_splice(false);
+                        entry_object.set_min_segment("");
+                        entry_object.set_join_allowance("");
+
+                        entry_object.set_major_width(pane.stored_text(entry));
                     }
+                    pane.signal_entries_updated();
                 }
             }
         ));
@@ -477,6 +938,36 @@ impl EntryPane {
         self.connect_require_quantity_notify(|pane| {
             pane.validate_all_entries();
         });
+        self.connect_allow_assembly_notify(|pane| {
+            pane.validate_all_entries();
+        });
+        self.connect_allow_angles_notify(|pane| {
+            pane.update_angles_visibility();
+            pane.validate_all_entries();
+        });
+        self.connect_allow_splice_notify(|pane| {
+            pane.update_splice_visibility();
+            pane.validate_all_entries();
+        });
+        self.connect_allow_fill_notify(|pane| {
+            pane.update_fill_visibility();
+            pane.validate_all_entries();
+        });
+        imp.dimension_field.connect_active_notify(clone!(
+            #[weak(rename_to = pane)]
+            self,
+            move |_| {
+                pane.update_angles_visibility();
+                pane.update_splice_visibility();
+                pane.update_fill_visibility();
+            }
+        ));
+        self.update_angles_visibility();
+        self.update_splice_visibility();
+        self.update_fill_visibility();
+        self.connect_check_supply_notify(|pane| {
+            pane.update_material_warnings();
+        });
         self.connect_all_entries_valid_notify(|pane| {
             pane.update_can_add();
         });
@@ -540,11 +1031,7 @@ impl EntryPane {
             .model(&self.entries())
             .sorter(&column_view.sorter().unwrap())
             .build();
-        let selection = gtk::SingleSelection::builder()
-            .model(&sorter)
-            .autoselect(false)
-            .can_unselect(true)
-            .build();
+        let selection = gtk::MultiSelection::new(Some(sorter));
         column_view.set_model(Some(&selection));
 
         // Define property mappings for each column
@@ -554,6 +1041,43 @@ impl EntryPane {
         self.setup_column_factory(3, "quantity_display", "quantity_sort", true);
         self.setup_column_factory(4, "length_display", "length_sort", true);
         self.setup_column_factory(5, "width_display", "width_sort", true);
+        self.setup_column_factory(6, "assembly", "assembly", false);
+        self.setup_column_factory(7, "angles_display", "angles_display", false);
+        self.setup_column_factory(8, "vendor", "vendor", false);
+
+        // Highlight materials that have no supply
+        let factory = self
+            .column(0)
+            .factory()
+            .and_downcast::<gtk::SignalListItemFactory>()
+            .unwrap();
+        factory.connect_bind(move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let entry_object = list_item.item().and_downcast::<EntryObject>().unwrap();
+            let label = list_item.child().and_downcast::<gtk::Label>().unwrap();
+            entry_object
+                .bind_property("material-warning", &label, "css-classes")
+                .transform_to(|_, warning: bool| {
+                    Some(if warning {
+                        vec![String::from("warning")]
+                    } else {
+                        vec![]
+                    })
+                })
+                .sync_create()
+                .build();
+            entry_object
+                .bind_property("material-warning", &label, "tooltip-text")
+                .transform_to(|_, warning: bool| {
+                    Some(if warning {
+                        gettext("No supply has this material")
+                    } else {
+                        String::new()
+                    })
+                })
+                .sync_create()
+                .build();
+        });
     }
 
     fn setup_entries(&self) {
@@ -577,26 +1101,47 @@ impl EntryPane {
 
     fn update_can_delete(&self) {
         self.action_set_enabled("pane.delete", self.selection().is_some());
+        self.action_set_enabled("pane.duplicate", self.selection().is_some());
     }
 
     fn update_entry_object(&self, entry: &EntryObject) {
         let imp = self.imp();
         entry.set_dimension(imp.dimension_field.active());
         entry.set_name(imp.name_field.text().to_string());
-        entry.set_material(imp.material_field.text().to_string());
-        entry.set_price(imp.price_field.text().to_string());
+        entry.set_material(self.canonical_material(&imp.material_field.text()));
+        entry.set_price(self.stored_text(&imp.price_field));
+        if self.allow_price() {
+            entry.set_pack_size(imp.pack_size_field.text().to_string());
+            entry.set_bundles(self.stored_text(&imp.bundles_field));
+            entry.set_vendor(imp.vendor_field.text().to_string());
+        }
+        if self.allow_assembly() {
+            entry.set_assembly(imp.assembly_field.text().to_string());
+        }
+        if self.use_angles() {
+            entry.set_start_angle(self.stored_text(&imp.start_angle_field));
+            entry.set_end_angle(self.stored_text(&imp.end_angle_field));
+        }
+        if self.use_fill() {
+            entry.set_fill(imp.fill_field.is_active());
+        }
+        if self.use_splice() {
+            entry.set_splice(imp.splice_field.is_active());
+            entry.set_min_segment(self.stored_text(&imp.min_segment_field));
+            entry.set_join_allowance(self.stored_text(&imp.join_allowance_field));
+        }
         entry.set_quantity(imp.quantity_field.text().to_string());
         entry.set_length_unit(imp.length_unit_field.selected());
-        entry.set_major_length(imp.major_length_field.text().to_string());
+        entry.set_major_length(self.stored_text(&imp.major_length_field));
         if self.use_minor_length() {
-            entry.set_minor_length(imp.minor_length_field.text().to_string());
+            entry.set_minor_length(self.stored_text(&imp.minor_length_field));
         }
         entry.set_width_unit(imp.width_unit_field.selected());
         if self.use_width() {
-            entry.set_major_width(imp.major_width_field.text().to_string());
+            entry.set_major_width(self.stored_text(&imp.major_width_field));
         }
         if self.use_minor_width() {
-            entry.set_minor_width(imp.minor_width_field.text().to_string());
+            entry.set_minor_width(self.stored_text(&imp.minor_width_field));
         }
         self.signal_entries_updated();
 
@@ -610,18 +1155,40 @@ impl EntryPane {
 
     fn update_fields(&self) {
         let imp = self.imp();
+        imp.updating_fields.set(true);
+        let n_selected = self.selected_entry_objects().len();
         match self.selected_entry_object() {
             Some(entry_object) => {
-                imp.name_field.set_text(&entry_object.name());
-                imp.material_field.set_text(&entry_object.material());
-                imp.price_field.set_text(&entry_object.price());
-                imp.quantity_field.set_text(&entry_object.quantity());
-                imp.major_length_field
-                    .set_text(&entry_object.major_length());
-                imp.minor_length_field
-                    .set_text(&entry_object.minor_length());
-                imp.major_width_field.set_text(&entry_object.major_width());
-                imp.minor_width_field.set_text(&entry_object.minor_width());
+                // Fields that differ between the selected rows are left empty
+                for (field, value) in [
+                    (
+                        &imp.name_field,
+                        EntryObject::name as fn(&EntryObject) -> String,
+                    ),
+                    (&imp.material_field, EntryObject::material),
+                    (&imp.price_field, EntryObject::price),
+                    (&imp.pack_size_field, EntryObject::pack_size),
+                    (&imp.bundles_field, EntryObject::bundles),
+                    (&imp.vendor_field, EntryObject::vendor),
+                    (&imp.assembly_field, EntryObject::assembly),
+                    (&imp.quantity_field, EntryObject::quantity),
+                    (&imp.major_length_field, EntryObject::major_length),
+                    (&imp.minor_length_field, EntryObject::minor_length),
+                    (&imp.major_width_field, EntryObject::major_width),
+                    (&imp.minor_width_field, EntryObject::minor_width),
+                    (&imp.start_angle_field, EntryObject::start_angle),
+                    (&imp.end_angle_field, EntryObject::end_angle),
+                    (&imp.min_segment_field, EntryObject::min_segment),
+                    (&imp.join_allowance_field, EntryObject::join_allowance),
+                ] {
+                    let text = self.common_value(value).unwrap_or_default();
+                    field.set_text(&self.field_text(field, &text));
+                }
+                let splice = self.common_value(|e| e.splice().to_string());
+                imp.splice_field
+                    .set_active(splice.as_deref() == Some("true"));
+                let fill = self.common_value(|e| e.fill().to_string());
+                imp.fill_field.set_active(fill.as_deref() == Some("true"));
 
                 // Do this after setting other fields to skip the entry animations
                 imp.dimension_field.set_active(entry_object.dimension());
@@ -633,14 +1200,24 @@ impl EntryPane {
                 for field in [
                     &imp.name_field,
                     &imp.price_field,
+                    &imp.pack_size_field,
+                    &imp.bundles_field,
+                    &imp.vendor_field,
+                    &imp.assembly_field,
                     &imp.quantity_field,
                     &imp.major_length_field,
                     &imp.minor_length_field,
                     &imp.major_width_field,
                     &imp.minor_width_field,
+                    &imp.start_angle_field,
+                    &imp.end_angle_field,
+                    &imp.min_segment_field,
+                    &imp.join_allowance_field,
                 ] {
                     field.set_text("");
                 }
+                imp.splice_field.set_active(false);
+                imp.fill_field.set_active(false);
                 let material = match imp.dimension_field.active() {
                     0 => self.default_material_1d(),
                     1 => self.default_material_2d(),
@@ -653,6 +1230,64 @@ impl EntryPane {
                 imp.width_unit_field.set_selected(self.default_unit());
             }
         }
+        let description = (n_selected > 1).then(|| {
+            ni18n_f(
+                "Editing {} row",
+                "Editing {} rows",
+                n_selected as u32,
+                &[&n_selected.to_string()],
+            )
+        });
+        imp.properties_group.set_description(description.as_deref());
+        imp.updating_fields.set(false);
+        self.validate_all_entries();
+        self.update_material_warnings();
+    }
+
+    fn update_material_list(&self) {
+        let imp = self.imp();
+        let text = imp.material_field.text().trim().to_lowercase();
+        let known = imp.known_materials.borrow();
+
+        // Show everything if the field already holds a known material
+        let exact = known.iter().any(|e| e.material.to_lowercase() == text);
+        let suggestions: Vec<_> = known
+            .iter()
+            .filter(|e| exact || e.material.to_lowercase().contains(&text))
+            .cloned()
+            .collect();
+
+        imp.material_list.remove_all();
+        for suggestion in suggestions.iter() {
+            let dimension = if suggestion.dimension == 0 {
+                "1D"
+            } else {
+                "2D"
+            };
+            let unit = SizeUnit::from(suggestion.length_unit);
+            let row = adw::ActionRow::builder()
+                .title(&suggestion.material)
+                .subtitle(format!("{} · {}", dimension, major_unit_name(&unit)))
+                .use_markup(false)
+                .activatable(true)
+                .build();
+            imp.material_list.append(&row);
+        }
+        imp.material_menu_button
+            .set_sensitive(!suggestions.is_empty());
+        imp.material_suggestions.replace(suggestions);
+    }
+
+    fn update_material_warnings(&self) {
+        let imp = self.imp();
+        for entry_object in self.entries().iter::<EntryObject>().filter_map(Result::ok) {
+            let supplied = self.is_supplied(&entry_object.material(), entry_object.dimension());
+            entry_object.set_material_warning(!supplied);
+        }
+        let material = imp.material_field.text();
+        let supplied = self.is_supplied(material.trim(), imp.dimension_field.active());
+        imp.material_warning
+            .set_visible(!material.trim().is_empty() && !supplied);
     }
 
     fn update_placeholder(&self) {
@@ -664,6 +1299,30 @@ impl EntryPane {
         self.imp().content_stack.set_visible_child_name(name);
     }
 
+    fn update_angles_visibility(&self) {
+        self.imp().angles_group.set_visible(self.use_angles());
+    }
+
+    fn update_fill_visibility(&self) {
+        self.imp().fill_field.set_visible(self.use_fill());
+    }
+
+    fn update_splice_visibility(&self) {
+        self.imp().splice_group.set_visible(self.use_splice());
+    }
+
+    fn use_angles(&self) -> bool {
+        self.allow_angles() && self.imp().dimension_field.active() == 0
+    }
+
+    fn use_fill(&self) -> bool {
+        self.allow_fill() && self.imp().dimension_field.active() == 0
+    }
+
+    fn use_splice(&self) -> bool {
+        self.allow_splice() && self.imp().dimension_field.active() == 0
+    }
+
     fn use_minor_length(&self) -> bool {
         SizeUnit::from(self.imp().length_unit_field.selected()).has_minor()
     }
@@ -679,53 +1338,94 @@ impl EntryPane {
     fn validate_all_entries(&self) {
         let mut all_valid = true;
         let imp = self.imp();
-        let entry = self.selected_entry_object();
-        all_valid &= validate_entry(
-            &imp.material_field.get(),
-            entry.as_ref().and_then(|e| Some(e.material())),
-            |e| e.text_length() != 0,
-        );
-        all_valid &= validate_entry(
-            &imp.name_field.get(),
-            entry.as_ref().and_then(|e| Some(e.name())),
-            |_| true,
-        );
-        all_valid &= validate_entry(
-            &imp.price_field.get(),
-            entry.as_ref().and_then(|e| Some(e.price())),
-            |e| parse_price(&e.text(), true).is_ok(),
-        );
-        all_valid &= validate_entry(
-            &imp.quantity_field.get(),
-            entry.as_ref().and_then(|e| Some(e.quantity())),
-            |e| parse_quantity(&e.text(), !self.require_quantity()).is_ok(),
-        );
-        all_valid &= validate_entry(
-            &imp.major_length_field.get(),
-            entry.as_ref().and_then(|e| Some(e.major_length())),
-            |e| parse_positive_fraction(&e.text(), false).is_ok(),
-        );
+        all_valid &= self.validate_field(&imp.material_field, EntryObject::material, |e| {
+            e.text_length() != 0
+        });
+        all_valid &= self.validate_field(&imp.name_field, EntryObject::name, |_| true);
+        let separator = number_format().separator();
+        all_valid &= self.validate_field(&imp.price_field, EntryObject::price, |e| {
+            parse_price(&e.text(), true, separator).is_ok()
+        });
+        all_valid &= self.validate_field(&imp.pack_size_field, EntryObject::pack_size, |e| {
+            parse_quantity(&e.text(), true).is_ok()
+        });
+        all_valid &= self.validate_field(&imp.bundles_field, EntryObject::bundles, |e| {
+            parse_bundles(&e.text(), separator).is_ok()
+        });
+        all_valid &= self.validate_field(&imp.vendor_field, EntryObject::vendor, |_| true);
+        if self.allow_assembly() {
+            all_valid &= self.validate_field(&imp.assembly_field, EntryObject::assembly, |e| {
+                let text = e.text();
+                text.is_empty() || imp.known_assemblies.borrow().iter().any(|a| *a == text)
+            });
+        }
+        all_valid &= self.validate_field(&imp.quantity_field, EntryObject::quantity, |e| {
+            let filling = self.use_fill() && imp.fill_field.is_active();
+            parse_quantity(&e.text(), !self.require_quantity() || filling).is_ok()
+        });
+        let length_unit = SizeUnit::from(imp.length_unit_field.selected());
+        let width_unit = SizeUnit::from(imp.width_unit_field.selected());
+        all_valid &= self.validate_field(&imp.major_length_field, EntryObject::major_length, |e| {
+            check_size_field(e, &length_unit, false, false)
+        });
         if self.use_minor_length() {
-            all_valid &= validate_entry(
-                &imp.minor_length_field.get(),
-                entry.as_ref().and_then(|e| Some(e.minor_length())),
-                |e| parse_positive_fraction(&e.text(), true).is_ok(),
-            );
+            all_valid &=
+                self.validate_field(&imp.minor_length_field, EntryObject::minor_length, |e| {
+                    check_size_field(e, &length_unit, true, true)
+                });
+        }
+        if self.use_angles() {
+            all_valid &=
+                self.validate_field(&imp.start_angle_field, EntryObject::start_angle, |e| {
+                    parse_angle(&e.text(), separator).is_ok()
+                });
+            all_valid &= self.validate_field(&imp.end_angle_field, EntryObject::end_angle, |e| {
+                parse_angle(&e.text(), separator).is_ok()
+            });
+        }
+        if self.use_splice() && imp.splice_field.is_active() {
+            for (field, value) in [
+                (
+                    &imp.min_segment_field,
+                    EntryObject::min_segment as fn(&EntryObject) -> String,
+                ),
+                (&imp.join_allowance_field, EntryObject::join_allowance),
+            ] {
+                all_valid &= self.validate_field(field, value, |e| {
+                    check_size_field(e, &length_unit, length_unit.has_minor(), true)
+                });
+            }
         }
         if self.use_width() {
-            all_valid &= validate_entry(
-                &imp.major_width_field.get(),
-                entry.as_ref().and_then(|e| Some(e.major_width())),
-                |e| parse_positive_fraction(&e.text(), false).is_ok(),
-            );
+            all_valid &=
+                self.validate_field(&imp.major_width_field, EntryObject::major_width, |e| {
+                    check_size_field(e, &width_unit, false, false)
+                });
         }
         if self.use_minor_width() {
-            all_valid &= validate_entry(
-                &imp.minor_width_field.get(),
-                entry.as_ref().and_then(|e| Some(e.minor_width())),
-                |e| parse_positive_fraction(&e.text(), true).is_ok(),
-            );
+            all_valid &=
+                self.validate_field(&imp.minor_width_field, EntryObject::minor_width, |e| {
+                    check_size_field(e, &width_unit, true, true)
+                });
         }
         self.set_all_entries_valid(all_valid);
     }
+
+    /// Validates a field against the selected rows, returning whether it can be used for a new row
+    ///
+    /// An empty field is not flagged if the selected rows have differing values (it leaves them
+    /// unchanged).
+    fn validate_field<F, V>(&self, field: &adw::EntryRow, value: V, validate: F) -> bool
+    where
+        F: Fn(&adw::EntryRow) -> bool,
+        V: Fn(&EntryObject) -> String,
+    {
+        let reference = self
+            .selection()
+            .map(|_| self.field_text(field, &self.common_value(value).unwrap_or_default()));
+        let mixed = reference.as_deref() == Some("") && self.selected_entry_objects().len() > 1;
+        validate_entry(field, reference, |e| {
+            (mixed && e.text_length() == 0) || validate(e)
+        }) && validate(field)
+    }
 }
@@ -0,0 +1,205 @@
+use crate::i18n::i18n_f;
+use crate::modeling::{Material, Solution};
+use crate::size::FractionFormat;
+
+const INCH: f64 = 72.0;
+const MM: f64 = 72.0 / 25.4;
+
+/// A grid of labels on a sticker sheet
+///
+/// All sizes are in points (1/72 inch), measured from the top-left corner of the page.
+#[derive(Debug, Clone)]
+pub struct LabelLayout {
+    pub name: &'static str,
+
+    /// A PWG 5101.1 paper name (e.g., "na_letter")
+    pub paper: &'static str,
+
+    pub page_width: f64,
+    pub page_height: f64,
+    pub columns: usize,
+    pub rows: usize,
+    pub label_width: f64,
+    pub label_height: f64,
+    pub margin_left: f64,
+    pub margin_top: f64,
+
+    /// Distance between the left edges of neighboring labels
+    pub pitch_horizontal: f64,
+
+    /// Distance between the top edges of neighboring labels
+    pub pitch_vertical: f64,
+}
+
+impl LabelLayout {
+    pub fn labels_per_page(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    /// Returns the top-left corner of the `i`th label on a page
+    pub fn position(&self, i: usize) -> (f64, f64) {
+        let column = i % self.columns;
+        let row = i / self.columns;
+        (
+            self.margin_left + column as f64 * self.pitch_horizontal,
+            self.margin_top + row as f64 * self.pitch_vertical,
+        )
+    }
+}
+
+/// Supported sticker sheets (indices match the "label-layout" setting)
+pub const LABEL_LAYOUTS: [LabelLayout; 5] = [
+    LabelLayout {
+        name: "Avery 5160 (Letter, 30 per sheet)",
+        paper: "na_letter",
+        page_width: 8.5 * INCH,
+        page_height: 11.0 * INCH,
+        columns: 3,
+        rows: 10,
+        label_width: 2.625 * INCH,
+        label_height: 1.0 * INCH,
+        margin_left: 0.1875 * INCH,
+        margin_top: 0.5 * INCH,
+        pitch_horizontal: 2.75 * INCH,
+        pitch_vertical: 1.0 * INCH,
+    },
+    LabelLayout {
+        name: "Avery 5163 (Letter, 10 per sheet)",
+        paper: "na_letter",
+        page_width: 8.5 * INCH,
+        page_height: 11.0 * INCH,
+        columns: 2,
+        rows: 5,
+        label_width: 4.0 * INCH,
+        label_height: 2.0 * INCH,
+        margin_left: 0.15625 * INCH,
+        margin_top: 0.5 * INCH,
+        pitch_horizontal: 4.1875 * INCH,
+        pitch_vertical: 2.0 * INCH,
+    },
+    LabelLayout {
+        name: "Avery 5167 (Letter, 80 per sheet)",
+        paper: "na_letter",
+        page_width: 8.5 * INCH,
+        page_height: 11.0 * INCH,
+        columns: 4,
+        rows: 20,
+        label_width: 1.75 * INCH,
+        label_height: 0.5 * INCH,
+        margin_left: 0.28125 * INCH,
+        margin_top: 0.5 * INCH,
+        pitch_horizontal: 2.0625 * INCH,
+        pitch_vertical: 0.5 * INCH,
+    },
+    LabelLayout {
+        name: "Avery L7160 (A4, 21 per sheet)",
+        paper: "iso_a4",
+        page_width: 210.0 * MM,
+        page_height: 297.0 * MM,
+        columns: 3,
+        rows: 7,
+        label_width: 63.5 * MM,
+        label_height: 38.1 * MM,
+        margin_left: 7.25 * MM,
+        margin_top: 15.15 * MM,
+        pitch_horizontal: 66.04 * MM,
+        pitch_vertical: 38.1 * MM,
+    },
+    LabelLayout {
+        name: "Avery L7163 (A4, 14 per sheet)",
+        paper: "iso_a4",
+        page_width: 210.0 * MM,
+        page_height: 297.0 * MM,
+        columns: 2,
+        rows: 7,
+        label_width: 99.1 * MM,
+        label_height: 38.1 * MM,
+        margin_left: 4.65 * MM,
+        margin_top: 15.15 * MM,
+        pitch_horizontal: 101.6 * MM,
+        pitch_vertical: 38.1 * MM,
+    },
+];
+
+/// The information printed on the label for a single physical part
+#[derive(Debug, Clone)]
+pub struct PartLabel {
+    pub name: String,
+    pub assembly: String,
+    pub length: String,
+    pub material: String,
+
+    /// Numbered like the cut lists in the results view (starting at 1)
+    pub cut_list: usize,
+
+    /// Which repeat of the cut list the part comes from (starting at 1)
+    pub repeat: usize,
+    pub repeats: usize,
+
+    /// Position of the part within the cut list (starting at 1)
+    pub sequence: usize,
+}
+
+impl PartLabel {
+    /// Plain-text lines, most important first
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![self.name.clone()];
+        if !self.assembly.is_empty() {
+            lines.push(self.assembly.clone());
+        }
+        lines.push(format!("{} · {}", self.length, self.material));
+        let mut cut = i18n_f(
+            "Cut list {} · #{}",
+            &[&self.cut_list.to_string(), &self.sequence.to_string()],
+        );
+        if self.repeats > 1 {
+            cut.push_str(" · ");
+            cut.push_str(&i18n_f(
+                "{} of {}",
+                &[&self.repeat.to_string(), &self.repeats.to_string()],
+            ));
+        }
+        lines.push(cut);
+        lines
+    }
+
+    /// Data encoded in the QR code (the same text as the label)
+    pub fn qr_data(&self) -> String {
+        self.lines().join("\n")
+    }
+}
+
+/// Orders materials by name and dimension so cut list numbers are stable between redraws
+pub fn sorted_materials(solution: &Solution) -> Vec<&Material> {
+    let mut materials: Vec<_> = solution.keys().collect();
+    materials.sort_by_key(|m| (m.name.clone(), m.dimension.clone() as u32));
+    materials
+}
+
+/// Generates one label for every part cut in the solution
+pub fn generate_labels(solution: &Solution, format: &FractionFormat) -> Vec<PartLabel> {
+    let mut labels = Vec::new();
+    let mut cut_list_number = 1;
+    for material in sorted_materials(solution) {
+        let sub_solution = &solution[material];
+        for cut_list in sub_solution.cut_lists.iter() {
+            for repeat in 1..=cut_list.quantity {
+                for (i, part_index) in cut_list.part_indices.iter().enumerate() {
+                    let part = &sub_solution.parts[*part_index];
+                    labels.push(PartLabel {
+                        name: part.name.clone(),
+                        assembly: part.assembly.clone(),
+                        length: part.length.format(format),
+                        material: material.name.clone(),
+                        cut_list: cut_list_number,
+                        repeat,
+                        repeats: cut_list.quantity,
+                        sequence: i + 1,
+                    });
+                }
+            }
+            cut_list_number += 1;
+        }
+    }
+    labels
+}
@@ -0,0 +1,108 @@
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+use gtk::glib;
+use serde::{Deserialize, Serialize};
+
+use super::entry::EntryData;
+use crate::i18n::i18n_f;
+
+/// A price observed for a library entry on a given date
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PriceRecord {
+    /// ISO 8601 date (YYYY-MM-DD)
+    pub date: String,
+    pub price: String,
+}
+
+/// A reusable supply stored in the material library
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibraryEntry {
+    /// The supply as it should appear in the supplies pane
+    pub entry_data: EntryData,
+
+    /// Where the supply is purchased
+    pub vendor: String,
+
+    /// Prices recorded over time, oldest first
+    ///
+    /// The last record matches `entry_data.price`.
+    pub price_history: Vec<PriceRecord>,
+}
+
+impl LibraryEntry {
+    /// Updates the entry, recording a new price if it changed
+    pub fn update(&mut self, entry_data: EntryData, vendor: String) {
+        let price_changed = match self.price_history.last() {
+            Some(record) => record.price.trim() != entry_data.price.trim(),
+            None => true,
+        };
+        if price_changed {
+            self.price_history.push(PriceRecord {
+                date: today(),
+                price: entry_data.price.clone(),
+            });
+        }
+        self.entry_data = entry_data;
+        self.vendor = vendor;
+    }
+}
+
+/// A material and supply library shared across all projects
+///
+/// Stored as JSON in the user data directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Library {
+    pub entries: Vec<LibraryEntry>,
+}
+
+impl Library {
+    /// Loads the library, returning an empty one if the file is missing
+    ///
+    /// A file that can't be read is moved aside (see `backup_file_path`) so saving the library
+    /// doesn't overwrite it.
+    pub fn load() -> Result<Self, String> {
+        let file_path = Self::file_path();
+        if !file_path.exists() {
+            return Ok(Self::default());
+        }
+        File::open(&file_path)
+            .map_err(|e| e.to_string())
+            .and_then(|file| serde_json::from_reader(file).map_err(|e| e.to_string()))
+            .map_err(|message| {
+                let backup_file_path = Self::backup_file_path();
+                match fs::rename(&file_path, &backup_file_path) {
+                    Ok(_) => i18n_f(
+                        "{} (the file was moved to \"{}\")",
+                        &[&message, &backup_file_path.display().to_string()],
+                    ),
+                    Err(_) => message,
+                }
+            })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let file_path = Self::file_path();
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let file = File::create(&file_path).map_err(|e| e.to_string())?;
+        serde_json::to_writer_pretty(file, self).map_err(|e| e.to_string())
+    }
+
+    pub fn file_path() -> PathBuf {
+        glib::user_data_dir().join("chop-chop").join("library.json")
+    }
+
+    /// Where an unreadable library file is moved
+    pub fn backup_file_path() -> PathBuf {
+        Self::file_path().with_extension("json.bak")
+    }
+}
+
+fn today() -> String {
+    glib::DateTime::now_local()
+        .and_then(|date| date.format("%Y-%m-%d"))
+        .map(|s| s.to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,513 @@
+use std::cell::{Cell, RefCell};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use gtk::glib::{Object, clone, subclass::InitializingObject};
+use gtk::{CompositeTemplate, gio::Settings, glib};
+
+use super::entry::EntryData;
+use super::entry_pane::EntryPane;
+use super::library::{Library, LibraryEntry};
+use super::number_format::{STORED_SEPARATOR, number_format};
+use super::unit_row::UnitRow;
+use super::utils::*;
+use crate::i18n::i18n_f;
+use crate::size::{FractionFormat, Size, SizeUnit};
+
+mod imp {
+    use super::*;
+
+    // Object holding the state
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/com/ohmm-software/Chop-Chop/library_dialog.ui")]
+    pub struct LibraryDialog {
+        // Entry fields
+        #[template_child]
+        pub(super) dimension_field: TemplateChild<adw::ToggleGroup>,
+        #[template_child]
+        pub(super) material_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) name_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) vendor_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) price_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) length_unit_field: TemplateChild<UnitRow>,
+        #[template_child]
+        pub(super) major_length_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) minor_length_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) width_unit_field: TemplateChild<UnitRow>,
+        #[template_child]
+        pub(super) major_width_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) minor_width_field: TemplateChild<adw::EntryRow>,
+
+        // Used to toggle visibility of width entry fields
+        #[template_child]
+        pub(super) width_group: TemplateChild<adw::PreferencesGroup>,
+
+        // Price history of the selected entry
+        #[template_child]
+        pub(super) history_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub(super) history_list: TemplateChild<gtk::ListBox>,
+
+        // Used to switch between the entry list and a placeholder
+        #[template_child]
+        pub(super) content_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) entry_list: TemplateChild<gtk::ListBox>,
+
+        #[template_child]
+        pub(super) toast_overlay: TemplateChild<adw::ToastOverlay>,
+
+        // Library contents (mirrors the file in the user data directory)
+        pub(super) library: RefCell<Library>,
+
+        // Index of the library entry being edited
+        pub(super) selection: Cell<Option<usize>>,
+
+        // Pane that library entries are imported into
+        pub(super) supplies_pane: RefCell<Option<EntryPane>>,
+
+        // App settings (used for formatting)
+        pub(super) settings: RefCell<Option<Settings>>,
+    }
+
+    // The central trait for subclassing a GObject
+    #[glib::object_subclass]
+    impl ObjectSubclass for LibraryDialog {
+        const NAME: &'static str = "ChopChopLibraryDialog";
+        type Type = super::LibraryDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            // Required for CompositeTemplate
+            klass.bind_template();
+
+            // Set up widget actions
+            klass.install_action("library.save", None, |dialog, _, _| dialog.save_entry());
+            klass.install_action("library.delete", None, |dialog, _, _| dialog.delete_entry());
+            klass.install_action("library.new", None, |dialog, _, _| {
+                dialog.set_selection(None)
+            });
+            klass.install_action("library.add-selected", None, |dialog, _, _| {
+                dialog.add_selected_supply()
+            });
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            // Required for CompositeTemplate
+            obj.init_template();
+        }
+    }
+
+    // Trait shared by all GObjects
+    impl ObjectImpl for LibraryDialog {
+        // Called when the object is constructed
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+            obj.setup_bindings();
+            obj.setup_callbacks();
+        }
+    }
+
+    // Trait shared by all widgets
+    impl WidgetImpl for LibraryDialog {}
+
+    // Trait shared by all Adwaita dialogs
+    impl AdwDialogImpl for LibraryDialog {}
+}
+
+glib::wrapper! {
+    pub struct LibraryDialog(ObjectSubclass<imp::LibraryDialog>)
+        @extends adw::Dialog, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl LibraryDialog {
+    pub fn new(settings: &Settings, supplies_pane: &EntryPane) -> Self {
+        let dialog: Self = Object::builder().build();
+        let imp = dialog.imp();
+        imp.settings.replace(Some(settings.clone()));
+        imp.supplies_pane.replace(Some(supplies_pane.clone()));
+        match Library::load() {
+            Ok(library) => {
+                imp.library.replace(library);
+            }
+            Err(message) => {
+                dialog.show_toast(&i18n_f("Failed to read library: {}", &[&message]));
+            }
+        }
+        dialog.update_list();
+        dialog.set_selection(None);
+        dialog
+    }
+
+    fn add_selected_supply(&self) {
+        let Some(entry_data) = self.supplies_pane().selected_entry_data() else {
+            self.show_toast(&gettext("No supply is selected"));
+            return;
+        };
+        let mut entry = LibraryEntry::default();
+        entry.update(entry_data, String::new());
+        self.imp().library.borrow_mut().entries.push(entry);
+        self.save_library();
+        let n = self.imp().library.borrow().entries.len();
+        self.update_list();
+        self.set_selection(Some(n - 1));
+    }
+
+    fn delete_entry(&self) {
+        if let Some(i) = self.imp().selection.get() {
+            self.imp().library.borrow_mut().entries.remove(i);
+            self.save_library();
+            self.update_list();
+            self.set_selection(None);
+        }
+    }
+
+    fn entry_data(&self) -> EntryData {
+        let imp = self.imp();
+        let stored = |field: &adw::EntryRow| number_format().to_stored(&field.text());
+        let mut entry_data = EntryData {
+            dimension: imp.dimension_field.active(),
+            name: imp.name_field.text().to_string(),
+            material: imp.material_field.text().to_string(),
+            price: stored(&imp.price_field),
+            length_unit: imp.length_unit_field.selected(),
+            major_length: stored(&imp.major_length_field),
+            width_unit: imp.width_unit_field.selected(),
+            ..Default::default()
+        };
+        if self.use_minor_length() {
+            entry_data.minor_length = stored(&imp.minor_length_field);
+        }
+        if self.use_width() {
+            entry_data.major_width = stored(&imp.major_width_field);
+        }
+        if self.use_minor_width() {
+            entry_data.minor_width = stored(&imp.minor_width_field);
+        }
+        entry_data
+    }
+
+    fn import_entry(&self, i: usize) {
+        let entry_data = self.imp().library.borrow().entries[i].entry_data.clone();
+        let message = i18n_f("Added \"{}\" to supplies", &[&entry_data.material]);
+        self.supplies_pane().append_entry_data(entry_data);
+        self.show_toast(&message);
+    }
+
+    fn save_entry(&self) {
+        let imp = self.imp();
+        let entry_data = self.entry_data();
+        let vendor = imp.vendor_field.text().to_string();
+        let i = {
+            let mut library = imp.library.borrow_mut();
+            match imp.selection.get() {
+                Some(i) => {
+                    library.entries[i].update(entry_data, vendor);
+                    i
+                }
+                None => {
+                    let mut entry = LibraryEntry::default();
+                    entry.update(entry_data, vendor);
+                    library.entries.push(entry);
+                    library.entries.len() - 1
+                }
+            }
+        };
+        self.save_library();
+        self.update_list();
+        self.set_selection(Some(i));
+    }
+
+    fn save_library(&self) {
+        if let Err(message) = self.imp().library.borrow().save() {
+            self.show_toast(&i18n_f("Failed to save library: {}", &[&message]));
+        }
+    }
+
+    fn set_selection(&self, selection: Option<usize>) {
+        let imp = self.imp();
+        imp.selection.set(selection);
+        match selection.and_then(|i| imp.entry_list.row_at_index(i as i32)) {
+            Some(row) => imp.entry_list.select_row(Some(&row)),
+            None => imp.entry_list.unselect_all(),
+        }
+        self.update_fields();
+        self.action_set_enabled("library.delete", selection.is_some());
+    }
+
+    fn settings(&self) -> Settings {
+        self.imp().settings.borrow().clone().unwrap()
+    }
+
+    fn setup_bindings(&self) {
+        let imp = self.imp();
+
+        // Show the minor length fields only if applicable
+        for (unit_field, minor_field, major_field) in [
+            (
+                &imp.length_unit_field,
+                &imp.minor_length_field,
+                &imp.major_length_field,
+            ),
+            (
+                &imp.width_unit_field,
+                &imp.minor_width_field,
+                &imp.major_width_field,
+            ),
+        ] {
+            unit_field
+                .bind_property("selected", &minor_field.get(), "visible")
+                .transform_to(|_, i| Some(SizeUnit::from(i).has_minor()))
+                .sync_create()
+                .build();
+            unit_field
+                .bind_property("selected", &major_field.get(), "title")
+                .transform_to(|_, i| Some(major_unit_name(&SizeUnit::from(i))))
+                .sync_create()
+                .build();
+        }
+
+        imp.dimension_field
+            .bind_property("active", &imp.width_group.get(), "visible")
+            .transform_to(|_, i: u32| Some(i == 1))
+            .sync_create()
+            .build();
+    }
+
+    fn setup_callbacks(&self) {
+        let imp = self.imp();
+        for field in [
+            &imp.material_field,
+            &imp.price_field,
+            &imp.major_length_field,
+            &imp.minor_length_field,
+            &imp.major_width_field,
+            &imp.minor_width_field,
+        ] {
+            field.connect_changed(clone!(
+                #[weak(rename_to = dialog)]
+                self,
+                move |_| {
+                    dialog.validate_all_entries();
+                }
+            ));
+        }
+        for field in [&imp.length_unit_field, &imp.width_unit_field] {
+            field.connect_selected_notify(clone!(
+                #[weak(rename_to = dialog)]
+                self,
+                move |_| {
+                    dialog.validate_all_entries();
+                }
+            ));
+        }
+        imp.dimension_field.connect_active_notify(clone!(
+            #[weak(rename_to = dialog)]
+            self,
+            move |_| {
+                dialog.validate_all_entries();
+            }
+        ));
+        imp.entry_list.connect_row_activated(clone!(
+            #[weak(rename_to = dialog)]
+            self,
+            move |_, row| {
+                dialog.set_selection(Some(row.index() as usize));
+            }
+        ));
+    }
+
+    fn show_toast(&self, message: &str) {
+        self.imp().toast_overlay.add_toast(adw::Toast::new(message));
+    }
+
+    fn supplies_pane(&self) -> EntryPane {
+        self.imp().supplies_pane.borrow().clone().unwrap()
+    }
+
+    fn update_fields(&self) {
+        let imp = self.imp();
+        let library = imp.library.borrow();
+        let entry = imp.selection.get().map(|i| &library.entries[i]);
+        let default_entry = LibraryEntry::default();
+        let LibraryEntry {
+            entry_data,
+            vendor,
+            price_history,
+        } = entry.unwrap_or(&default_entry);
+
+        imp.material_field.set_text(&entry_data.material);
+        imp.name_field.set_text(&entry_data.name);
+        imp.vendor_field.set_text(vendor);
+        let number_format = number_format();
+        imp.price_field
+            .set_text(&number_format.to_field(&entry_data.price));
+        imp.major_length_field
+            .set_text(&number_format.to_field(&entry_data.major_length));
+        imp.minor_length_field
+            .set_text(&number_format.to_field(&entry_data.minor_length));
+        imp.major_width_field
+            .set_text(&number_format.to_field(&entry_data.major_width));
+        imp.minor_width_field
+            .set_text(&number_format.to_field(&entry_data.minor_width));
+
+        // Do this after setting other fields to skip the entry animations
+        imp.dimension_field.set_active(entry_data.dimension);
+        if entry.is_some() {
+            imp.length_unit_field.set_selected(entry_data.length_unit);
+            imp.width_unit_field.set_selected(entry_data.width_unit);
+        } else {
+            let default_unit = self.settings().uint("default-unit");
+            imp.length_unit_field.set_selected(default_unit);
+            imp.width_unit_field.set_selected(default_unit);
+        }
+
+        // Most recent price first
+        let price_precision = self.settings().uint("price-precision");
+        imp.history_list.remove_all();
+        for record in price_history.iter().rev() {
+            let price = parse_price(&record.price, true, STORED_SEPARATOR).unwrap_or_default();
+            let row = adw::ActionRow::builder()
+                .title(&record.date)
+                .use_markup(false)
+                .build();
+            row.add_suffix(&gtk::Label::new(Some(&format_price(
+                price,
+                price_precision,
+            ))));
+            imp.history_list.append(&row);
+        }
+        imp.history_group.set_visible(!price_history.is_empty());
+    }
+
+    fn update_list(&self) {
+        let imp = self.imp();
+        let settings = self.settings();
+        let format = FractionFormat::from(
+            settings.uint("size-format"),
+            settings.uint("size-precision"),
+        );
+        let price_precision = settings.uint("price-precision");
+
+        imp.entry_list.remove_all();
+        for (i, entry) in imp.library.borrow().entries.iter().enumerate() {
+            let entry_data = &entry.entry_data;
+
+            // Entries edited outside the app are listed (so they can be fixed or deleted) but not
+            // parsed or added to the supplies
+            if !entry_data.is_valid() {
+                let row = adw::ActionRow::builder()
+                    .title(&entry_data.material)
+                    .subtitle(gettext("Invalid entry"))
+                    .use_markup(false)
+                    .activatable(true)
+                    .build();
+                imp.entry_list.append(&row);
+                continue;
+            }
+            let length = Size::from(
+                entry_data.length_unit,
+                &entry_data.major_length,
+                &entry_data.minor_length,
+            );
+            let mut details = vec![length.format(&format)];
+            if entry_data.dimension == 1 {
+                let width = Size::from(
+                    entry_data.width_unit,
+                    &entry_data.major_width,
+                    &entry_data.minor_width,
+                );
+                details.push(width.format(&format));
+            }
+            let price = parse_price(&entry_data.price, true, STORED_SEPARATOR).unwrap();
+            details.push(format_price(price, price_precision));
+            if !entry.vendor.is_empty() {
+                details.push(entry.vendor.clone());
+            }
+            if !entry_data.name.is_empty() {
+                details.push(entry_data.name.clone());
+            }
+
+            let row = adw::ActionRow::builder()
+                .title(&entry_data.material)
+                .subtitle(details.join(" · "))
+                .use_markup(false)
+                .activatable(true)
+                .build();
+            let import_button = gtk::Button::builder()
+                .icon_name("list-add-symbolic")
+                .tooltip_text(gettext("Add to supplies"))
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+            import_button.connect_clicked(clone!(
+                #[weak(rename_to = dialog)]
+                self,
+                move |_| {
+                    dialog.import_entry(i);
+                }
+            ));
+            row.add_suffix(&import_button);
+            imp.entry_list.append(&row);
+        }
+
+        let name = if imp.library.borrow().entries.is_empty() {
+            "placeholder"
+        } else {
+            "nonempty"
+        };
+        imp.content_stack.set_visible_child_name(name);
+    }
+
+    fn use_minor_length(&self) -> bool {
+        SizeUnit::from(self.imp().length_unit_field.selected()).has_minor()
+    }
+
+    fn use_minor_width(&self) -> bool {
+        self.use_width() && SizeUnit::from(self.imp().width_unit_field.selected()).has_minor()
+    }
+
+    fn use_width(&self) -> bool {
+        self.imp().dimension_field.active() == 1
+    }
+
+    fn validate_all_entries(&self) {
+        let mut all_valid = true;
+        let imp = self.imp();
+        all_valid &= validate_entry(&imp.material_field.get(), None, |e| e.text_length() != 0);
+        all_valid &= validate_entry(&imp.price_field.get(), None, |e| {
+            parse_price(&e.text(), true, number_format().separator()).is_ok()
+        });
+        let length_unit = SizeUnit::from(imp.length_unit_field.selected());
+        let width_unit = SizeUnit::from(imp.width_unit_field.selected());
+        all_valid &= validate_entry(&imp.major_length_field.get(), None, |e| {
+            check_size_field(e, &length_unit, false, false)
+        });
+        if self.use_minor_length() {
+            all_valid &= validate_entry(&imp.minor_length_field.get(), None, |e| {
+                check_size_field(e, &length_unit, true, true)
+            });
+        }
+        if self.use_width() {
+            all_valid &= validate_entry(&imp.major_width_field.get(), None, |e| {
+                check_size_field(e, &width_unit, false, false)
+            });
+        }
+        if self.use_minor_width() {
+            all_valid &= validate_entry(&imp.minor_width_field.get(), None, |e| {
+                check_size_field(e, &width_unit, true, true)
+            });
+        }
+        self.action_set_enabled("library.save", all_valid);
+    }
+}
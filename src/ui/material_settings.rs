@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+use crate::modeling::Material;
+use crate::size::Size;
+
+/// Project-specific settings for a material
+///
+/// Sizes are stored as user-entered strings, as in `EntryData`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MaterialSettings {
+    pub material: Material,
+
+    /// Depth across which angled ends are cut (e.g., the width of a picture frame moulding)
+    pub depth_unit: u32,
+    pub major_depth: String,
+    pub minor_depth: String,
+
+    /// Name of the `ToolProfile` used to cut the material (empty for the solver's blade width)
+    #[serde(default)]
+    pub tool: String,
+}
+
+impl MaterialSettings {
+    pub fn depth(&self) -> Size {
+        Size::from(self.depth_unit, &self.major_depth, &self.minor_depth)
+    }
+}
+
+/// A named saw (or other cutting tool) that materials can be assigned to
+///
+/// Examples:
+/// - Track saw, 1/16 inch kerf
+/// - Bandsaw, 1/16 inch kerf, 1/2 inch trimmed off rough ends
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolProfile {
+    pub name: String,
+
+    /// Unit for both the kerf and the end trim
+    pub unit: u32,
+    pub major_kerf: String,
+    pub minor_kerf: String,
+
+    /// Length cut off the end of each supply before any parts (empty for none)
+    pub major_trim: String,
+    pub minor_trim: String,
+}
+
+impl ToolProfile {
+    pub fn kerf(&self) -> Size {
+        Size::from(self.unit, &self.major_kerf, &self.minor_kerf)
+    }
+
+    pub fn trim(&self) -> Size {
+        Size::from(self.unit, &self.major_trim, &self.minor_trim)
+    }
+}
@@ -0,0 +1,575 @@
+use std::cell::{Cell, RefCell};
+use std::sync::OnceLock;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use fraction::{Fraction, Zero};
+use gettextrs::gettext;
+use gtk::glib::{Object, clone, subclass::InitializingObject, subclass::Signal};
+use gtk::{CompositeTemplate, glib};
+
+use super::material_settings::{MaterialSettings, ToolProfile};
+use super::number_format::number_format;
+use super::unit_row::UnitRow;
+use super::utils::*;
+use crate::i18n::i18n_f;
+use crate::modeling::{Dimension, Material};
+use crate::size::{FractionFormat, SizeUnit};
+
+mod imp {
+    use super::*;
+
+    // Object holding the state
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/com/ohmm-software/Chop-Chop/materials_dialog.ui")]
+    pub struct MaterialsDialog {
+        #[template_child]
+        pub(super) depth_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub(super) tools_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub(super) add_tool_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) assignment_group: TemplateChild<adw::PreferencesGroup>,
+
+        // Settings being edited (read back by the window on "materials-updated")
+        pub(super) material_settings: RefCell<Vec<MaterialSettings>>,
+        pub(super) tool_profiles: RefCell<Vec<ToolProfile>>,
+
+        // Every material used in the project (tools can be assigned to 1D and 2D materials)
+        pub(super) materials: RefCell<Vec<Material>>,
+
+        // Used to summarize the settings of each material
+        pub(super) format: RefCell<Option<FractionFormat>>,
+
+        // Unit for new settings and tool profiles
+        pub(super) default_unit: Cell<u32>,
+
+        // Rows added to the tool groups (removed whenever the rows are rebuilt)
+        pub(super) tool_rows: RefCell<Vec<adw::ExpanderRow>>,
+        pub(super) assignment_rows: RefCell<Vec<gtk::Widget>>,
+    }
+
+    // The central trait for subclassing a GObject
+    #[glib::object_subclass]
+    impl ObjectSubclass for MaterialsDialog {
+        const NAME: &'static str = "ChopChopMaterialsDialog";
+        type Type = super::MaterialsDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            // Required for CompositeTemplate
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            // Required for CompositeTemplate
+            obj.init_template();
+        }
+    }
+
+    // Trait shared by all GObjects
+    impl ObjectImpl for MaterialsDialog {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+
+            // The parent window watches this signal to store the new settings
+            SIGNALS.get_or_init(|| vec![Signal::builder("materials-updated").build()])
+        }
+    }
+
+    // Trait shared by all widgets
+    impl WidgetImpl for MaterialsDialog {}
+
+    // Trait shared by all Adwaita dialogs
+    impl AdwDialogImpl for MaterialsDialog {}
+}
+
+glib::wrapper! {
+    pub struct MaterialsDialog(ObjectSubclass<imp::MaterialsDialog>)
+        @extends adw::Dialog, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl MaterialsDialog {
+    /// Lists `materials` (the materials used in the project) with their current settings
+    pub fn new(
+        material_settings: Vec<MaterialSettings>,
+        tool_profiles: Vec<ToolProfile>,
+        materials: Vec<Material>,
+        format: FractionFormat,
+        default_unit: u32,
+    ) -> Self {
+        let dialog: Self = Object::builder().build();
+        let imp = dialog.imp();
+        imp.material_settings.replace(material_settings);
+        imp.tool_profiles.replace(tool_profiles);
+        imp.materials.replace(materials.clone());
+        imp.format.replace(Some(format));
+        imp.default_unit.set(default_unit);
+        imp.add_tool_button.connect_clicked(clone!(
+            #[weak]
+            dialog,
+            move |_| {
+                dialog.add_tool_profile();
+            }
+        ));
+        dialog.update_tool_rows(None);
+
+        let materials: Vec<_> = materials
+            .into_iter()
+            .filter(|m| m.dimension == Dimension::OneD)
+            .collect();
+        if materials.is_empty() {
+            let row = adw::ActionRow::builder()
+                .title(gettext("No 1D materials in this project"))
+                .build();
+            row.add_css_class("dim-label");
+            imp.depth_group.add(&row);
+        }
+        for material in materials {
+            let settings = dialog.settings(&material).unwrap_or(MaterialSettings {
+                material,
+                depth_unit: default_unit,
+                ..Default::default()
+            });
+            dialog.add_depth_row(settings);
+        }
+        dialog
+    }
+
+    pub fn material_settings(&self) -> Vec<MaterialSettings> {
+        self.imp().material_settings.borrow().clone()
+    }
+
+    pub fn tool_profiles(&self) -> Vec<ToolProfile> {
+        self.imp().tool_profiles.borrow().clone()
+    }
+
+    fn add_depth_row(&self, settings: MaterialSettings) {
+        let row = adw::ExpanderRow::builder()
+            .title(&settings.material.name)
+            .subtitle(self.describe_depth(&settings))
+            .use_markup(false)
+            .build();
+        let unit_row: UnitRow = Object::builder().build();
+        unit_row.set_selected(settings.depth_unit);
+        let major_row = adw::EntryRow::builder()
+            .text(number_format().to_field(&settings.major_depth))
+            .max_length(16)
+            .build();
+        let minor_row = adw::EntryRow::builder()
+            .text(number_format().to_field(&settings.minor_depth))
+            .max_length(16)
+            .build();
+        row.add_row(&unit_row);
+        row.add_row(&major_row);
+        row.add_row(&minor_row);
+
+        let update = clone!(
+            #[weak(rename_to = dialog)]
+            self,
+            #[weak]
+            row,
+            #[weak]
+            unit_row,
+            #[weak]
+            major_row,
+            #[weak]
+            minor_row,
+            move || {
+                let unit = SizeUnit::from(unit_row.selected());
+                major_row.set_title(&major_unit_name(&unit));
+                minor_row.set_title(&minor_unit_name(&unit));
+                minor_row.set_visible(unit.has_minor());
+                if !unit.has_minor() {
+                    minor_row.set_text("");
+                }
+
+                let mut all_valid = true;
+                for (field, minor) in [(&major_row, false), (&minor_row, true)] {
+                    all_valid &=
+                        validate_entry(field, None, |e| check_size_field(e, &unit, minor, true));
+                }
+                if all_valid {
+                    let settings = MaterialSettings {
+                        depth_unit: unit_row.selected(),
+                        major_depth: number_format().to_stored(major_row.text().trim()),
+                        minor_depth: number_format().to_stored(minor_row.text().trim()),
+                        ..dialog
+                            .settings(&settings.material)
+                            .unwrap_or(settings.clone())
+                    };
+                    row.set_subtitle(&dialog.describe_depth(&settings));
+                    dialog.replace_settings(settings);
+                }
+            }
+        );
+        update();
+        unit_row.connect_selected_notify(clone!(
+            #[strong]
+            update,
+            move |_| update()
+        ));
+        for field in [&major_row, &minor_row] {
+            field.connect_changed(clone!(
+                #[strong]
+                update,
+                move |_| update()
+            ));
+        }
+        self.imp().depth_group.add(&row);
+    }
+
+    fn add_tool_profile(&self) {
+        let imp = self.imp();
+        let i = imp.tool_profiles.borrow().len();
+        let mut n = i + 1;
+        while self
+            .tool_profile(&i18n_f("Tool {}", &[&n.to_string()]))
+            .is_some()
+        {
+            n += 1;
+        }
+        imp.tool_profiles.borrow_mut().push(ToolProfile {
+            name: i18n_f("Tool {}", &[&n.to_string()]),
+            unit: imp.default_unit.get(),
+            ..Default::default()
+        });
+        self.signal_materials_updated();
+        self.update_tool_rows(Some(i));
+    }
+
+    fn add_tool_row(&self, i: usize, profile: ToolProfile, expanded: bool) {
+        let row = adw::ExpanderRow::builder()
+            .title(&profile.name)
+            .subtitle(self.describe_tool(&profile))
+            .use_markup(false)
+            .expanded(expanded)
+            .build();
+        let remove_button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(gettext("Remove this tool profile"))
+            .valign(gtk::Align::Center)
+            .build();
+        remove_button.add_css_class("flat");
+        remove_button.connect_clicked(clone!(
+            #[weak(rename_to = dialog)]
+            self,
+            move |_| {
+                dialog.remove_tool_profile(i);
+            }
+        ));
+        row.add_suffix(&remove_button);
+
+        let name_row = adw::EntryRow::builder()
+            .title(gettext("Name"))
+            .text(&profile.name)
+            .max_length(64)
+            .build();
+        let unit_row: UnitRow = Object::builder().build();
+        unit_row.set_selected(profile.unit);
+        let kerf_rows = [
+            adw::EntryRow::builder()
+                .text(number_format().to_field(&profile.major_kerf))
+                .max_length(16)
+                .build(),
+            adw::EntryRow::builder()
+                .text(number_format().to_field(&profile.minor_kerf))
+                .max_length(16)
+                .build(),
+        ];
+        let trim_rows = [
+            adw::EntryRow::builder()
+                .text(number_format().to_field(&profile.major_trim))
+                .max_length(16)
+                .build(),
+            adw::EntryRow::builder()
+                .text(number_format().to_field(&profile.minor_trim))
+                .max_length(16)
+                .build(),
+        ];
+        row.add_row(&name_row);
+        row.add_row(&unit_row);
+        for field in kerf_rows.iter().chain(trim_rows.iter()) {
+            row.add_row(field);
+        }
+
+        let update = clone!(
+            #[weak(rename_to = dialog)]
+            self,
+            #[weak]
+            row,
+            #[weak]
+            name_row,
+            #[weak]
+            unit_row,
+            #[strong]
+            kerf_rows,
+            #[strong]
+            trim_rows,
+            move || {
+                let unit = SizeUnit::from(unit_row.selected());
+                for (rows, name) in [
+                    (&kerf_rows, gettext("Kerf")),
+                    (&trim_rows, gettext("End Trim")),
+                ] {
+                    rows[0].set_title(&format!("{} ({})", name, major_unit_name(&unit)));
+                    rows[1].set_title(&format!("{} ({})", name, minor_unit_name(&unit)));
+                    rows[1].set_visible(unit.has_minor());
+                    if !unit.has_minor() {
+                        rows[1].set_text("");
+                    }
+                }
+
+                // Materials refer to profiles by name, so names must be unique
+                let name = name_row.text().trim().to_string();
+                let mut all_valid = validate_entry(&name_row, None, |_| {
+                    !name.is_empty()
+                        && dialog
+                            .imp()
+                            .tool_profiles
+                            .borrow()
+                            .iter()
+                            .enumerate()
+                            .all(|(j, t)| j == i || t.name != name)
+                });
+                for rows in [&kerf_rows, &trim_rows] {
+                    for (field, minor) in rows.iter().zip([false, true]) {
+                        all_valid &= validate_entry(field, None, |e| {
+                            check_size_field(e, &unit, minor, true)
+                        });
+                    }
+                }
+                if all_valid {
+                    let stored =
+                        |field: &adw::EntryRow| number_format().to_stored(field.text().trim());
+                    let profile = ToolProfile {
+                        name,
+                        unit: unit_row.selected(),
+                        major_kerf: stored(&kerf_rows[0]),
+                        minor_kerf: stored(&kerf_rows[1]),
+                        major_trim: stored(&trim_rows[0]),
+                        minor_trim: stored(&trim_rows[1]),
+                    };
+                    row.set_title(&profile.name);
+                    row.set_subtitle(&dialog.describe_tool(&profile));
+                    dialog.replace_tool_profile(i, profile);
+                }
+            }
+        );
+        update();
+        unit_row.connect_selected_notify(clone!(
+            #[strong]
+            update,
+            move |_| update()
+        ));
+        for field in [&name_row]
+            .into_iter()
+            .chain(kerf_rows.iter())
+            .chain(trim_rows.iter())
+        {
+            field.connect_changed(clone!(
+                #[strong]
+                update,
+                move |_| update()
+            ));
+        }
+        self.imp().tools_group.add(&row);
+        self.imp().tool_rows.borrow_mut().push(row);
+    }
+
+    fn describe_depth(&self, settings: &MaterialSettings) -> String {
+        let format = self.imp().format.borrow().clone().unwrap();
+        let depth = settings.depth();
+        if depth.to_meters() > Fraction::zero() {
+            i18n_f("Depth {}", &[&depth.format(&format)])
+        } else {
+            gettext("No depth set (angled ends take no extra length)")
+        }
+    }
+
+    fn describe_tool(&self, profile: &ToolProfile) -> String {
+        let format = self.imp().format.borrow().clone().unwrap();
+        let mut s = i18n_f("Kerf {}", &[&profile.kerf().format(&format)]);
+        if profile.trim().to_meters() > Fraction::zero() {
+            s.push_str(" · ");
+            s.push_str(&i18n_f("End trim {}", &[&profile.trim().format(&format)]));
+        }
+        s
+    }
+
+    fn remove_tool_profile(&self, i: usize) {
+        let name = self.imp().tool_profiles.borrow_mut().remove(i).name;
+        self.rename_tool(&name, "");
+        self.signal_materials_updated();
+        self.update_tool_rows(None);
+    }
+
+    /// Points materials using tool profile `old` at `new` (empty for the solver's blade width)
+    fn rename_tool(&self, old: &str, new: &str) {
+        for settings in self.imp().material_settings.borrow_mut().iter_mut() {
+            if settings.tool == old {
+                settings.tool = String::from(new);
+            }
+        }
+    }
+
+    /// Stores a tool profile, carrying a new name over to the materials that use it
+    fn replace_tool_profile(&self, i: usize, profile: ToolProfile) {
+        let old = self.imp().tool_profiles.borrow()[i].clone();
+        let renamed = old.name != profile.name;
+        let changed = renamed
+            || (
+                &old.major_kerf,
+                &old.minor_kerf,
+                &old.major_trim,
+                &old.minor_trim,
+                old.unit,
+            ) != (
+                &profile.major_kerf,
+                &profile.minor_kerf,
+                &profile.major_trim,
+                &profile.minor_trim,
+                profile.unit,
+            );
+        if renamed {
+            self.rename_tool(&old.name, &profile.name);
+        }
+        self.imp().tool_profiles.borrow_mut()[i] = profile;
+        if renamed {
+            self.update_assignment_rows();
+        }
+        if changed {
+            self.signal_materials_updated();
+        }
+    }
+
+    /// Stores the settings of a material, notifying the window if they changed
+    fn replace_settings(&self, settings: MaterialSettings) {
+        let changed = {
+            let mut material_settings = self.imp().material_settings.borrow_mut();
+            match material_settings
+                .iter_mut()
+                .find(|s| s.material == settings.material)
+            {
+                Some(s) => {
+                    let changed = (s.depth_unit, &s.major_depth, &s.minor_depth, &s.tool)
+                        != (
+                            settings.depth_unit,
+                            &settings.major_depth,
+                            &settings.minor_depth,
+                            &settings.tool,
+                        );
+                    *s = settings;
+                    changed
+                }
+                None if settings.major_depth.is_empty()
+                    && settings.minor_depth.is_empty()
+                    && settings.tool.is_empty() =>
+                {
+                    false
+                }
+                None => {
+                    material_settings.push(settings);
+                    true
+                }
+            }
+        };
+        if changed {
+            self.signal_materials_updated();
+        }
+    }
+
+    fn signal_materials_updated(&self) {
+        self.emit_by_name::<()>("materials-updated", &[]);
+    }
+
+    fn settings(&self, material: &Material) -> Option<MaterialSettings> {
+        self.imp()
+            .material_settings
+            .borrow()
+            .iter()
+            .find(|s| s.material == *material)
+            .cloned()
+    }
+
+    fn tool_profile(&self, name: &str) -> Option<ToolProfile> {
+        self.imp()
+            .tool_profiles
+            .borrow()
+            .iter()
+            .find(|t| t.name == name)
+            .cloned()
+    }
+
+    /// Lists each material with a choice of tool profile
+    fn update_assignment_rows(&self) {
+        let imp = self.imp();
+        for row in imp.assignment_rows.take() {
+            imp.assignment_group.remove(&row);
+        }
+        let mut names = vec![gettext("Solver Blade Width")];
+        names.extend(imp.tool_profiles.borrow().iter().map(|t| t.name.clone()));
+        let names: Vec<&str> = names.iter().map(|s| s.as_str()).collect();
+        for material in imp.materials.borrow().iter() {
+            let settings = self.settings(material).unwrap_or(MaterialSettings {
+                material: material.clone(),
+                depth_unit: imp.default_unit.get(),
+                ..Default::default()
+            });
+            let selected = names
+                .iter()
+                .position(|n| !settings.tool.is_empty() && *n == settings.tool)
+                .unwrap_or(0);
+            let row = adw::ComboRow::builder()
+                .title(&material.name)
+                .model(&gtk::StringList::new(&names))
+                .selected(selected as u32)
+                .use_markup(false)
+                .build();
+            row.connect_selected_notify(clone!(
+                #[weak(rename_to = dialog)]
+                self,
+                move |row| {
+                    let tool = match row.selected() {
+                        0 => String::new(),
+                        i => dialog.imp().tool_profiles.borrow()[i as usize - 1]
+                            .name
+                            .clone(),
+                    };
+                    let settings = MaterialSettings {
+                        tool,
+                        ..dialog
+                            .settings(&settings.material)
+                            .unwrap_or(settings.clone())
+                    };
+                    dialog.replace_settings(settings);
+                }
+            ));
+            imp.assignment_group.add(&row);
+            imp.assignment_rows.borrow_mut().push(row.upcast());
+        }
+        if imp.materials.borrow().is_empty() {
+            let row = adw::ActionRow::builder()
+                .title(gettext("No materials in this project"))
+                .build();
+            row.add_css_class("dim-label");
+            imp.assignment_group.add(&row);
+            imp.assignment_rows.borrow_mut().push(row.upcast());
+        }
+    }
+
+    /// Rebuilds the tool profile rows (expanding the one at index `expanded`, if any)
+    fn update_tool_rows(&self, expanded: Option<usize>) {
+        let imp = self.imp();
+        for row in imp.tool_rows.take() {
+            imp.tools_group.remove(&row);
+        }
+        let tool_profiles = imp.tool_profiles.borrow().clone();
+        for (i, profile) in tool_profiles.into_iter().enumerate() {
+            self.add_tool_row(i, profile, expanded == Some(i));
+        }
+        self.update_assignment_rows();
+    }
+}
@@ -0,0 +1,123 @@
+use std::cell::RefCell;
+
+use gtk::gio::Settings;
+use gtk::glib;
+use gtk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Languages whose locales write decimals with a comma (e.g., "2,5")
+const COMMA_LANGUAGES: &[&str] = &[
+    "bg", "ca", "cs", "da", "de", "el", "es", "et", "eu", "fi", "fr", "gl", "hr", "hu", "id", "is",
+    "it", "lt", "lv", "nb", "nl", "nn", "no", "pl", "pt", "ro", "ru", "sk", "sl", "sr", "sv", "tr",
+    "uk", "vi",
+];
+
+/// The decimal separator of numbers stored in entries and settings, whatever the format
+///
+/// Fields show and accept the format's separator instead (see `NumberFormat::to_stored` and
+/// `NumberFormat::to_field`), so stored numbers stay valid when the separator is changed.
+pub const STORED_SEPARATOR: char = '.';
+
+thread_local! {
+    // Read by the parsing and formatting functions in `utils` (see `set_number_format`)
+    static NUMBER_FORMAT: RefCell<NumberFormat> = RefCell::new(NumberFormat::default());
+}
+
+/// How prices and decimal numbers are written
+///
+/// Set for the app in the preferences, and optionally overridden by a project. Only the format's
+/// decimal separator is accepted in fields, as the other one may group thousands.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NumberFormat {
+    pub currency_symbol: String,
+
+    /// Whether the currency symbol follows the amount (e.g., "3,50 €")
+    pub symbol_after: bool,
+
+    /// 0 for the system locale's separator, 1 for a point, or 2 for a comma
+    pub decimal_separator: u32,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            currency_symbol: String::from("$"),
+            symbol_after: false,
+            decimal_separator: 0,
+        }
+    }
+}
+
+impl NumberFormat {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            currency_symbol: settings.string("currency-symbol").to_string(),
+            symbol_after: settings.boolean("currency-after"),
+            decimal_separator: settings.uint("decimal-separator"),
+        }
+    }
+
+    pub fn separator(&self) -> char {
+        match self.decimal_separator {
+            1 => '.',
+            2 => ',',
+            _ if locale_uses_comma() => ',',
+            _ => '.',
+        }
+    }
+
+    /// Writes the text of a field as it is stored (see `STORED_SEPARATOR`)
+    ///
+    /// Points and commas are swapped rather than replaced, so a number written with the wrong
+    /// separator is still rejected once stored.
+    pub fn to_stored(&self, text: &str) -> String {
+        swap_separators(text, self.separator())
+    }
+
+    /// Writes stored text with this format's separator, for editing in a field
+    pub fn to_field(&self, text: &str) -> String {
+        swap_separators(text, self.separator())
+    }
+
+    /// Adds the currency symbol to a formatted amount
+    pub fn with_symbol(&self, amount: &str) -> String {
+        if self.symbol_after {
+            format!("{} {}", amount, self.currency_symbol)
+        } else {
+            format!("{}{}", self.currency_symbol, amount)
+        }
+    }
+}
+
+/// The format used by the active window
+pub fn number_format() -> NumberFormat {
+    NUMBER_FORMAT.with(|f| f.borrow().clone())
+}
+
+/// Called by a window whenever it becomes active or its format changes
+pub fn set_number_format(number_format: NumberFormat) {
+    NUMBER_FORMAT.with(|f| f.replace(number_format));
+}
+
+fn swap_separators(text: &str, separator: char) -> String {
+    if separator == STORED_SEPARATOR {
+        return text.to_string();
+    }
+    text.chars()
+        .map(|c| match c {
+            '.' => ',',
+            ',' => '.',
+            c => c,
+        })
+        .collect()
+}
+
+fn locale_uses_comma() -> bool {
+    match glib::language_names_with_category("LC_NUMERIC").first() {
+        Some(name) => {
+            let language = name.split(['_', '.', '@']).next().unwrap_or_default();
+            COMMA_LANGUAGES.contains(&language)
+        }
+        None => false,
+    }
+}
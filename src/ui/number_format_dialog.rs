@@ -0,0 +1,139 @@
+use std::cell::RefCell;
+use std::sync::OnceLock;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib::{Object, clone, subclass::InitializingObject, subclass::Signal};
+use gtk::{CompositeTemplate, glib};
+
+use super::number_format::NumberFormat;
+
+mod imp {
+    use super::*;
+
+    // Object holding the state
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/com/ohmm-software/Chop-Chop/number_format_dialog.ui")]
+    pub struct NumberFormatDialog {
+        #[template_child]
+        pub(super) override_field: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) currency_symbol_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) currency_after_field: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) decimal_separator_field: TemplateChild<adw::ComboRow>,
+
+        // Project format being edited (read back by the window on "number-format-updated")
+        pub(super) number_format: RefCell<Option<NumberFormat>>,
+    }
+
+    // The central trait for subclassing a GObject
+    #[glib::object_subclass]
+    impl ObjectSubclass for NumberFormatDialog {
+        const NAME: &'static str = "ChopChopNumberFormatDialog";
+        type Type = super::NumberFormatDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            // Required for CompositeTemplate
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            // Required for CompositeTemplate
+            obj.init_template();
+        }
+    }
+
+    // Trait shared by all GObjects
+    impl ObjectImpl for NumberFormatDialog {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+
+            // The parent window watches this signal to store and apply the new format
+            SIGNALS.get_or_init(|| vec![Signal::builder("number-format-updated").build()])
+        }
+    }
+
+    // Trait shared by all widgets
+    impl WidgetImpl for NumberFormatDialog {}
+
+    // Trait shared by all Adwaita dialogs
+    impl AdwDialogImpl for NumberFormatDialog {}
+}
+
+glib::wrapper! {
+    pub struct NumberFormatDialog(ObjectSubclass<imp::NumberFormatDialog>)
+        @extends adw::Dialog, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl NumberFormatDialog {
+    /// Shows the project's format, or the preferred format (`default`) if it has none
+    pub fn new(number_format: Option<NumberFormat>, default: NumberFormat) -> Self {
+        let dialog: Self = Object::builder().build();
+        let imp = dialog.imp();
+        let fields = number_format.clone().unwrap_or(default);
+        imp.override_field.set_active(number_format.is_some());
+        imp.currency_symbol_field.set_text(&fields.currency_symbol);
+        imp.currency_after_field.set_active(fields.symbol_after);
+        imp.decimal_separator_field
+            .set_selected(fields.decimal_separator);
+        imp.number_format.replace(number_format);
+
+        for field in [
+            imp.currency_symbol_field.upcast_ref::<gtk::Widget>(),
+            imp.currency_after_field.upcast_ref(),
+            imp.decimal_separator_field.upcast_ref(),
+        ] {
+            imp.override_field
+                .bind_property("active", field, "sensitive")
+                .sync_create()
+                .build();
+        }
+        imp.override_field.connect_active_notify(clone!(
+            #[weak]
+            dialog,
+            move |_| dialog.update_number_format()
+        ));
+        imp.currency_symbol_field.connect_changed(clone!(
+            #[weak]
+            dialog,
+            move |_| dialog.update_number_format()
+        ));
+        imp.currency_after_field.connect_active_notify(clone!(
+            #[weak]
+            dialog,
+            move |_| dialog.update_number_format()
+        ));
+        imp.decimal_separator_field.connect_selected_notify(clone!(
+            #[weak]
+            dialog,
+            move |_| dialog.update_number_format()
+        ));
+        dialog
+    }
+
+    /// The project's format (`None` to use the preferences)
+    pub fn number_format(&self) -> Option<NumberFormat> {
+        self.imp().number_format.borrow().clone()
+    }
+
+    fn update_number_format(&self) {
+        let imp = self.imp();
+        let number_format = if imp.override_field.is_active() {
+            Some(NumberFormat {
+                currency_symbol: imp.currency_symbol_field.text().trim().to_string(),
+                symbol_after: imp.currency_after_field.is_active(),
+                decimal_separator: imp.decimal_separator_field.selected(),
+            })
+        } else {
+            None
+        };
+        if number_format != *imp.number_format.borrow() {
+            imp.number_format.replace(number_format);
+            self.emit_by_name::<()>("number-format-updated", &[]);
+        }
+    }
+}
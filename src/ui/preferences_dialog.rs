@@ -23,12 +23,28 @@ mod imp {
         #[template_child]
         pub(super) print_font_entry: TemplateChild<FontRow>,
         #[template_child]
+        pub(super) label_layout_entry: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub(super) label_qr_entry: TemplateChild<adw::SwitchRow>,
+        #[template_child]
         pub(super) size_format_entry: TemplateChild<adw::ComboRow>,
         #[template_child]
+        pub(super) supply_unit_entry: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub(super) part_unit_entry: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub(super) dual_units_entry: TemplateChild<adw::SwitchRow>,
+        #[template_child]
         pub(super) size_precision_entry: TemplateChild<adw::SpinRow>,
         #[template_child]
         pub(super) price_precision_entry: TemplateChild<adw::SpinRow>,
         #[template_child]
+        pub(super) currency_symbol_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) currency_after_entry: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) decimal_separator_entry: TemplateChild<adw::ComboRow>,
+        #[template_child]
         pub(super) deselect_add_entry: TemplateChild<adw::SwitchRow>,
         #[template_child]
         pub(super) default_unit_entry: TemplateChild<UnitRow>,
@@ -103,15 +119,43 @@ impl PreferencesDialog {
         settings
             .bind("print-font", &imp.print_font_entry.get(), "font-desc-str")
             .build();
+        settings
+            .bind("label-layout", &imp.label_layout_entry.get(), "selected")
+            .build();
+        settings
+            .bind("label-qr", &imp.label_qr_entry.get(), "active")
+            .build();
         settings
             .bind("size-format", &imp.size_format_entry.get(), "selected")
             .build();
+        settings
+            .bind("supply-unit", &imp.supply_unit_entry.get(), "selected")
+            .build();
+        settings
+            .bind("part-unit", &imp.part_unit_entry.get(), "selected")
+            .build();
+        settings
+            .bind("dual-units", &imp.dual_units_entry.get(), "active")
+            .build();
         settings
             .bind("size-precision", &imp.size_precision_entry.get(), "value")
             .build();
         settings
             .bind("price-precision", &imp.price_precision_entry.get(), "value")
             .build();
+        settings
+            .bind("currency-symbol", &imp.currency_symbol_entry.get(), "text")
+            .build();
+        settings
+            .bind("currency-after", &imp.currency_after_entry.get(), "active")
+            .build();
+        settings
+            .bind(
+                "decimal-separator",
+                &imp.decimal_separator_entry.get(),
+                "selected",
+            )
+            .build();
         settings
             .bind("deselect-add", &imp.deselect_add_entry.get(), "active")
             .build();
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::assembly::Assembly;
+use super::cut_plan::LockedPattern;
+use super::entry::EntryData;
+use super::material_settings::{MaterialSettings, ToolProfile};
+use super::number_format::NumberFormat;
+use super::project_details::ProjectDetails;
+use super::vendor_settings::VendorSettings;
+use crate::modeling::{Material, SubSolution};
+use crate::solvers::{Parameters, SolverRef};
+
+/// Everything stored in a project file
+///
+/// Older project files store the first four fields as a JSON array, which serde still accepts.
+/// Fields added since then need `#[serde(default)]`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectData {
+    pub supply_entries: Vec<EntryData>,
+    pub part_entries: Vec<EntryData>,
+
+    /// Flattened with `flatten_results` (required due to HashMap)
+    pub results: Option<Result<Vec<(Material, SubSolution)>, String>>,
+
+    /// Solver pane fields (solver, blade unit, major blade width, minor blade width)
+    pub solver_field_data: (SolverRef, u32, String, String),
+
+    #[serde(default)]
+    pub assemblies: Vec<Assembly>,
+
+    #[serde(default)]
+    pub locked_patterns: Vec<LockedPattern>,
+
+    /// Parameter values for each solver (keyed by `SolverInfo::id`)
+    #[serde(default)]
+    pub solver_parameters: HashMap<String, Parameters>,
+
+    #[serde(default)]
+    pub material_settings: Vec<MaterialSettings>,
+
+    #[serde(default)]
+    pub tool_profiles: Vec<ToolProfile>,
+
+    #[serde(default)]
+    pub vendor_settings: Vec<VendorSettings>,
+
+    /// Overrides the format set in the preferences
+    #[serde(default)]
+    pub number_format: Option<NumberFormat>,
+
+    #[serde(default)]
+    pub details: ProjectDetails,
+}
@@ -0,0 +1,37 @@
+use gtk::glib;
+use serde::{Deserialize, Serialize};
+
+/// Who and what a project is for, shown at the top of reports and on each printed page
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectDetails {
+    pub title: String,
+    pub customer: String,
+    pub job_number: String,
+    pub notes: String,
+
+    /// ISO 8601 date (YYYY-MM-DD), or empty
+    pub due_date: String,
+}
+
+impl ProjectDetails {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The due date written the way the locale prefers (or as entered if it is not a date)
+    pub fn formatted_due_date(&self) -> String {
+        parse_date(&self.due_date)
+            .and_then(|date| date.format("%x").ok())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| self.due_date.clone())
+    }
+}
+
+/// Reads an ISO 8601 date (YYYY-MM-DD)
+pub fn parse_date(text: &str) -> Option<glib::DateTime> {
+    let mut parts = text.trim().splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    glib::DateTime::from_local(year, month, day, 0, 0, 0.0).ok()
+}
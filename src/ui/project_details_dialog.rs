@@ -0,0 +1,155 @@
+use std::cell::RefCell;
+use std::sync::OnceLock;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib::{Object, clone, subclass::InitializingObject, subclass::Signal};
+use gtk::{CompositeTemplate, glib};
+
+use super::project_details::{ProjectDetails, parse_date};
+
+mod imp {
+    use super::*;
+
+    // Object holding the state
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/com/ohmm-software/Chop-Chop/project_details_dialog.ui")]
+    pub struct ProjectDetailsDialog {
+        #[template_child]
+        pub(super) title_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) customer_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) job_number_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) due_date_field: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) due_calendar: TemplateChild<gtk::Calendar>,
+        #[template_child]
+        pub(super) notes_field: TemplateChild<gtk::TextView>,
+
+        // Details being edited (read back by the window on "project-details-updated")
+        pub(super) details: RefCell<ProjectDetails>,
+    }
+
+    // The central trait for subclassing a GObject
+    #[glib::object_subclass]
+    impl ObjectSubclass for ProjectDetailsDialog {
+        const NAME: &'static str = "ChopChopProjectDetailsDialog";
+        type Type = super::ProjectDetailsDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            // Required for CompositeTemplate
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            // Required for CompositeTemplate
+            obj.init_template();
+        }
+    }
+
+    // Trait shared by all GObjects
+    impl ObjectImpl for ProjectDetailsDialog {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+
+            // The parent window watches this signal to store and display the new details
+            SIGNALS.get_or_init(|| vec![Signal::builder("project-details-updated").build()])
+        }
+    }
+
+    // Trait shared by all widgets
+    impl WidgetImpl for ProjectDetailsDialog {}
+
+    // Trait shared by all Adwaita dialogs
+    impl AdwDialogImpl for ProjectDetailsDialog {}
+}
+
+glib::wrapper! {
+    pub struct ProjectDetailsDialog(ObjectSubclass<imp::ProjectDetailsDialog>)
+        @extends adw::Dialog, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl ProjectDetailsDialog {
+    pub fn new(details: ProjectDetails) -> Self {
+        let dialog: Self = Object::builder().build();
+        let imp = dialog.imp();
+        imp.title_field.set_text(&details.title);
+        imp.customer_field.set_text(&details.customer);
+        imp.job_number_field.set_text(&details.job_number);
+        imp.due_date_field.set_text(&details.due_date);
+        imp.notes_field.buffer().set_text(&details.notes);
+        if let Some(date) = parse_date(&details.due_date) {
+            imp.due_calendar.select_day(&date);
+        }
+        imp.details.replace(details);
+
+        for field in [
+            &imp.title_field,
+            &imp.customer_field,
+            &imp.job_number_field,
+            &imp.due_date_field,
+        ] {
+            field.connect_changed(clone!(
+                #[weak]
+                dialog,
+                move |_| dialog.update_details()
+            ));
+        }
+        imp.notes_field.buffer().connect_changed(clone!(
+            #[weak]
+            dialog,
+            move |_| dialog.update_details()
+        ));
+        imp.due_calendar.connect_day_selected(clone!(
+            #[weak]
+            dialog,
+            move |calendar| {
+                if let Ok(date) = calendar.date().format("%Y-%m-%d") {
+                    dialog.imp().due_date_field.set_text(&date);
+                }
+                if let Some(popover) = calendar.ancestor(gtk::Popover::static_type()) {
+                    popover.downcast::<gtk::Popover>().unwrap().popdown();
+                }
+            }
+        ));
+        dialog
+    }
+
+    pub fn details(&self) -> ProjectDetails {
+        self.imp().details.borrow().clone()
+    }
+
+    fn update_details(&self) {
+        let imp = self.imp();
+
+        // Keep the last valid due date while one is being typed
+        let text = imp.due_date_field.text().trim().to_string();
+        let due_date = if text.is_empty() || parse_date(&text).is_some() {
+            imp.due_date_field.remove_css_class("invalid-entry");
+            text
+        } else {
+            imp.due_date_field.add_css_class("invalid-entry");
+            imp.details.borrow().due_date.clone()
+        };
+
+        let buffer = imp.notes_field.buffer();
+        let details = ProjectDetails {
+            title: imp.title_field.text().trim().to_string(),
+            customer: imp.customer_field.text().trim().to_string(),
+            job_number: imp.job_number_field.text().trim().to_string(),
+            notes: buffer
+                .text(&buffer.start_iter(), &buffer.end_iter(), false)
+                .trim()
+                .to_string(),
+            due_date,
+        };
+        if details != *imp.details.borrow() {
+            imp.details.replace(details);
+            self.emit_by_name::<()>("project-details-updated", &[]);
+        }
+    }
+}
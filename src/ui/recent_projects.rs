@@ -0,0 +1,99 @@
+use std::path::{Path, PathBuf};
+
+use gtk::gio::Settings;
+use gtk::prelude::*;
+
+/// How many unpinned projects are remembered
+const MAX_RECENT: usize = 10;
+
+/// A project listed on the start page and in the primary menu
+#[derive(Debug, Clone)]
+pub struct RecentProject {
+    pub path: PathBuf,
+    pub pinned: bool,
+}
+
+impl RecentProject {
+    pub fn name(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn folder(&self) -> String {
+        self.path
+            .parent()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Moves a project to the top of the recent projects
+pub fn add_recent_project(settings: &Settings, path: &Path) {
+    let path = path.display().to_string();
+    let mut recent = paths(settings, "recent-projects");
+    recent.retain(|p| *p != path);
+    recent.insert(0, path);
+    recent.truncate(MAX_RECENT);
+    set_paths(settings, "recent-projects", recent);
+}
+
+/// Pinned projects followed by the others, most recent first
+///
+/// Projects whose files no longer exist are forgotten.
+pub fn recent_projects(settings: &Settings) -> Vec<RecentProject> {
+    let mut projects = Vec::new();
+    for (key, pinned) in [("pinned-projects", true), ("recent-projects", false)] {
+        let paths = paths(settings, key);
+        let existing: Vec<_> = paths
+            .iter()
+            .filter(|p| Path::new(p).exists())
+            .cloned()
+            .collect();
+        if existing.len() < paths.len() {
+            set_paths(settings, key, existing.clone());
+        }
+        for path in existing {
+            if pinned
+                || !projects
+                    .iter()
+                    .any(|p: &RecentProject| p.path == Path::new(&path))
+            {
+                projects.push(RecentProject {
+                    path: PathBuf::from(path),
+                    pinned,
+                });
+            }
+        }
+    }
+    projects
+}
+
+/// Pins a project (keeping it listed however long ago it was opened) or unpins it
+pub fn toggle_pinned(settings: &Settings, path: &str) {
+    let mut pinned = paths(settings, "pinned-projects");
+    if pinned.iter().any(|p| p == path) {
+        pinned.retain(|p| p != path);
+        // Unpinned projects go back to the top of the recent projects
+        add_recent_project(settings, Path::new(path));
+    } else {
+        pinned.push(path.to_string());
+    }
+    set_paths(settings, "pinned-projects", pinned);
+}
+
+/// Forgets the recent projects other than the pinned ones
+pub fn clear_recent_projects(settings: &Settings) {
+    set_paths(settings, "recent-projects", Vec::new());
+}
+
+fn paths(settings: &Settings, key: &str) -> Vec<String> {
+    settings.strv(key).iter().map(|s| s.to_string()).collect()
+}
+
+fn set_paths(settings: &Settings, key: &str, paths: Vec<String>) {
+    settings
+        .set_strv(key, paths)
+        .expect("Failed to update the recent projects");
+}
@@ -1,21 +1,36 @@
 use std::cell::{Cell, RefCell, RefMut};
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
 use std::sync::OnceLock;
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
+use fraction::{Fraction, Zero};
+use gettextrs::gettext;
 use gtk::glib::{Properties, clone, subclass::InitializingObject, subclass::Signal};
-use gtk::{CompositeTemplate, PrintOperationAction::PrintDialog, glib};
+use gtk::{CompositeTemplate, PrintOperationAction, glib};
 use pango::FontDescription;
 
-use super::display::DisplayEngine;
+use super::display::{DisplayEngine, PageDecorations};
+use super::labels::{LABEL_LAYOUTS, generate_labels, sorted_materials};
+use super::number_format::{NumberFormat, number_format};
+use super::project_details::ProjectDetails;
 use super::unit_row::UnitRow;
 use super::utils::*;
 use super::window::Window;
-use crate::modeling::Solution;
+use crate::i18n::{i18n_f, ni18n_f};
+use crate::layout::{CutLayout, cut_sequence};
+use crate::modeling::{Part, Solution};
 use crate::size::FractionFormat;
 use crate::size::{Size, SizeUnit};
-use crate::solvers::{Solver, naive_solver::NaiveSolver};
-use crate::utils::{compute_supply_consumption, compute_total_price};
+use crate::solvers::{
+    ParameterKind, ParameterValue, Parameters, SOLVERS, Solver, SolverInfo, SolverRef,
+};
+use crate::utils::{
+    compute_optimality_gap, compute_part_production, compute_supply_consumption,
+    compute_supply_subtotal, compute_total_lower_bound, compute_total_price, compute_vendor_totals,
+};
 
 mod imp {
     use super::*;
@@ -29,6 +44,8 @@ mod imp {
         #[template_child]
         pub(super) solver_field: TemplateChild<adw::ComboRow>,
         #[template_child]
+        pub(super) parameters_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
         pub(super) blade_unit_field: TemplateChild<UnitRow>,
         #[template_child]
         pub(super) major_blade_field: TemplateChild<adw::EntryRow>,
@@ -55,13 +72,32 @@ mod imp {
         #[property(get, set)]
         pub(super) size_precision: Cell<u32>,
         #[property(get, set)]
+        pub(super) supply_unit: Cell<u32>,
+        #[property(get, set)]
+        pub(super) part_unit: Cell<u32>,
+        #[property(get, set)]
+        pub(super) dual_units: Cell<bool>,
+        #[property(get, set)]
         pub(super) price_precision: Cell<u32>,
         #[property(get, set)]
         pub(super) default_unit: Cell<u32>,
+        #[property(get, set)]
+        pub(super) label_layout: Cell<u32>,
+        #[property(get, set)]
+        pub(super) label_qr: Cell<bool>,
+
+        // Parameter values for each solver (keyed by `SolverInfo::id`)
+        pub(super) solver_parameters: RefCell<HashMap<String, Parameters>>,
+
+        // Rows in the parameters group (removed whenever another solver is selected)
+        pub(super) parameter_rows: RefCell<Vec<gtk::Widget>>,
 
         // Solver result
         pub results: RefCell<Option<Result<Solution, String>>>,
 
+        // Who and what the project is for (shown above the results)
+        pub(super) details: RefCell<ProjectDetails>,
+
         // Display engine for drawing and printing results
         pub display_engine: RefCell<DisplayEngine>,
     }
@@ -91,6 +127,7 @@ mod imp {
         fn constructed(&self) {
             self.parent_constructed();
             let obj = self.obj();
+            obj.setup_solvers();
             obj.setup_callbacks();
             obj.setup_bindings();
             obj.update_fields();
@@ -121,12 +158,8 @@ glib::wrapper! {
 
 impl SolverPane {
     pub fn blade_width(&self) -> Size {
-        let imp = self.imp();
-        Size {
-            unit: SizeUnit::from(imp.blade_unit_field.selected()),
-            major: parse_positive_fraction(&imp.major_blade_field.text(), true).unwrap(),
-            minor: parse_positive_fraction(&imp.minor_blade_field.text(), true).unwrap(),
-        }
+        let (_, blade_unit, major_blade, minor_blade) = self.field_data();
+        Size::from(blade_unit, &major_blade, &minor_blade)
     }
 
     pub fn clear_results(&self) {
@@ -136,25 +169,77 @@ impl SolverPane {
 
     // The Send trait is required because the solver will be sent to the worker thread
     pub fn create_solver(&self) -> Box<dyn Solver + Send> {
-        match self.imp().solver_field.selected() {
-            0 => Box::new(NaiveSolver {}),
-            _ => panic!(),
-        }
+        let info = self.solver_info();
+        (info.create)(&self.parameters(info))
     }
 
-    pub fn field_data(&self) -> (u32, u32, String, String) {
+    pub fn field_data(&self) -> (SolverRef, u32, String, String) {
         let imp = self.imp();
         (
-            imp.solver_field.selected(),
+            SolverRef::Id(String::from(self.solver_info().id)),
             imp.blade_unit_field.selected(),
-            imp.major_blade_field.text().to_string(),
-            imp.minor_blade_field.text().to_string(),
+            number_format().to_stored(&imp.major_blade_field.text()),
+            number_format().to_stored(&imp.minor_blade_field.text()),
         )
     }
 
-    // https://github.com/gtk-rs/examples/blob/master/src/bin/printing.rs
+    pub fn details(&self) -> ProjectDetails {
+        self.imp().details.borrow().clone()
+    }
+
+    /// Saves the results to a PDF file, laid out as they would be printed
+    pub fn export_results(&self, file_path: &Path) -> Result<(), glib::Error> {
+        let print_operation = gtk::PrintOperation::new();
+        print_operation.set_export_filename(file_path);
+        self.setup_results_operation(&print_operation);
+        print_operation
+            .run(
+                PrintOperationAction::Export,
+                self.root().and_downcast_ref::<Window>(),
+            )
+            .map(|_| ())
+    }
+
     pub fn print_results(&self) {
         let print_operation = gtk::PrintOperation::new();
+        self.setup_results_operation(&print_operation);
+        print_operation
+            .run(
+                PrintOperationAction::PrintDialog,
+                self.root().and_downcast_ref::<Window>(),
+            )
+            .unwrap();
+    }
+
+    /// Repeats the project details and page numbers on each page
+    fn page_decorations(&self) -> PageDecorations {
+        let details = self.details();
+        let header_start = [details.title.clone(), details.customer.clone()]
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" — ");
+        let header_end = if details.due_date.is_empty() {
+            String::new()
+        } else {
+            i18n_f("Due {}", &[&details.formatted_due_date()])
+        };
+        let footer = if details.job_number.is_empty() {
+            String::new()
+        } else {
+            i18n_f("Job {}", &[&details.job_number])
+        };
+        PageDecorations {
+            header_start,
+            header_end,
+            footer,
+        }
+    }
+
+    // https://github.com/gtk-rs/examples/blob/master/src/bin/printing.rs
+    fn setup_results_operation(&self, print_operation: &gtk::PrintOperation) {
+        self.display_engine()
+            .set_page_decorations(Some(self.page_decorations()));
 
         // Create outside callbacks in case the user changes the font during the print operation
         let font_description = FontDescription::from_string(&self.print_font());
@@ -190,24 +275,108 @@ impl SolverPane {
                 );
             }
         ));
+    }
+
+    /// Prints a sticker sheet with one label for each part in the solution
+    pub fn print_labels(&self) {
+        let Some(Ok(solution)) = self.results() else {
+            return;
+        };
+        let i = self.label_layout() as usize;
+        let layout = LABEL_LAYOUTS.get(i).unwrap_or(&LABEL_LAYOUTS[0]).clone();
+        let format = FractionFormat::from(self.size_format(), self.size_precision());
+        let labels = generate_labels(&solution, &format);
+
+        // Labels are positioned relative to the page edge, so no margins
+        let mut d = DisplayEngine::default();
+        d.set_margins(0.0, 0.0);
+        for page in labels.chunks(layout.labels_per_page()) {
+            d.append_label_sheet(&layout, page, self.label_qr());
+        }
+        let d = Rc::new(RefCell::new(d));
+
+        let page_setup = gtk::PageSetup::new();
+        page_setup.set_paper_size(&gtk::PaperSize::new(Some(layout.paper)));
+        let print_operation = gtk::PrintOperation::builder()
+            .default_page_setup(&page_setup)
+            .use_full_page(true)
+            .unit(gtk::Unit::Points)
+            .build();
+        let font_description = FontDescription::from_string(&self.print_font());
+
+        print_operation.connect_begin_print(clone!(
+            #[strong]
+            d,
+            #[strong]
+            font_description,
+            move |operation, context| {
+                let mut d = d.borrow_mut();
+                d.paginate(
+                    &context.cairo_context(),
+                    &font_description,
+                    context.width(),
+                    context.height(),
+                );
+                operation.set_n_pages(d.n_pages() as i32);
+            },
+        ));
+        print_operation.connect_draw_page(move |_, context, i| {
+            d.borrow().draw_page(
+                &context.cairo_context(),
+                &font_description,
+                context.width(),
+                i as usize,
+            );
+        });
         print_operation
-            .run(PrintDialog, self.root().and_downcast_ref::<Window>())
+            .run(
+                PrintOperationAction::PrintDialog,
+                self.root().and_downcast_ref::<Window>(),
+            )
             .unwrap();
     }
 
+    /// Returns the parameter values entered for a solver
+    fn parameters(&self, info: &SolverInfo) -> Parameters {
+        let solver_parameters = self.imp().solver_parameters.borrow();
+        solver_parameters.get(info.id).cloned().unwrap_or_default()
+    }
+
     pub fn redraw(&self) {
         self.draw_results();
         self.update_placeholder();
         self.display_engine().display(&self.imp().display_area);
     }
 
-    pub fn replace_field_data(&self, data: (u32, u32, String, String)) {
+    /// Rewrites the blade width fields written in the `previous` format in the active one
+    pub fn reformat_fields(&self, previous: &NumberFormat) {
+        let imp = self.imp();
+        for field in [&imp.major_blade_field, &imp.minor_blade_field] {
+            field.set_text(&number_format().to_field(&previous.to_stored(&field.text())));
+        }
+    }
+
+    pub fn replace_details(&self, details: ProjectDetails) {
+        self.imp().details.replace(details);
+    }
+
+    pub fn replace_field_data(&self, data: (SolverRef, u32, String, String)) {
         let (solver, blade_unit, major_blade, minor_blade) = data;
         let imp = self.imp();
-        imp.solver_field.set_selected(solver);
+
+        // Fall back to the first solver for solvers from newer project files
+        imp.solver_field
+            .set_selected(solver.index().unwrap_or(0) as u32);
         imp.blade_unit_field.set_selected(blade_unit);
-        imp.major_blade_field.set_text(&major_blade);
-        imp.minor_blade_field.set_text(&minor_blade);
+        imp.major_blade_field
+            .set_text(&number_format().to_field(&major_blade));
+        imp.minor_blade_field
+            .set_text(&number_format().to_field(&minor_blade));
+    }
+
+    pub fn replace_solver_parameters(&self, solver_parameters: HashMap<String, Parameters>) {
+        self.imp().solver_parameters.replace(solver_parameters);
+        self.update_parameter_rows();
     }
 
     pub fn replace_results(&self, results: Option<Result<Solution, String>>) {
@@ -219,6 +388,15 @@ impl SolverPane {
         self.imp().results.borrow().clone()
     }
 
+    pub fn solver_info(&self) -> &'static SolverInfo {
+        let i = self.imp().solver_field.selected() as usize;
+        SOLVERS.get(i).unwrap_or(&SOLVERS[0])
+    }
+
+    pub fn solver_parameters(&self) -> HashMap<String, Parameters> {
+        self.imp().solver_parameters.borrow().clone()
+    }
+
     fn display_engine(&self) -> RefMut<'_, DisplayEngine> {
         self.imp().display_engine.borrow_mut()
     }
@@ -242,53 +420,265 @@ impl SolverPane {
         };
 
         let format = FractionFormat::from(self.size_format(), self.size_precision());
+        let units = self.display_units();
 
         let mut i = 1;
-        for (material, sub_solution) in solution.iter() {
+        for material in sorted_materials(solution) {
+            let sub_solution = &solution[material];
             for cut_list in sub_solution.cut_lists.iter() {
                 d.start_section();
-                d.append_header_1(&format!("Cut List {} ({})", i, material.name));
+                let header = if cut_list.locked {
+                    i18n_f(
+                        "Cut List {} ({}, locked)",
+                        &[&i.to_string(), &material.name],
+                    )
+                } else {
+                    i18n_f("Cut List {} ({})", &[&i.to_string(), &material.name])
+                };
+                d.append_header_1(&header);
                 let supply = &sub_solution.supplies[cut_list.supply_index];
                 let mut s = String::new();
-                s.push_str(&format!("Repeats: {}\n", cut_list.quantity));
-                s.push_str(&format!("Supply \"{}\"\n", supply.name));
-                s.push_str(&format!(
-                    "Original length {}\n",
-                    supply.length.format(&format)
+                s.push_str(&ni18n_f(
+                    "Repeats: {} time",
+                    "Repeats: {} times",
+                    cut_list.quantity as u32,
+                    &[&cut_list.quantity.to_string()],
+                ));
+                s.push('\n');
+                s.push_str(&i18n_f("Supply \"{}\"", &[&supply.name]));
+                s.push('\n');
+                s.push_str(&i18n_f(
+                    "Original length {}",
+                    &[&units.format_supply(&supply.length, &format)],
                 ));
-                s.push_str("Parts to cut:\n");
-                for (i, part_index) in cut_list.part_indices.iter().enumerate() {
-                    let part = &sub_solution.parts[*part_index];
+                s.push('\n');
+                let layout = CutLayout::from(cut_list, sub_solution);
+                if layout.end_trim > Fraction::zero() {
+                    let end_trim = Size::from_meters_in(layout.end_trim, &supply.length.unit);
+                    s.push_str(&i18n_f(
+                        "Trim the end by {}",
+                        &[&units.format_supply(&end_trim, &format)],
+                    ));
+                    s.push('\n');
+                }
+                s.push_str(&gettext("Parts to cut:"));
+                s.push('\n');
+                for (i, placement) in layout.placements.iter().enumerate() {
+                    let part = &sub_solution.parts[placement.part_index];
+                    let mut details = vec![units.format_part(&part.length, &format)];
+                    if !part.angles.is_square() {
+                        details.push(format_angles(&part.angles));
+                    }
+                    if placement.flipped {
+                        details.push(gettext("flipped"));
+                    }
                     s.push_str(&format!(
                         "\t#{}\t{} ({})",
                         i + 1,
-                        part.name,
-                        part.length.format(&format)
+                        part.label(),
+                        details.join(", ")
                     ));
                     if i < cut_list.part_indices.len() - 1 {
                         s.push_str("\n");
                     }
                 }
+                let marks: Vec<_> = layout
+                    .marks()
+                    .into_iter()
+                    .map(|m| {
+                        let mark = Size::from_meters_in(m, &supply.length.unit);
+                        units.format_supply(&mark, &format)
+                    })
+                    .collect();
+                if !marks.is_empty() {
+                    s.push('\n');
+                    s.push_str(&i18n_f(
+                        "Marks from the end, past each kerf (cut on the end side): {}",
+                        &[&marks.join(", ")],
+                    ));
+                }
+                if layout.offcut() > Fraction::zero() {
+                    let offcut = Size::from_meters_in(layout.offcut(), &supply.length.unit);
+                    s.push('\n');
+                    s.push_str(&i18n_f(
+                        "Offcut {}",
+                        &[&units.format_supply(&offcut, &format)],
+                    ));
+                }
                 d.append_paragraph(&s);
-                d.append_cut_diagram(cut_list, sub_solution, max_length);
+                d.append_cut_diagram(cut_list, sub_solution, &format, &units, max_length);
                 d.end_section();
                 i += 1;
             }
         }
     }
 
+    fn draw_cutting_instructions(&self, solution: &Solution) {
+        let mut d = self.display_engine();
+        d.start_section();
+        d.append_header_1(&gettext("Cutting Instructions"));
+        d.append_paragraph(&gettext(
+            "Set the stop block once for each length and cut every piece of that length before \
+            moving it. Pieces are numbered in cutting order.",
+        ));
+
+        let format = FractionFormat::from(self.size_format(), self.size_precision());
+        let units = self.display_units();
+
+        // Cut lists are numbered as in draw_cut_lists()
+        let mut first_cut_list = 1;
+        for material in sorted_materials(solution) {
+            let sub_solution = &solution[material];
+            d.append_header_2(&material.name);
+            for step in cut_sequence(sub_solution) {
+                let unit = &sub_solution.parts[step.cuts[0].part_index].length.unit;
+                let length = Size::from_meters_in(step.length, unit);
+                let n = step.cuts.len();
+                let instruction = if step.angles.is_square() {
+                    ni18n_f(
+                        "Set the stop block to {} and cut {} piece",
+                        "Set the stop block to {} and cut {} pieces",
+                        n as u32,
+                        &[&units.format_part(&length, &format), &n.to_string()],
+                    )
+                } else {
+                    ni18n_f(
+                        "Set the stop block to {}, set the ends to {} and cut {} piece",
+                        "Set the stop block to {}, set the ends to {} and cut {} pieces",
+                        n as u32,
+                        &[
+                            &units.format_part(&length, &format),
+                            &format_angles(&step.angles),
+                            &n.to_string(),
+                        ],
+                    )
+                };
+                d.append_paragraph(&instruction);
+
+                let mut rows = vec![vec![
+                    format!("<b>{}</b>", gettext("Piece")),
+                    format!("<b>{}</b>", gettext("Part")),
+                    format!("<b>{}</b>", gettext("From")),
+                ]];
+                let runs = step.cuts.chunk_by(|a, b| {
+                    a.part_index == b.part_index && a.cut_list_index == b.cut_list_index
+                });
+                for run in runs {
+                    let (first, last) = (&run[0], &run[run.len() - 1]);
+                    let numbers = if first.number == last.number {
+                        format!("#{}", first.number)
+                    } else {
+                        format!("#{}–{}", first.number, last.number)
+                    };
+                    let cut_list_number = first_cut_list + first.cut_list_index;
+                    let from = if sub_solution.cut_lists[first.cut_list_index].quantity == 1 {
+                        i18n_f("Cut list {}", &[&cut_list_number.to_string()])
+                    } else if first.repeat == last.repeat {
+                        i18n_f(
+                            "Cut list {}, board {}",
+                            &[&cut_list_number.to_string(), &first.repeat.to_string()],
+                        )
+                    } else {
+                        i18n_f(
+                            "Cut list {}, boards {}–{}",
+                            &[
+                                &cut_list_number.to_string(),
+                                &first.repeat.to_string(),
+                                &last.repeat.to_string(),
+                            ],
+                        )
+                    };
+                    let part = &sub_solution.parts[first.part_index];
+                    rows.push(vec![
+                        numbers,
+                        glib::markup_escape_text(&part.label()).to_string(),
+                        from,
+                    ]);
+                }
+                d.append_table(
+                    rows,
+                    vec![gtk::Align::Start, gtk::Align::Start, gtk::Align::Start],
+                );
+            }
+
+            // Segments of each spliced part are listed together, in order
+            let segments: Vec<_> = sub_solution
+                .parts
+                .iter()
+                .filter(|p| p.segment.is_some())
+                .collect();
+            for run in segments.chunk_by(|_, b| b.segment.as_ref().unwrap().number != 1) {
+                let part = Part {
+                    segment: None,
+                    ..run[0].clone()
+                };
+                let lengths: Vec<_> = run
+                    .iter()
+                    .map(|p| units.format_part(&p.length, &format))
+                    .collect();
+                d.append_paragraph(&i18n_f(
+                    "Splice {} ({}) from {} segments: {}",
+                    &[
+                        &part.label(),
+                        &units.format_part(&run[0].segment.as_ref().unwrap().part_length, &format),
+                        &run.len().to_string(),
+                        &lengths.join(", "),
+                    ],
+                ));
+            }
+            first_cut_list += sub_solution.cut_lists.len();
+        }
+
+        d.end_section();
+    }
+
+    /// Lists the project details (if any) as the report header
+    fn draw_details(&self) {
+        let details = self.details();
+        if details.is_empty() {
+            return;
+        }
+        let mut d = self.display_engine();
+        if details.title.is_empty() {
+            d.append_header_1(&gettext("Project"));
+        } else {
+            d.append_header_1(&details.title);
+        }
+        let mut rows = Vec::new();
+        for (name, value) in [
+            (gettext("Customer"), details.customer.clone()),
+            (gettext("Job Number"), details.job_number.clone()),
+            (gettext("Due Date"), details.formatted_due_date()),
+        ] {
+            if !value.is_empty() {
+                rows.push(vec![
+                    format!("<b>{}</b>", name),
+                    glib::markup_escape_text(&value).to_string(),
+                ]);
+            }
+        }
+        if !rows.is_empty() {
+            d.append_table(rows, vec![gtk::Align::Start, gtk::Align::Start]);
+        }
+        if !details.notes.is_empty() {
+            d.append_paragraph(&details.notes);
+        }
+    }
+
     fn draw_results(&self) {
         self.display_engine().clear();
+        self.draw_details();
         match self.imp().results.borrow().as_ref() {
             Some(Ok(solution)) => {
                 self.draw_solution(solution);
             }
             Some(Err(message)) => {
-                let message = &format!("Solver failed\n{}", message);
+                let message = &format!("{}\n{}", gettext("Solver failed"), message);
                 self.display_engine().append_paragraph(message);
             }
             None => {
-                self.display_engine().append_paragraph("Solver not yet run");
+                self.display_engine()
+                    .append_paragraph(&gettext("Solver not yet run"));
             }
         }
     }
@@ -296,46 +686,130 @@ impl SolverPane {
     fn draw_shopping_list(&self, solution: &Solution) {
         let mut d = self.display_engine();
         d.start_section();
-        d.append_header_1("Shopping List");
+        d.append_header_1(&gettext("Shopping List"));
 
         let format = FractionFormat::from(self.size_format(), self.size_precision());
+        let units = self.display_units();
+        let precision = self.price_precision();
 
         // TODO: Only do this once, not whenever we redraw?
         let consumption = compute_supply_consumption(solution);
+        let vendor_totals = compute_vendor_totals(solution);
 
-        // TODO: Order of material iteration is arbitrary (changes every redraw)
+        // Purchases are grouped by vendor, each with its own tax and fees
+        for vendor_total in vendor_totals.iter() {
+            if vendor_total.vendor.is_empty() {
+                d.append_header_2(&gettext("Other Supplies"));
+            } else {
+                d.append_header_2(&vendor_total.vendor);
+            }
+            let mut rows = vec![vec![
+                format!("<b>{}</b>", gettext("Material")),
+                format!("<b>{}</b>", gettext("Supply")),
+                format!("<b>{}</b>", gettext("Length")),
+                format!("<b>{}</b>", gettext("Price")),
+                format!("<b>{}</b>", gettext("Count")),
+                format!("<b>{}</b>", gettext("Total")),
+            ]];
+            for material in sorted_materials(solution) {
+                for (i, consumption) in consumption[material].iter().enumerate() {
+                    let supply = &solution[material].supplies[i];
+                    if *consumption == 0
+                        || supply.cost(*consumption).is_zero()
+                        || supply.vendor != vendor_total.vendor
+                    {
+                        continue;
+                    }
+                    // Packs and bundles can leave spare items, or not fit in a limited stock
+                    let bought = supply.purchase_quantity(*consumption);
+                    let count = if bought > *consumption {
+                        i18n_f(
+                            "{} ({} spare)",
+                            &[&bought.to_string(), &(bought - consumption).to_string()],
+                        )
+                    } else if bought < *consumption {
+                        i18n_f(
+                            "{} ({} short)",
+                            &[&bought.to_string(), &(consumption - bought).to_string()],
+                        )
+                    } else {
+                        bought.to_string()
+                    };
+                    rows.push(vec![
+                        glib::markup_escape_text(&material.name).to_string(),
+                        glib::markup_escape_text(&supply.name).to_string(),
+                        units.format_supply(&supply.length, &format),
+                        format_price(supply.price, precision),
+                        count,
+                        format_price(supply.cost(*consumption), precision),
+                    ]);
+                }
+            }
+            let mut totals = vec![(gettext("Subtotal"), vendor_total.subtotal)];
+            if !vendor_total.tax.is_zero() {
+                totals.push((gettext("Tax"), vendor_total.tax));
+            }
+            if !vendor_total.fees.is_zero() {
+                totals.push((gettext("Fees"), vendor_total.fees));
+            }
+            totals.push((gettext("Total"), vendor_total.total()));
+            for (name, amount) in totals {
+                let mut row = vec![String::new(); 6];
+                row[0] = format!("<b>{}</b>", name);
+                row[5] = format!("<b>{}</b>", format_price(amount, precision));
+                rows.push(row);
+            }
+            d.append_table(
+                rows,
+                vec![
+                    gtk::Align::Start,
+                    gtk::Align::Start,
+                    gtk::Align::End,
+                    gtk::Align::End,
+                    gtk::Align::End,
+                    gtk::Align::End,
+                ],
+            );
+        }
+        if vendor_totals.len() > 1 {
+            d.append_paragraph(&i18n_f(
+                "Total for all vendors {}",
+                &[&format_price(compute_total_price(solution), precision)],
+            ));
+        }
+
+        // Supplies already on hand (entered without a price) only need to be gathered
         let mut rows = vec![vec![
-            String::from("<b>Material</b>"),
-            String::from("<b>Supply</b>"),
-            String::from("<b>Length</b>"),
-            String::from("<b>Price</b>"),
-            String::from("<b>Count</b>"),
-            String::from("<b>Total</b>"),
+            format!("<b>{}</b>", gettext("Material")),
+            format!("<b>{}</b>", gettext("Supply")),
+            format!("<b>{}</b>", gettext("Length")),
+            format!("<b>{}</b>", gettext("Count")),
         ]];
-        for (material, sub_consumption) in consumption.iter() {
-            for (i, consumption) in sub_consumption.iter().enumerate() {
+        for material in sorted_materials(solution) {
+            for (i, consumption) in consumption[material].iter().enumerate() {
                 let supply = &solution[material].supplies[i];
-                rows.push(vec![
-                    material.name.clone(),
-                    supply.name.clone(),
-                    supply.length.format(&format),
-                    format_price(supply.price, self.price_precision()),
-                    consumption.to_string(),
-                    format_price(supply.price * (*consumption), self.price_precision()),
-                ]);
+                if *consumption > 0 && supply.cost(*consumption).is_zero() {
+                    rows.push(vec![
+                        glib::markup_escape_text(&material.name).to_string(),
+                        glib::markup_escape_text(&supply.name).to_string(),
+                        units.format_supply(&supply.length, &format),
+                        consumption.to_string(),
+                    ]);
+                }
             }
         }
-        d.append_table(
-            rows,
-            vec![
-                gtk::Align::Start,
-                gtk::Align::Start,
-                gtk::Align::End,
-                gtk::Align::End,
-                gtk::Align::End,
-                gtk::Align::End,
-            ],
-        );
+        if rows.len() > 1 {
+            d.append_header_2(&gettext("Pull from Shop"));
+            d.append_table(
+                rows,
+                vec![
+                    gtk::Align::Start,
+                    gtk::Align::Start,
+                    gtk::Align::End,
+                    gtk::Align::End,
+                ],
+            );
+        }
 
         d.end_section();
     }
@@ -344,21 +818,57 @@ impl SolverPane {
         self.draw_summary(solution);
         self.draw_shopping_list(solution);
         self.draw_cut_lists(solution);
+        self.draw_cutting_instructions(solution);
     }
 
     fn draw_summary(&self, solution: &Solution) {
         let mut d = self.display_engine();
         d.start_section();
-        d.append_header_1("Summary");
-        d.append_paragraph("Solution found!");
+        d.append_header_1(&gettext("Summary"));
+        d.append_paragraph(&gettext("Solution found!"));
 
         // TODO: Only do this once, not whenever we redraw?
         let total_price = compute_total_price(solution);
 
-        d.append_paragraph(&format!(
+        d.append_paragraph(&i18n_f(
             "Total price {}",
-            format_price(total_price, self.price_precision())
+            &[&format_price(total_price, self.price_precision())],
         ));
+
+        // Bounds are before tax and fees, so the gap is measured against the supplies alone
+        let subtotal = compute_supply_subtotal(solution);
+        if let (Some(lower_bound), Some(gap)) = (
+            compute_total_lower_bound(solution),
+            compute_optimality_gap(solution),
+        ) {
+            if subtotal != total_price {
+                d.append_paragraph(&i18n_f(
+                    "Supplies {} before tax and fees",
+                    &[&format_price(subtotal, self.price_precision())],
+                ));
+            }
+            d.append_paragraph(&i18n_f(
+                "Lower bound {}, gap {}%",
+                &[
+                    &format_price(lower_bound, self.price_precision()),
+                    &format!("{:.1}", gap),
+                ],
+            ));
+        }
+
+        // Fill parts are made in whatever number fit, so say how many that was
+        let production = compute_part_production(solution);
+        for material in sorted_materials(solution) {
+            let sub_solution = &solution[material];
+            for (part, count) in sub_solution.parts.iter().zip(production[material].iter()) {
+                if part.fill {
+                    d.append_paragraph(&i18n_f(
+                        "Made {} × {} ({}) from offcuts",
+                        &[&count.to_string(), &part.label(), &material.name],
+                    ));
+                }
+            }
+        }
         d.end_section();
     }
 
@@ -375,7 +885,7 @@ impl SolverPane {
         // Set the title of the length field based on the unit type
         imp.blade_unit_field
             .bind_property("selected", &imp.major_blade_field.get(), "title")
-            .transform_to(|_, i| Some(SizeUnit::from(i).major_name()))
+            .transform_to(|_, i| Some(major_unit_name(&SizeUnit::from(i))))
             .sync_create()
             .build();
     }
@@ -386,6 +896,7 @@ impl SolverPane {
             #[weak(rename_to = pane)]
             self,
             move |_| {
+                pane.update_parameter_rows();
                 pane.signal_fields_updated();
             }
         ));
@@ -412,6 +923,9 @@ impl SolverPane {
             "display-font",
             "size-format",
             "size-precision",
+            "supply-unit",
+            "part-unit",
+            "dual-units",
             "price-precision",
         ] {
             self.connect_notify(Some(name), |pane, _| {
@@ -423,6 +937,25 @@ impl SolverPane {
         });
     }
 
+    fn set_parameter(&self, info: &SolverInfo, key: &str, value: ParameterValue) {
+        self.imp()
+            .solver_parameters
+            .borrow_mut()
+            .entry(String::from(info.id))
+            .or_default()
+            .insert(String::from(key), value);
+        self.signal_fields_updated();
+    }
+
+    fn setup_solvers(&self) {
+        let imp = self.imp();
+        let names: Vec<String> = SOLVERS.iter().map(|s| gettext(s.name)).collect();
+        let names: Vec<&str> = names.iter().map(|s| s.as_str()).collect();
+        imp.solver_field
+            .set_model(Some(&gtk::StringList::new(&names)));
+        self.update_parameter_rows();
+    }
+
     fn signal_fields_updated(&self) {
         self.emit_by_name::<()>("fields-updated", &[]);
     }
@@ -436,6 +969,79 @@ impl SolverPane {
         }
     }
 
+    /// Shows the description of the selected solver and a row for each of its parameters
+    fn update_parameter_rows(&self) {
+        let imp = self.imp();
+        let info = self.solver_info();
+        imp.solver_field.set_subtitle(&format!(
+            "{}\n{}",
+            gettext(info.description),
+            info.capability_summary()
+        ));
+
+        for row in imp.parameter_rows.take() {
+            imp.parameters_group.remove(&row);
+        }
+        let parameters = self.parameters(info);
+        let mut rows: Vec<gtk::Widget> = Vec::new();
+        for spec in info.parameters.iter() {
+            let key = spec.key;
+            let row: gtk::Widget = match &spec.kind {
+                ParameterKind::Integer { min, max } => {
+                    let row = adw::SpinRow::with_range(*min as f64, *max as f64, 1.0);
+                    row.set_value(spec.integer(&parameters) as f64);
+                    row.connect_value_notify(clone!(
+                        #[weak(rename_to = pane)]
+                        self,
+                        move |row| {
+                            let value = ParameterValue::Integer(row.value() as i64);
+                            pane.set_parameter(info, key, value);
+                        }
+                    ));
+                    row.upcast()
+                }
+                ParameterKind::Float { min, max, digits } => {
+                    let step = 10f64.powi(-(*digits as i32));
+                    let row = adw::SpinRow::with_range(*min, *max, step);
+                    row.set_digits(*digits);
+                    row.set_value(spec.float(&parameters));
+                    row.connect_value_notify(clone!(
+                        #[weak(rename_to = pane)]
+                        self,
+                        move |row| {
+                            let value = ParameterValue::Float(row.value());
+                            pane.set_parameter(info, key, value);
+                        }
+                    ));
+                    row.upcast()
+                }
+                ParameterKind::Choice(names) => {
+                    let names: Vec<String> = names.iter().map(|n| gettext(*n)).collect();
+                    let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+                    let row = adw::ComboRow::builder()
+                        .model(&gtk::StringList::new(&names))
+                        .selected(spec.choice(&parameters) as u32)
+                        .build();
+                    row.connect_selected_notify(clone!(
+                        #[weak(rename_to = pane)]
+                        self,
+                        move |row| {
+                            let value = ParameterValue::Choice(row.selected() as usize);
+                            pane.set_parameter(info, key, value);
+                        }
+                    ));
+                    row.upcast()
+                }
+            };
+            row.set_property("title", gettext(spec.name));
+            row.set_tooltip_text(Some(&gettext(spec.description)));
+            imp.parameters_group.add(&row);
+            rows.push(row);
+        }
+        imp.parameters_group.set_visible(!rows.is_empty());
+        imp.parameter_rows.replace(rows);
+    }
+
     fn update_placeholder(&self) {
         let name = if self.imp().results.borrow().is_none() {
             "placeholder"
@@ -445,6 +1051,14 @@ impl SolverPane {
         self.imp().content_stack.set_visible_child_name(name);
     }
 
+    fn display_units(&self) -> DisplayUnits {
+        DisplayUnits {
+            supply: SizeUnit::from_display(self.supply_unit()),
+            part: SizeUnit::from_display(self.part_unit()),
+            dual: self.dual_units(),
+        }
+    }
+
     fn use_minor_blade(&self) -> bool {
         SizeUnit::from(self.imp().blade_unit_field.selected()).has_minor()
     }
@@ -452,12 +1066,13 @@ impl SolverPane {
     fn validate_all_entries(&self) {
         let mut all_valid = true;
         let imp = self.imp();
+        let unit = SizeUnit::from(imp.blade_unit_field.selected());
         all_valid &= validate_entry(&imp.major_blade_field.get(), None, |e| {
-            parse_positive_fraction(&e.text(), true).is_ok()
+            check_size_field(e, &unit, false, true)
         });
         if self.use_minor_blade() {
             all_valid &= validate_entry(&imp.minor_blade_field.get(), None, |e| {
-                parse_positive_fraction(&e.text(), true).is_ok()
+                check_size_field(e, &unit, true, true)
             });
         }
         self.action_set_enabled("win.solve", all_valid);
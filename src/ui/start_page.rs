@@ -0,0 +1,89 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use gtk::glib::subclass::InitializingObject;
+use gtk::{CompositeTemplate, glib};
+
+use super::recent_projects::RecentProject;
+
+mod imp {
+    use super::*;
+
+    // Object holding the state
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/com/ohmm-software/Chop-Chop/start_page.ui")]
+    pub struct StartPage {
+        #[template_child]
+        pub(super) recent_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub(super) recent_list: TemplateChild<gtk::ListBox>,
+    }
+
+    // The central trait for subclassing a GObject
+    #[glib::object_subclass]
+    impl ObjectSubclass for StartPage {
+        const NAME: &'static str = "ChopChopStartPage";
+        type Type = super::StartPage;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            // Required for CompositeTemplate
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            // Required for CompositeTemplate
+            obj.init_template();
+        }
+    }
+
+    // Trait shared by all GObjects
+    impl ObjectImpl for StartPage {}
+
+    // Trait shared by all widgets
+    impl WidgetImpl for StartPage {}
+
+    // Trait shared by all Adwaita bins
+    impl BinImpl for StartPage {}
+}
+
+glib::wrapper! {
+    pub struct StartPage(ObjectSubclass<imp::StartPage>)
+        @extends adw::Bin, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl StartPage {
+    /// Lists the projects to open, each with a button to pin it (see `win.open-recent`)
+    pub fn update_recent_projects(&self, projects: &[RecentProject]) {
+        let imp = self.imp();
+        imp.recent_list.remove_all();
+        for project in projects {
+            let path = project.path.display().to_string();
+            let row = adw::ActionRow::builder()
+                .title(project.name())
+                .subtitle(project.folder())
+                .use_markup(false)
+                .activatable(true)
+                .action_name("win.open-recent")
+                .action_target(&path.to_variant())
+                .build();
+            let pin_button = gtk::ToggleButton::builder()
+                .icon_name("view-pin-symbolic")
+                .tooltip_text(if project.pinned {
+                    gettext("Unpin")
+                } else {
+                    gettext("Pin")
+                })
+                .active(project.pinned)
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .action_name("win.pin-recent")
+                .action_target(&path.to_variant())
+                .build();
+            row.add_suffix(&pin_button);
+            imp.recent_list.append(&row);
+        }
+        imp.recent_group.set_visible(!projects.is_empty());
+    }
+}
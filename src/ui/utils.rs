@@ -3,12 +3,20 @@ use std::str::FromStr;
 
 use adw::prelude::*;
 use fraction::{Decimal, Fraction, Zero};
+use gettextrs::gettext;
 
+use super::assembly::assembly_instances;
 use super::entry::EntryData;
+use super::number_format::{STORED_SEPARATOR, number_format};
+use super::project::ProjectData;
+use crate::i18n::i18n_f;
 use crate::modeling::{
-    Dimension, Material, Part, Problem, Solution, SubProblem, SubSolution, Supply,
+    Bundle, Dimension, EndAngles, Material, Part, Problem, Solution, Splice, SubProblem,
+    SubSolution, Supply,
 };
-use crate::size::{FractionFormat, Size, SizeUnit};
+use crate::size::{FractionFormat, LengthError, Size, SizeUnit, normalize_decimal};
+use crate::solvers::{SolverInfo, split_long_parts};
+use crate::utils::parse_amount;
 
 // Put `from` methods here because selection indices are UI-specific
 
@@ -25,7 +33,7 @@ impl Dimension {
 impl FractionFormat {
     pub fn from(selection: u32, precision: u32) -> Self {
         match selection {
-            0 => Self::Decimal(precision as usize),
+            0 => Self::Decimal(precision as usize, number_format().separator()),
             1 => Self::Mixed,
             2 => Self::Fraction,
             _ => panic!(),
@@ -35,26 +43,58 @@ impl FractionFormat {
 
 impl Size {
     pub fn from(selection: u32, major: &str, minor: &str) -> Self {
+        let unit = SizeUnit::from(selection);
         Self {
-            unit: SizeUnit::from(selection),
-            major: parse_positive_fraction(major, true).unwrap(),
-            minor: parse_positive_fraction(minor, true).unwrap(),
+            major: parse_size_field(major, &unit, false, true, STORED_SEPARATOR).unwrap(),
+            minor: parse_size_field(minor, &unit, true, true, STORED_SEPARATOR).unwrap(),
+            unit,
         }
     }
 }
 
 impl SizeUnit {
+    /// Converts a display unit selection, where 0 keeps the unit that sizes were entered in
+    pub fn from_display(selection: u32) -> Option<Self> {
+        selection.checked_sub(1).map(Self::from)
+    }
+
     pub fn from(selection: u32) -> Self {
+        Self::checked_from(selection).unwrap()
+    }
+
+    /// Like `from`, but for selections read from a file (which may be out of range)
+    pub fn checked_from(selection: u32) -> Option<Self> {
         match selection {
-            0 => Self::FeetInches,
-            1 => Self::Inches,
-            2 => Self::Meters,
-            3 => Self::Centimeters,
-            _ => panic!(),
+            0 => Some(Self::FeetInches),
+            1 => Some(Self::Inches),
+            2 => Some(Self::Meters),
+            3 => Some(Self::Centimeters),
+            _ => None,
         }
     }
 }
 
+/// Units chosen in the preferences for displaying supply and part sizes (`None` keeps the unit
+/// they were entered in)
+#[derive(Debug, Clone, Default)]
+pub struct DisplayUnits {
+    pub supply: Option<SizeUnit>,
+    pub part: Option<SizeUnit>,
+
+    /// Whether to add the size in the other measurement system (e.g., "8 ft, 0 in (243.84 cm)")
+    pub dual: bool,
+}
+
+impl DisplayUnits {
+    pub fn format_supply(&self, size: &Size, format: &FractionFormat) -> String {
+        size.format_in(self.supply.as_ref(), self.dual, format)
+    }
+
+    pub fn format_part(&self, size: &Size, format: &FractionFormat) -> String {
+        size.format_in(self.part.as_ref(), self.dual, format)
+    }
+}
+
 /// For Option<Result<Solution, String>>> serialization (required due to HashMap)
 pub fn flatten_results(
     results: Option<Result<Solution, String>>,
@@ -66,42 +106,106 @@ pub fn flatten_results(
     }
 }
 
+/// Formats end angles for display (e.g., "45° / -45°")
+pub fn format_angles(angles: &EndAngles) -> String {
+    let format = FractionFormat::Decimal(2, number_format().separator());
+    format!(
+        "{}° / {}°",
+        format.format(angles.start),
+        format.format(angles.end)
+    )
+}
+
+/// Lists bundle prices (e.g., "10 for $30.00; 50 for $140.00")
+pub fn format_bundles(bundles: &[Bundle], precision: u32) -> String {
+    bundles
+        .iter()
+        .map(|b| {
+            i18n_f(
+                "{} for {}",
+                &[&b.quantity.to_string(), &format_price(b.price, precision)],
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Formats a price with the currency symbol and decimal separator of the `NumberFormat`
 pub fn format_price(price: fraction::Decimal, precision: u32) -> String {
     if price.is_zero() {
-        String::from("Free")
+        gettext("Free")
     } else {
         // Conversion to f64 is required for correct rounding
         let value: f64 = price.try_into().unwrap();
 
-        format!("${0:.1$}", value, precision as usize)
+        let number_format = number_format();
+        let amount = format!("{0:.1$}", value, precision as usize)
+            .replace('.', &number_format.separator().to_string());
+        number_format.with_symbol(&amount)
     }
 }
 
 pub fn format_quantity(quantity: i64) -> String {
     if quantity == -1 {
-        String::from("Unlimited")
+        gettext("Unlimited")
     } else {
         quantity.to_string()
     }
 }
 
-pub fn generate_problem(
-    supply_entry_data: Vec<EntryData>,
-    part_entry_data: Vec<EntryData>,
-    blade_width: Size,
-) -> Problem {
+/// Translated name of the larger part of a unit (e.g., "Feet")
+pub fn major_unit_name(unit: &SizeUnit) -> String {
+    gettext(unit.major_name())
+}
+
+/// Translated name of the smaller part of a unit, or an empty string if it has none
+pub fn minor_unit_name(unit: &SizeUnit) -> String {
+    if unit.has_minor() {
+        gettext(unit.minor_name())
+    } else {
+        String::new()
+    }
+}
+
+/// Builds a problem from the supplies, parts, and settings of a project
+///
+/// Parts in an assembly are repeated once for each instance of the assembly. Materials assigned to
+/// a tool profile use its kerf and end trim instead of `blade_width`. Fails if a part refers to an
+/// unknown assembly, if assemblies are nested in a cycle, if a locked pattern refers to supplies or
+/// parts that no longer exist, or if an entry has a field that can't be parsed (see
+/// `EntryData::is_valid`).
+pub fn generate_problem(project: &ProjectData, blade_width: Size) -> Result<Problem, String> {
+    let ProjectData {
+        supply_entries,
+        part_entries,
+        assemblies,
+        locked_patterns,
+        material_settings,
+        tool_profiles,
+        vendor_settings,
+        ..
+    } = project;
     let mut problem = Problem::new();
 
-    for entry_data in supply_entry_data {
+    for entry_data in supply_entries {
+        if !entry_data.is_valid() {
+            return Err(i18n_f(
+                "Supply \"{}\" has a field that can't be read",
+                &[&entry_data.name],
+            ));
+        }
         let material = Material {
             name: entry_data.material.clone(),
             dimension: Dimension::from(entry_data.dimension),
         };
         let supply = Supply {
             name: entry_data.name.clone(),
-            length: parse_length(&entry_data),
-            price: parse_price(&entry_data.price, true).unwrap(),
+            length: parse_length(entry_data),
+            price: parse_price(&entry_data.price, true, STORED_SEPARATOR).unwrap(),
             max_quantity: parse_quantity(&entry_data.quantity, true).unwrap(),
+            vendor: entry_data.vendor.trim().to_string(),
+            pack_size: parse_quantity(&entry_data.pack_size, true).unwrap(),
+            bundles: parse_bundles(&entry_data.bundles, STORED_SEPARATOR).unwrap(),
         };
         match problem.get_mut(&material) {
             Some(sub_problem) => {
@@ -112,66 +216,253 @@ pub fn generate_problem(
                     supplies: vec![supply],
                     parts: vec![],
                     blade_width: blade_width.clone(),
+                    depth: Size::default(),
+                    end_trim: Size::default(),
+                    vendors: Vec::new(),
+                    locked_cut_lists: Vec::new(),
                 };
                 problem.insert(material, sub_problem);
             }
         }
     }
 
-    for entry_data in part_entry_data {
+    for entry_data in part_entries {
+        if !entry_data.is_valid() {
+            return Err(i18n_f(
+                "Part \"{}\" has a field that can't be read",
+                &[&entry_data.name],
+            ));
+        }
         let material = Material {
             name: entry_data.material.clone(),
             dimension: Dimension::from(entry_data.dimension),
         };
-        let part = Part {
-            name: entry_data.name.clone(),
-            length: parse_length(&entry_data),
-            quantity: parse_quantity(&entry_data.quantity, true).unwrap(),
+        let instances = if entry_data.assembly.is_empty() {
+            vec![String::new()]
+        } else {
+            assembly_instances(assemblies, &entry_data.assembly)
+                .map_err(|message| i18n_f("Part \"{}\": {}", &[&entry_data.name, &message]))?
         };
-        match problem.get_mut(&material) {
-            Some(sub_problem) => {
-                sub_problem.parts.push(part);
+        for assembly in instances {
+            let angles = match material.dimension {
+                Dimension::OneD => parse_angles(&entry_data.start_angle, &entry_data.end_angle),
+                Dimension::TwoD => EndAngles::default(),
+            };
+            let splice = match material.dimension {
+                Dimension::OneD => parse_splice(entry_data),
+                Dimension::TwoD => None,
+            };
+            let part = Part {
+                name: entry_data.name.clone(),
+                length: parse_length(entry_data),
+                quantity: match parse_quantity(&entry_data.quantity, true).unwrap() {
+                    -1 if entry_data.fill => 0,
+                    quantity => quantity,
+                },
+                assembly,
+                angles,
+                splice,
+                segment: None,
+                fill: entry_data.fill && material.dimension == Dimension::OneD,
+            };
+            match problem.get_mut(&material) {
+                Some(sub_problem) => {
+                    sub_problem.parts.push(part);
+                }
+                None => {
+                    let sub_problem = SubProblem {
+                        supplies: vec![],
+                        parts: vec![part],
+                        blade_width: blade_width.clone(),
+                        depth: Size::default(),
+                        end_trim: Size::default(),
+                        vendors: Vec::new(),
+                        locked_cut_lists: Vec::new(),
+                    };
+                    problem.insert(material.clone(), sub_problem);
+                }
             }
-            None => {
-                let sub_problem = SubProblem {
-                    supplies: vec![],
-                    parts: vec![part],
-                    blade_width: blade_width.clone(),
-                };
-                problem.insert(material, sub_problem);
+        }
+    }
+
+    let vendors: Vec<_> = vendor_settings.iter().map(|v| v.vendor()).collect();
+    for sub_problem in problem.values_mut() {
+        sub_problem.vendors = vendors.clone();
+    }
+
+    for settings in material_settings {
+        if let Some(sub_problem) = problem.get_mut(&settings.material) {
+            sub_problem.depth = settings.depth();
+            if let Some(tool) = tool_profiles.iter().find(|t| t.name == settings.tool) {
+                sub_problem.blade_width = tool.kerf();
+                sub_problem.end_trim = tool.trim();
             }
         }
     }
 
-    problem
+    // Locked patterns may hold segments of spliced parts
+    for sub_problem in problem.values_mut() {
+        *sub_problem = split_long_parts(std::mem::take(sub_problem))?;
+    }
+
+    for pattern in locked_patterns {
+        let sub_problem = problem.get_mut(&pattern.material).ok_or_else(|| {
+            i18n_f(
+                "Locked cut list refers to a missing material \"{}\"",
+                &[&pattern.material.name],
+            )
+        })?;
+        let cut_list = pattern.resolve(sub_problem)?;
+        sub_problem.locked_cut_lists.push(cut_list);
+    }
+
+    Ok(problem)
 }
 
-pub fn parse_positive_fraction(text: &str, allow_empty: bool) -> Result<Fraction, ()> {
-    let tokens: Vec<_> = text.trim().split(" ").filter(|s| !s.is_empty()).collect();
-    if (tokens.is_empty() && !allow_empty) || (tokens.len() > 2) {
-        Err(())
-    } else {
-        let mut size = Fraction::zero();
-        for token in tokens {
-            size += match Fraction::from_str(token) {
-                Ok(value) if (!value.is_sign_negative()) => value,
-                _ => return Err(()),
+/// Fails if the problem has materials that the solver cannot handle
+pub fn check_capabilities(problem: Problem, info: &SolverInfo) -> Result<Problem, String> {
+    for material in problem.keys() {
+        let supported = match material.dimension {
+            Dimension::OneD => info.capabilities.one_d,
+            Dimension::TwoD => info.capabilities.two_d,
+        };
+        if !supported {
+            let dimension = match material.dimension {
+                Dimension::OneD => "1D",
+                Dimension::TwoD => "2D",
             };
+            return Err(i18n_f(
+                "The {} solver does not support {} materials such as \"{}\"",
+                &[&gettext(info.name), dimension, &material.name],
+            ));
         }
-        Ok(size)
     }
+    Ok(problem)
 }
 
+/// Parses an angle in degrees from square, which must be less than 90 either way
+///
+/// Empty text is a square cut.
+pub fn parse_angle(text: &str, separator: char) -> Result<Fraction, ()> {
+    let text = text.trim().trim_end_matches('°');
+    if text.is_empty() {
+        Ok(Fraction::zero())
+    } else {
+        match normalize_decimal(text, separator).map(|text| Fraction::from_str(&text)) {
+            Some(Ok(value))
+                if !value.is_nan() && value > Fraction::from(-90) && value < Fraction::from(90) =>
+            {
+                Ok(value)
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+/// Parses validated angle fields (see `parse_angle`)
+pub fn parse_angles(start: &str, end: &str) -> EndAngles {
+    EndAngles {
+        start: parse_angle(start, STORED_SEPARATOR).unwrap(),
+        end: parse_angle(end, STORED_SEPARATOR).unwrap(),
+    }
+}
+
+/// Parses the splice limits of a part, if it may be spliced
+///
+/// Limits are entered in the smaller unit of the part's length (e.g., inches for feet and inches).
+pub fn parse_splice(entry_data: &EntryData) -> Option<Splice> {
+    if !entry_data.splice {
+        return None;
+    }
+    let unit = SizeUnit::from(entry_data.length_unit);
+    let size = |text: &str| {
+        let value =
+            parse_size_field(text, &unit, unit.has_minor(), true, STORED_SEPARATOR).unwrap();
+        if unit.has_minor() {
+            Size {
+                unit: unit.clone(),
+                major: Fraction::zero(),
+                minor: value,
+            }
+        } else {
+            Size {
+                unit: unit.clone(),
+                major: value,
+                minor: Fraction::zero(),
+            }
+        }
+    };
+    Some(Splice {
+        min_segment: size(&entry_data.min_segment),
+        allowance: size(&entry_data.join_allowance),
+    })
+}
+
+/// Parses bundle prices entered as "10 for 30; 50 for 140" (empty for none)
+///
+/// The word between quantity and price may also be the translated one used by `format_bundles`.
+pub fn parse_bundles(text: &str, separator: char) -> Result<Vec<Bundle>, ()> {
+    let pattern = gettext("{} for {}");
+    let translated = pattern.split("{}").nth(1).unwrap_or_default();
+    let mut bundles = Vec::new();
+    for item in text.split(';').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        let (quantity, price) = [" for ", translated]
+            .into_iter()
+            .filter(|s| !s.trim().is_empty())
+            .find_map(|s| item.split_once(s))
+            .ok_or(())?;
+        let quantity = parse_quantity(quantity, false)?;
+        if quantity == 0 {
+            return Err(());
+        }
+        bundles.push(Bundle {
+            quantity,
+            price: parse_price(price, false, separator)?,
+        });
+    }
+    Ok(bundles)
+}
+
+/// Parses a length field in `unit`, or its minor unit if `minor` (see `SizeUnit::parse`)
+pub fn parse_size_field(
+    text: &str,
+    unit: &SizeUnit,
+    minor: bool,
+    allow_empty: bool,
+    separator: char,
+) -> Result<Fraction, LengthError> {
+    match unit.parse(text, minor, separator) {
+        Err(LengthError::Empty) if allow_empty => Ok(Fraction::zero()),
+        result => result,
+    }
+}
+
+/// Checks a length field with `parse_size_field` in the active `NumberFormat`, explaining any
+/// error in its tooltip
+pub fn check_size_field(
+    entry: &adw::EntryRow,
+    unit: &SizeUnit,
+    minor: bool,
+    allow_empty: bool,
+) -> bool {
+    let separator = number_format().separator();
+    let result = parse_size_field(&entry.text(), unit, minor, allow_empty, separator);
+    entry.set_tooltip_text(result.as_ref().err().map(|e| e.to_string()).as_deref());
+    result.is_ok()
+}
+
+/// Accepts `separator` as the decimal separator, with or without a currency symbol (see
+/// `parse_amount`)
 // Currently allows prices as fractions
-pub fn parse_price(text: &str, allow_empty: bool) -> Result<Decimal, ()> {
+pub fn parse_price(text: &str, allow_empty: bool, separator: char) -> Result<Decimal, ()> {
     let text = text.trim();
     if text.is_empty() && !allow_empty {
         Err(())
     } else if text.is_empty() {
         Ok(Decimal::zero())
     } else {
-        match Decimal::from_str(text) {
-            Ok(value) if (value >= Decimal::zero()) => Ok(value),
+        match parse_amount(text, &number_format().currency_symbol, separator) {
+            Some(value) if (value >= Decimal::zero()) => Ok(value),
             _ => Err(()),
         }
     }
@@ -232,9 +523,9 @@ where
 }
 
 fn parse_length(entry_data: &EntryData) -> Size {
-    Size {
-        unit: SizeUnit::from(entry_data.length_unit),
-        major: parse_positive_fraction(&entry_data.major_length, true).unwrap(),
-        minor: parse_positive_fraction(&entry_data.minor_length, true).unwrap(),
-    }
+    Size::from(
+        entry_data.length_unit,
+        &entry_data.major_length,
+        &entry_data.minor_length,
+    )
 }
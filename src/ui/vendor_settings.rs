@@ -0,0 +1,35 @@
+use fraction::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::number_format::STORED_SEPARATOR;
+use super::utils::parse_price;
+use crate::modeling::Vendor;
+
+/// Project-specific terms of a vendor named by supply entries
+///
+/// Amounts are stored as user-entered strings, as in `EntryData`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VendorSettings {
+    pub name: String,
+
+    /// Sales tax as a percentage (e.g., "8.25")
+    pub tax_percent: String,
+
+    pub delivery_fee: String,
+    pub minimum_order: String,
+    pub small_order_fee: String,
+}
+
+impl VendorSettings {
+    /// Panics if any amount is invalid (entries are validated before they are stored)
+    pub fn vendor(&self) -> Vendor {
+        let amount = |text: &str| parse_price(text, true, STORED_SEPARATOR).unwrap();
+        Vendor {
+            name: self.name.clone(),
+            tax_rate: amount(&self.tax_percent) / Decimal::from(100),
+            delivery_fee: amount(&self.delivery_fee),
+            minimum_order: amount(&self.minimum_order),
+            small_order_fee: amount(&self.small_order_fee),
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use std::cell::{Cell, RefCell};
+use std::sync::OnceLock;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use fraction::{Decimal, Zero};
+use gettextrs::gettext;
+use gtk::glib::{Object, clone, subclass::InitializingObject, subclass::Signal};
+use gtk::{CompositeTemplate, glib};
+
+use super::number_format::number_format;
+use super::utils::*;
+use super::vendor_settings::VendorSettings;
+use crate::i18n::i18n_f;
+
+mod imp {
+    use super::*;
+
+    // Object holding the state
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/com/ohmm-software/Chop-Chop/vendors_dialog.ui")]
+    pub struct VendorsDialog {
+        #[template_child]
+        pub(super) vendors_group: TemplateChild<adw::PreferencesGroup>,
+
+        // Settings being edited (read back by the window on "vendors-updated")
+        pub(super) vendor_settings: RefCell<Vec<VendorSettings>>,
+
+        // Used to summarize the terms of each vendor
+        pub(super) price_precision: Cell<u32>,
+    }
+
+    // The central trait for subclassing a GObject
+    #[glib::object_subclass]
+    impl ObjectSubclass for VendorsDialog {
+        const NAME: &'static str = "ChopChopVendorsDialog";
+        type Type = super::VendorsDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            // Required for CompositeTemplate
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            // Required for CompositeTemplate
+            obj.init_template();
+        }
+    }
+
+    // Trait shared by all GObjects
+    impl ObjectImpl for VendorsDialog {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+
+            // The parent window watches this signal to store the new settings
+            SIGNALS.get_or_init(|| vec![Signal::builder("vendors-updated").build()])
+        }
+    }
+
+    // Trait shared by all widgets
+    impl WidgetImpl for VendorsDialog {}
+
+    // Trait shared by all Adwaita dialogs
+    impl AdwDialogImpl for VendorsDialog {}
+}
+
+glib::wrapper! {
+    pub struct VendorsDialog(ObjectSubclass<imp::VendorsDialog>)
+        @extends adw::Dialog, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl VendorsDialog {
+    /// Lists `vendors` (the vendors named by supplies) with their current settings
+    pub fn new(
+        vendor_settings: Vec<VendorSettings>,
+        vendors: Vec<String>,
+        price_precision: u32,
+    ) -> Self {
+        let dialog: Self = Object::builder().build();
+        let imp = dialog.imp();
+        imp.vendor_settings.replace(vendor_settings);
+        imp.price_precision.set(price_precision);
+
+        if vendors.is_empty() {
+            let row = adw::ActionRow::builder()
+                .title(gettext("No supplies have a vendor"))
+                .build();
+            row.add_css_class("dim-label");
+            imp.vendors_group.add(&row);
+        }
+        for name in vendors {
+            let settings = dialog.settings(&name).unwrap_or(VendorSettings {
+                name,
+                ..Default::default()
+            });
+            dialog.add_vendor_row(settings);
+        }
+        dialog
+    }
+
+    pub fn vendor_settings(&self) -> Vec<VendorSettings> {
+        self.imp().vendor_settings.borrow().clone()
+    }
+
+    fn add_vendor_row(&self, settings: VendorSettings) {
+        let row = adw::ExpanderRow::builder()
+            .title(&settings.name)
+            .subtitle(self.describe_terms(&settings))
+            .use_markup(false)
+            .build();
+        let [tax_row, delivery_row, minimum_row, small_order_row] = [
+            (gettext("Sales Tax (%)"), &settings.tax_percent),
+            (gettext("Delivery Fee"), &settings.delivery_fee),
+            (gettext("Minimum Order"), &settings.minimum_order),
+            (
+                gettext("Fee for Orders Under the Minimum"),
+                &settings.small_order_fee,
+            ),
+        ]
+        .map(|(title, text)| {
+            adw::EntryRow::builder()
+                .title(title)
+                .text(number_format().to_field(text))
+                .max_length(16)
+                .build()
+        });
+        for field in [&tax_row, &delivery_row, &minimum_row, &small_order_row] {
+            row.add_row(field);
+        }
+
+        let update = clone!(
+            #[weak(rename_to = dialog)]
+            self,
+            #[weak]
+            row,
+            #[weak]
+            tax_row,
+            #[weak]
+            delivery_row,
+            #[weak]
+            minimum_row,
+            #[weak]
+            small_order_row,
+            move || {
+                let fields = [&tax_row, &delivery_row, &minimum_row, &small_order_row];
+                let number_format = number_format();
+                let mut all_valid = true;
+                for field in fields {
+                    all_valid &= validate_entry(field, None, |e| {
+                        parse_price(&e.text(), true, number_format.separator()).is_ok()
+                    });
+                }
+                if all_valid {
+                    let [tax_percent, delivery_fee, minimum_order, small_order_fee] =
+                        fields.map(|f| number_format.to_stored(f.text().trim()));
+                    let settings = VendorSettings {
+                        name: settings.name.clone(),
+                        tax_percent,
+                        delivery_fee,
+                        minimum_order,
+                        small_order_fee,
+                    };
+                    row.set_subtitle(&dialog.describe_terms(&settings));
+                    dialog.replace_settings(settings);
+                }
+            }
+        );
+        update();
+        for field in [&tax_row, &delivery_row, &minimum_row, &small_order_row] {
+            field.connect_changed(clone!(
+                #[strong]
+                update,
+                move |_| update()
+            ));
+        }
+        self.imp().vendors_group.add(&row);
+    }
+
+    /// Summarizes the tax and fees (e.g., "8.25% tax · $50.00 delivery")
+    fn describe_terms(&self, settings: &VendorSettings) -> String {
+        let precision = self.imp().price_precision.get();
+        let vendor = settings.vendor();
+        let mut terms = Vec::new();
+        if vendor.tax_rate > Decimal::zero() {
+            let tax_percent = number_format().to_field(&settings.tax_percent);
+            terms.push(i18n_f("{}% tax", &[&tax_percent]));
+        }
+        if vendor.delivery_fee > Decimal::zero() {
+            terms.push(i18n_f(
+                "{} delivery",
+                &[&format_price(vendor.delivery_fee, precision)],
+            ));
+        }
+        if vendor.small_order_fee > Decimal::zero() {
+            terms.push(i18n_f(
+                "{} fee under {}",
+                &[
+                    &format_price(vendor.small_order_fee, precision),
+                    &format_price(vendor.minimum_order, precision),
+                ],
+            ));
+        }
+        if terms.is_empty() {
+            gettext("No tax or fees")
+        } else {
+            terms.join(" · ")
+        }
+    }
+
+    /// Stores the settings of a vendor, notifying the window if they changed
+    fn replace_settings(&self, settings: VendorSettings) {
+        let is_empty = [
+            &settings.tax_percent,
+            &settings.delivery_fee,
+            &settings.minimum_order,
+            &settings.small_order_fee,
+        ]
+        .iter()
+        .all(|s| s.is_empty());
+        let changed = {
+            let mut vendor_settings = self.imp().vendor_settings.borrow_mut();
+            match vendor_settings.iter_mut().find(|s| s.name == settings.name) {
+                Some(s) => {
+                    let changed = s.vendor() != settings.vendor();
+                    *s = settings;
+                    changed
+                }
+                None if is_empty => false,
+                None => {
+                    vendor_settings.push(settings);
+                    true
+                }
+            }
+        };
+        if changed {
+            self.emit_by_name::<()>("vendors-updated", &[]);
+        }
+    }
+
+    fn settings(&self, name: &str) -> Option<VendorSettings> {
+        self.imp()
+            .vendor_settings
+            .borrow()
+            .iter()
+            .find(|s| s.name == name)
+            .cloned()
+    }
+}
@@ -1,22 +1,67 @@
 use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
 use std::fs::File;
 use std::path::PathBuf;
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
+use gettextrs::gettext;
 use gtk::glib::{Object, Properties, clone, closure_local, subclass::InitializingObject};
-use gtk::{CompositeTemplate, gio, gio::ListStore, gio::Settings, glib};
+use gtk::{CompositeTemplate, gdk, gio, gio::ListStore, gio::Settings, glib};
 
+use super::advisor_dialog::AdvisorDialog;
+use super::assemblies_dialog::AssembliesDialog;
+use super::assembly::Assembly;
+use super::csv_import::import_csv;
+use super::cut_plan::LockedPattern;
+use super::cut_plan_dialog::CutPlanDialog;
 use super::dialogs::{
-    about_dialog, open_failed_dialog, save_failed_dialog, unsaved_changes_dialog,
+    about_dialog, import_failed_dialog, open_failed_dialog, save_failed_dialog,
+    unsaved_changes_dialog,
 };
+use super::entry::EntryData;
 use super::entry_pane::EntryPane;
+use super::library_dialog::LibraryDialog;
+use super::material_settings::{MaterialSettings, ToolProfile};
+use super::materials_dialog::MaterialsDialog;
+use super::number_format::{NumberFormat, set_number_format};
+use super::number_format_dialog::NumberFormatDialog;
 use super::preferences_dialog::PreferencesDialog;
+use super::project::ProjectData;
+use super::project_details_dialog::ProjectDetailsDialog;
+use super::recent_projects::{
+    add_recent_project, clear_recent_projects, recent_projects, toggle_pinned,
+};
 use super::solver_overlay::SolverOverlay;
 use super::solver_pane::SolverPane;
+use super::start_page::StartPage;
 use super::utils::*;
-use crate::APP_ID;
-use crate::solvers::Message;
+use super::vendor_settings::VendorSettings;
+use super::vendors_dialog::VendorsDialog;
+use crate::advisor::{StockAdvice, advise_stock};
+use crate::modeling::{Dimension, Material};
+use crate::size::FractionFormat;
+use crate::solvers::{Message, Objective};
+use crate::{APP_ID, PROJECT_EXTENSION};
+
+/// Actions that only make sense once the start page has given way to a project
+const PROJECT_ACTIONS: &[&str] = &[
+    "save",
+    "save-as",
+    "library",
+    "assemblies",
+    "materials",
+    "vendors",
+    "project-details",
+    "number-format",
+    "cut-plan",
+    "advisor",
+    "solve",
+    "print",
+    "export",
+    "print-labels",
+    "clear",
+];
 
 mod imp {
     use super::*;
@@ -31,6 +76,14 @@ mod imp {
         #[template_child]
         pub(super) unsaved_indicator: TemplateChild<gtk::Label>,
         #[template_child]
+        pub(super) view_switcher: TemplateChild<adw::ViewSwitcher>,
+        #[template_child]
+        pub(super) recent_menu: TemplateChild<gio::Menu>,
+        #[template_child]
+        pub(super) page_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) start_page: TemplateChild<StartPage>,
+        #[template_child]
         pub(super) stack: TemplateChild<adw::ViewStack>,
         #[template_child]
         pub(super) supplies_pane: TemplateChild<EntryPane>,
@@ -47,6 +100,25 @@ mod imp {
         #[property(get, set)]
         pub(super) project_filepath: RefCell<Option<String>>,
 
+        // Assemblies that parts can belong to
+        pub(super) assemblies: RefCell<Vec<Assembly>>,
+
+        // Cut lists pinned by the user
+        pub(super) locked_patterns: RefCell<Vec<LockedPattern>>,
+
+        // Per-material settings such as depth
+        pub(super) material_settings: RefCell<Vec<MaterialSettings>>,
+
+        // Saws that materials can be assigned to (see `MaterialSettings::tool`)
+        pub(super) tool_profiles: RefCell<Vec<ToolProfile>>,
+
+        // Terms of the vendors named by supplies
+        pub(super) vendor_settings: RefCell<Vec<VendorSettings>>,
+
+        // Overrides the preferred currency and decimal separator
+        pub(super) number_format: RefCell<Option<NumberFormat>>,
+        pub(super) applied_number_format: RefCell<NumberFormat>,
+
         // App settings
         pub(super) settings: RefCell<Option<Settings>>,
     }
@@ -117,6 +189,58 @@ impl Window {
         self.imp().settings.borrow().clone().unwrap()
     }
 
+    /// Replaces the start page with the project
+    pub fn show_project(&self) {
+        self.imp().page_stack.set_visible_child_name("project");
+    }
+
+    fn assemblies_dialog(&self) {
+        let imp = self.imp();
+        let dialog = AssembliesDialog::new(imp.assemblies.borrow().clone(), &imp.parts_pane);
+        dialog.connect_closure(
+            "assemblies-updated",
+            false,
+            closure_local!(
+                #[weak(rename_to = window)]
+                self,
+                move |dialog: AssembliesDialog| {
+                    window.replace_assemblies(dialog.assemblies());
+                    window.set_unsaved_changes(true);
+                }
+            ),
+        );
+        dialog.present(Some(self));
+    }
+
+    fn cut_plan_dialog(&self) {
+        let imp = self.imp();
+        let solution = match imp.solver_pane.results() {
+            Some(Ok(solution)) => Some(solution),
+            _ => None,
+        };
+        let format = FractionFormat::from(
+            imp.solver_pane.size_format(),
+            imp.solver_pane.size_precision(),
+        );
+        let dialog = CutPlanDialog::new(imp.locked_patterns.borrow().clone(), solution, format);
+        dialog.connect_closure(
+            "cut-plan-updated",
+            false,
+            closure_local!(
+                #[weak(rename_to = window)]
+                self,
+                move |dialog: CutPlanDialog| {
+                    window
+                        .imp()
+                        .locked_patterns
+                        .replace(dialog.locked_patterns());
+                    window.set_unsaved_changes(true);
+                }
+            ),
+        );
+        dialog.present(Some(self));
+    }
+
     fn close_dialog(&self) {
         let dialog = unsaved_changes_dialog();
         dialog.connect_response(
@@ -141,16 +265,165 @@ impl Window {
         dialog.present(Some(self));
     }
 
+    fn materials_dialog(&self) {
+        let imp = self.imp();
+        let mut materials: Vec<Material> = Vec::new();
+        for entry_data in imp
+            .supplies_pane
+            .entry_data()
+            .into_iter()
+            .chain(imp.parts_pane.entry_data())
+        {
+            let material = Material {
+                name: entry_data.material,
+                dimension: Dimension::from(entry_data.dimension),
+            };
+            if !materials.contains(&material) {
+                materials.push(material);
+            }
+        }
+        materials.sort_by(|a, b| a.name.cmp(&b.name));
+        let format = FractionFormat::from(
+            imp.solver_pane.size_format(),
+            imp.solver_pane.size_precision(),
+        );
+        let dialog = MaterialsDialog::new(
+            imp.material_settings.borrow().clone(),
+            imp.tool_profiles.borrow().clone(),
+            materials,
+            format,
+            imp.solver_pane.default_unit(),
+        );
+        dialog.connect_closure(
+            "materials-updated",
+            false,
+            closure_local!(
+                #[weak(rename_to = window)]
+                self,
+                move |dialog: MaterialsDialog| {
+                    window
+                        .imp()
+                        .material_settings
+                        .replace(dialog.material_settings());
+                    window.imp().tool_profiles.replace(dialog.tool_profiles());
+                    window.set_unsaved_changes(true);
+                }
+            ),
+        );
+        dialog.present(Some(self));
+    }
+
+    fn vendors_dialog(&self) {
+        let imp = self.imp();
+        let mut vendors: Vec<String> = imp
+            .supplies_pane
+            .entry_data()
+            .into_iter()
+            .map(|entry_data| entry_data.vendor.trim().to_string())
+            .filter(|vendor| !vendor.is_empty())
+            .collect();
+        vendors.sort();
+        vendors.dedup();
+        let dialog = VendorsDialog::new(
+            imp.vendor_settings.borrow().clone(),
+            vendors,
+            imp.solver_pane.price_precision(),
+        );
+        dialog.connect_closure(
+            "vendors-updated",
+            false,
+            closure_local!(
+                #[weak(rename_to = window)]
+                self,
+                move |dialog: VendorsDialog| {
+                    window
+                        .imp()
+                        .vendor_settings
+                        .replace(dialog.vendor_settings());
+                    window.set_unsaved_changes(true);
+                }
+            ),
+        );
+        dialog.present(Some(self));
+    }
+
+    fn number_format_dialog(&self) {
+        let dialog = NumberFormatDialog::new(
+            self.imp().number_format.borrow().clone(),
+            NumberFormat::from_settings(&self.settings()),
+        );
+        dialog.connect_closure(
+            "number-format-updated",
+            false,
+            closure_local!(
+                #[weak(rename_to = window)]
+                self,
+                move |dialog: NumberFormatDialog| {
+                    window.imp().number_format.replace(dialog.number_format());
+                    window.apply_number_format();
+                    window.set_unsaved_changes(true);
+                }
+            ),
+        );
+        dialog.present(Some(self));
+    }
+
+    fn project_details_dialog(&self) {
+        let dialog = ProjectDetailsDialog::new(self.imp().solver_pane.details());
+        dialog.connect_closure(
+            "project-details-updated",
+            false,
+            closure_local!(
+                #[weak(rename_to = window)]
+                self,
+                move |dialog: ProjectDetailsDialog| {
+                    let solver_pane = &window.imp().solver_pane;
+                    solver_pane.replace_details(dialog.details());
+                    solver_pane.redraw();
+                    window.set_unsaved_changes(true);
+                }
+            ),
+        );
+        dialog.present(Some(self));
+    }
+
+    /// Makes the project's format (or the preferred one) current and redisplays everything
+    fn apply_number_format(&self) {
+        let imp = self.imp();
+        let number_format = imp
+            .number_format
+            .borrow()
+            .clone()
+            .unwrap_or_else(|| NumberFormat::from_settings(&self.settings()));
+
+        // Fields are written in the format last applied to this window, which the other windows
+        // may have replaced since
+        let previous = imp.applied_number_format.replace(number_format.clone());
+        set_number_format(number_format);
+        imp.supplies_pane.reformat_fields(&previous);
+        imp.parts_pane.reformat_fields(&previous);
+        imp.solver_pane.reformat_fields(&previous);
+        imp.supplies_pane.refresh_display();
+        imp.parts_pane.refresh_display();
+        imp.solver_pane.redraw();
+    }
+
     fn open_dialog(&self) {
         let filter_list = ListStore::new::<gtk::FileFilter>();
 
         let filter = gtk::FileFilter::new();
-        filter.set_name(Some("JSON files"));
+        filter.set_name(Some(&gettext("Projects")));
+        filter.add_suffix(PROJECT_EXTENSION);
         filter.add_suffix("json");
         filter_list.append(&filter);
 
         let filter = gtk::FileFilter::new();
-        filter.set_name(Some("All files"));
+        filter.set_name(Some(&gettext("CSV files")));
+        filter.add_suffix("csv");
+        filter_list.append(&filter);
+
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some(&gettext("All files")));
         filter.add_pattern("*");
         filter_list.append(&filter);
 
@@ -165,13 +438,65 @@ impl Window {
                     if let Ok(file) = a
                         && let Some(file_path) = file.path()
                     {
-                        window.open_project(&file_path);
+                        window.open_file(&file_path);
                     }
                 }
             ),
         );
     }
 
+    /// Opens a project, or imports the supplies or parts in a CSV file (see `import_csv`)
+    pub fn open_file(&self, file_path: &PathBuf) {
+        let is_csv = file_path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+        if is_csv {
+            self.import_file(file_path);
+        } else {
+            self.open_project(file_path);
+        }
+    }
+
+    /// Imports dropped CSV files here, and opens dropped projects here only if the window is empty
+    fn open_dropped_file(&self, file_path: &PathBuf) {
+        let is_empty = self.project_filepath().is_none() && !self.unsaved_changes();
+        let is_csv = file_path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+        if is_csv || is_empty {
+            self.open_file(file_path);
+        } else if let Some(app) = self.application().and_downcast::<adw::Application>() {
+            let window = Self::new(&app, false);
+            window.open_file(file_path);
+            window.present();
+        }
+    }
+
+    fn import_file(&self, file_path: &PathBuf) {
+        let Ok(text) = std::fs::read_to_string(file_path) else {
+            open_failed_dialog(file_path).present(Some(self));
+            return;
+        };
+        let import = match import_csv(&text, self.settings().uint("default-unit")) {
+            Ok(import) => import,
+            Err(message) => {
+                import_failed_dialog(file_path, &message).present(Some(self));
+                return;
+            }
+        };
+        let imp = self.imp();
+        let (pane, page) = if import.supplies {
+            (&imp.supplies_pane, "supplies")
+        } else {
+            (&imp.parts_pane, "parts")
+        };
+        for entry_data in import.entries {
+            pane.append_entry_data(entry_data);
+        }
+        imp.stack.set_visible_child_name(page);
+        self.show_project();
+    }
+
     fn open_project(&self, file_path: &PathBuf) {
         let file = match File::open(&file_path) {
             Ok(file) => file,
@@ -180,39 +505,73 @@ impl Window {
                 return;
             }
         };
-        let (supply_entries, part_entries, results, solver_field_data) =
-            match serde_json::from_reader(file) {
-                Ok(data) => data,
-                Err(_) => {
-                    open_failed_dialog(file_path).present(Some(self));
-                    return;
-                }
-            };
+        let project: ProjectData = match serde_json::from_reader(file) {
+            Ok(data) => data,
+            Err(_) => {
+                open_failed_dialog(file_path).present(Some(self));
+                return;
+            }
+        };
+
+        // Entries are parsed without further checks once they are in a pane
+        let mut entries = project.supply_entries.iter().chain(&project.part_entries);
+        if !entries.all(|e| e.is_valid()) {
+            open_failed_dialog(file_path).present(Some(self));
+            return;
+        }
         let imp = self.imp();
-        imp.supplies_pane.replace_entry_data(supply_entries);
-        imp.parts_pane.replace_entry_data(part_entries);
-        imp.solver_pane.replace_results(unflatten_results(results));
-        imp.solver_pane.replace_field_data(solver_field_data);
+        imp.number_format.replace(project.number_format);
+        self.apply_number_format();
+        imp.supplies_pane.replace_entry_data(project.supply_entries);
+        imp.parts_pane.replace_entry_data(project.part_entries);
+        imp.solver_pane
+            .replace_results(unflatten_results(project.results));
+        imp.solver_pane
+            .replace_field_data(project.solver_field_data);
+        imp.solver_pane
+            .replace_solver_parameters(project.solver_parameters);
+        imp.solver_pane.replace_details(project.details);
+        self.replace_assemblies(project.assemblies);
+        imp.locked_patterns.replace(project.locked_patterns);
+        imp.material_settings.replace(project.material_settings);
+        imp.tool_profiles.replace(project.tool_profiles);
+        imp.vendor_settings.replace(project.vendor_settings);
+        self.update_materials();
 
         // TODO: When might to_str() fail?
         self.set_project_filepath(file_path.to_str().unwrap());
         self.update_last_project();
+        add_recent_project(&self.settings(), file_path);
         self.set_unsaved_changes(false);
         imp.solver_pane.redraw();
+        self.show_project();
+    }
+
+    fn replace_assemblies(&self, assemblies: Vec<Assembly>) {
+        let names = assemblies.iter().map(|a| a.name.clone()).collect();
+        self.imp().parts_pane.update_assemblies(names);
+        self.imp().assemblies.replace(assemblies);
     }
 
     // https://gtk-rs.org/gtk4-rs/git/book/main_event_loop.html#channels
     fn run_solver(&self) {
+        let imp = self.imp();
+        let problem = match generate_problem(&self.project_data(), imp.solver_pane.blade_width())
+            .and_then(|problem| check_capabilities(problem, imp.solver_pane.solver_info()))
+        {
+            Ok(problem) => problem,
+            Err(message) => {
+                imp.solver_pane.replace_results(Some(Err(message)));
+                self.set_unsaved_changes(true);
+                imp.solver_pane.redraw();
+                return;
+            }
+        };
+
         let overlay = SolverOverlay::new();
         overlay.present(Some(self));
 
-        let imp = self.imp();
         let solver = imp.solver_pane.create_solver();
-        let problem = generate_problem(
-            imp.supplies_pane.entry_data(),
-            imp.parts_pane.entry_data(),
-            imp.solver_pane.blade_width(),
-        );
         let (sender, receiver) = async_channel::bounded(1);
 
         // TODO: Pressing "Cancel" will not stop this thread
@@ -244,16 +603,157 @@ impl Window {
                             window.set_unsaved_changes(true);
                             imp.solver_pane.redraw();
                         }
+                        Message::Advice(_) => (),
+                    }
+                }
+                overlay.force_close();
+            }
+        ));
+    }
+
+    /// Solves with mixes of the candidate supplies, then lists them to choose from
+    fn run_advisor(&self) {
+        let imp = self.imp();
+        let problem = match generate_problem(&self.project_data(), imp.solver_pane.blade_width())
+            .and_then(|problem| check_capabilities(problem, imp.solver_pane.solver_info()))
+        {
+            Ok(problem) => problem,
+            Err(message) => {
+                imp.solver_pane.replace_results(Some(Err(message)));
+                imp.solver_pane.redraw();
+                return;
+            }
+        };
+
+        let overlay = SolverOverlay::new();
+        overlay.present(Some(self));
+
+        let solver = imp.solver_pane.create_solver();
+        let (sender, receiver) = async_channel::bounded(1);
+
+        // TODO: Pressing "Cancel" will not stop this thread
+        gio::spawn_blocking(move || {
+            advise_stock(solver.as_ref(), problem, Objective::Cost, &Some(sender));
+        });
+
+        glib::spawn_future_local(clone!(
+            #[weak]
+            overlay,
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                let mut advice = None;
+                while let Ok(message) = receiver.recv().await {
+                    match message {
+                        Message::Progress(progress) => {
+                            overlay.update_progress(progress);
+                        }
+                        Message::SubProgress(sub_progress) => {
+                            overlay.update_sub_progress(sub_progress);
+                        }
+                        Message::Results(_) => (),
+                        Message::Advice(stock_advice) => {
+                            advice = Some(stock_advice);
+                        }
                     }
                 }
                 overlay.force_close();
+                if let Some(advice) = advice {
+                    window.advisor_dialog(advice);
+                }
             }
         ));
     }
 
+    fn advisor_dialog(&self, advice: Vec<StockAdvice>) {
+        let imp = self.imp();
+        let format = FractionFormat::from(
+            imp.solver_pane.size_format(),
+            imp.solver_pane.size_precision(),
+        );
+        let dialog = AdvisorDialog::new(advice, format, imp.solver_pane.price_precision());
+        dialog.connect_closure(
+            "mix-chosen",
+            false,
+            closure_local!(
+                #[weak(rename_to = window)]
+                self,
+                move |dialog: AdvisorDialog| {
+                    if let Some((material, keep)) = dialog.chosen_mix() {
+                        window.keep_supplies(&material, &keep);
+                    }
+                }
+            ),
+        );
+        dialog.present(Some(self));
+    }
+
+    /// Removes the supplies of a material other than those at the given indices
+    ///
+    /// Indices count only the supplies of the material, in entry order (as in its `SubProblem`).
+    fn keep_supplies(&self, material: &Material, keep: &[usize]) {
+        let imp = self.imp();
+        let mut i = 0;
+        let supply_entries: Vec<EntryData> = imp
+            .supplies_pane
+            .entry_data()
+            .into_iter()
+            .filter(|entry_data| {
+                if entry_data.material != material.name
+                    || Dimension::from(entry_data.dimension) != material.dimension
+                {
+                    return true;
+                }
+                i += 1;
+                keep.contains(&(i - 1))
+            })
+            .collect();
+        imp.supplies_pane.replace_entry_data(supply_entries);
+        self.update_materials();
+        self.set_unsaved_changes(true);
+    }
+
+    fn export_dialog(&self) {
+        let filter_list = ListStore::new::<gtk::FileFilter>();
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some(&gettext("PDF files")));
+        filter.add_suffix("pdf");
+        filter_list.append(&filter);
+
+        // Name the export after the project
+        let name = self
+            .project_filepath()
+            .and_then(|f| {
+                PathBuf::from(f)
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+            })
+            .unwrap_or_else(|| gettext("Untitled"));
+        let file_chooser = gtk::FileDialog::builder()
+            .initial_name(format!("{}.pdf", name))
+            .filters(&filter_list)
+            .build();
+        file_chooser.save(
+            Some(self),
+            None::<&gio::Cancellable>,
+            clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |a| {
+                    if let Ok(file) = a
+                        && let Some(file_path) = file.path()
+                        && window.imp().solver_pane.export_results(&file_path).is_err()
+                    {
+                        save_failed_dialog(&file_path).present(Some(&window));
+                    }
+                }
+            ),
+        );
+    }
+
     fn save_dialog(&self, close_on_success: bool) {
         let file_chooser = gtk::FileDialog::builder()
-            .initial_name("Project.json")
+            .initial_name(format!("Project.{}", PROJECT_EXTENSION))
             .build();
         file_chooser.save(
             Some(self),
@@ -272,6 +772,26 @@ impl Window {
         );
     }
 
+    /// Everything that would be saved in the project file
+    fn project_data(&self) -> ProjectData {
+        // TODO: These accessor methods return cloned data (wasteful)
+        let imp = self.imp();
+        ProjectData {
+            supply_entries: imp.supplies_pane.entry_data(),
+            part_entries: imp.parts_pane.entry_data(),
+            results: flatten_results(imp.solver_pane.results()),
+            solver_field_data: imp.solver_pane.field_data(),
+            assemblies: imp.assemblies.borrow().clone(),
+            locked_patterns: imp.locked_patterns.borrow().clone(),
+            solver_parameters: imp.solver_pane.solver_parameters(),
+            material_settings: imp.material_settings.borrow().clone(),
+            tool_profiles: imp.tool_profiles.borrow().clone(),
+            vendor_settings: imp.vendor_settings.borrow().clone(),
+            number_format: imp.number_format.borrow().clone(),
+            details: imp.solver_pane.details(),
+        }
+    }
+
     fn save_project(&self, file_path: &PathBuf, close_on_success: bool) {
         let file = match File::create(&file_path) {
             Ok(file) => file,
@@ -281,15 +801,7 @@ impl Window {
             }
         };
 
-        // TODO: These accessor methods return cloned data (wasteful)
-        let imp = self.imp();
-        let state = (
-            imp.supplies_pane.entry_data(),
-            imp.parts_pane.entry_data(),
-            flatten_results(imp.solver_pane.results()),
-            imp.solver_pane.field_data(),
-        );
-        if serde_json::to_writer_pretty(file, &state).is_err() {
+        if serde_json::to_writer_pretty(file, &self.project_data()).is_err() {
             save_failed_dialog(file_path).present(Some(self));
             return;
         }
@@ -297,6 +809,7 @@ impl Window {
         // TODO: When might to_str() fail?
         self.set_project_filepath(file_path.to_str().unwrap());
         self.update_last_project();
+        add_recent_project(&self.settings(), file_path);
         self.set_unsaved_changes(false);
         if close_on_success {
             self.close();
@@ -309,6 +822,34 @@ impl Window {
                 window.open_dialog();
             })
             .build();
+        let start_project_action = gio::ActionEntry::builder("start-project")
+            .activate(|window: &Self, _, _| {
+                window.show_project();
+            })
+            .build();
+        let open_recent_action = gio::ActionEntry::builder("open-recent")
+            .parameter_type(Some(&String::static_variant_type()))
+            .activate(|window: &Self, _, parameter| {
+                if let Some(file_path) = parameter.and_then(|p| p.get::<String>()) {
+                    window.open_file(&PathBuf::from(file_path));
+                    // Forget the project if it has gone missing since being listed
+                    window.update_recent_projects();
+                }
+            })
+            .build();
+        let pin_recent_action = gio::ActionEntry::builder("pin-recent")
+            .parameter_type(Some(&String::static_variant_type()))
+            .activate(|window: &Self, _, parameter| {
+                if let Some(file_path) = parameter.and_then(|p| p.get::<String>()) {
+                    toggle_pinned(&window.settings(), &file_path);
+                }
+            })
+            .build();
+        let clear_recent_action = gio::ActionEntry::builder("clear-recent")
+            .activate(|window: &Self, _, _| {
+                clear_recent_projects(&window.settings());
+            })
+            .build();
         let save_action = gio::ActionEntry::builder("save")
             .activate(|window: &Self, _, _| match window.project_filepath() {
                 Some(file_path) => window.save_project(&PathBuf::from(file_path), false),
@@ -325,6 +866,47 @@ impl Window {
                 PreferencesDialog::new(&window.settings()).present(Some(window));
             })
             .build();
+        let library_action = gio::ActionEntry::builder("library")
+            .activate(|window: &Self, _, _| {
+                LibraryDialog::new(&window.settings(), &window.imp().supplies_pane)
+                    .present(Some(window));
+            })
+            .build();
+        let assemblies_action = gio::ActionEntry::builder("assemblies")
+            .activate(|window: &Self, _, _| {
+                window.assemblies_dialog();
+            })
+            .build();
+        let materials_action = gio::ActionEntry::builder("materials")
+            .activate(|window: &Self, _, _| {
+                window.materials_dialog();
+            })
+            .build();
+        let vendors_action = gio::ActionEntry::builder("vendors")
+            .activate(|window: &Self, _, _| {
+                window.vendors_dialog();
+            })
+            .build();
+        let project_details_action = gio::ActionEntry::builder("project-details")
+            .activate(|window: &Self, _, _| {
+                window.project_details_dialog();
+            })
+            .build();
+        let number_format_action = gio::ActionEntry::builder("number-format")
+            .activate(|window: &Self, _, _| {
+                window.number_format_dialog();
+            })
+            .build();
+        let advisor_action = gio::ActionEntry::builder("advisor")
+            .activate(|window: &Self, _, _| {
+                window.run_advisor();
+            })
+            .build();
+        let cut_plan_action = gio::ActionEntry::builder("cut-plan")
+            .activate(|window: &Self, _, _| {
+                window.cut_plan_dialog();
+            })
+            .build();
         let about_action = gio::ActionEntry::builder("about")
             .activate(|window: &Self, _, _| {
                 about_dialog().present(Some(window));
@@ -345,6 +927,16 @@ impl Window {
                 window.imp().solver_pane.print_results();
             })
             .build();
+        let export_action = gio::ActionEntry::builder("export")
+            .activate(|window: &Self, _, _| {
+                window.export_dialog();
+            })
+            .build();
+        let print_labels_action = gio::ActionEntry::builder("print-labels")
+            .activate(|window: &Self, _, _| {
+                window.imp().solver_pane.print_labels();
+            })
+            .build();
         let clear_action = gio::ActionEntry::builder("clear")
             .activate(|window: &Self, _, _| {
                 window.imp().solver_pane.clear_results();
@@ -352,13 +944,27 @@ impl Window {
             .build();
         self.add_action_entries([
             open_action,
+            start_project_action,
+            open_recent_action,
+            pin_recent_action,
+            clear_recent_action,
             save_action,
             save_as_action,
             preferences_action,
+            library_action,
+            assemblies_action,
+            materials_action,
+            vendors_action,
+            project_details_action,
+            number_format_action,
+            cut_plan_action,
+            advisor_action,
             about_action,
             close_action,
             solve_action,
             print_action,
+            export_action,
+            print_labels_action,
             clear_action,
         ]);
     }
@@ -372,8 +978,13 @@ impl Window {
         self.bind_property("project-filepath", &imp.filename_label.get(), "label")
             .transform_to(|_, p: Option<&str>| match p {
                 // Unwrap is safe because split yields >= 1 substrings
-                Some(f) => Some(f.split(std::path::MAIN_SEPARATOR).last().unwrap()),
-                None => Some("Untitled"),
+                Some(f) => Some(
+                    f.split(std::path::MAIN_SEPARATOR)
+                        .last()
+                        .unwrap()
+                        .to_string(),
+                ),
+                None => Some(gettext("Untitled")),
             })
             .build();
         self.bind_property(
@@ -382,11 +993,23 @@ impl Window {
             "tooltip-text",
         )
         .build();
+        // The start page has no project to name or panes to switch between
+        for widget in [
+            imp.view_switcher.upcast_ref::<gtk::Widget>(),
+            imp.filename_label.upcast_ref(),
+        ] {
+            imp.page_stack
+                .bind_property("visible-child-name", widget, "visible")
+                .transform_to(|_, name: Option<&str>| Some(name == Some("project")))
+                .sync_create()
+                .build();
+        }
         for pane in [&imp.parts_pane.get(), &imp.supplies_pane.get()] {
             settings.bind("size-format", pane, "size-format").build();
             settings
                 .bind("size-precision", pane, "size-precision")
                 .build();
+            settings.bind("dual-units", pane, "dual-units").build();
             settings
                 .bind("price-precision", pane, "price-precision")
                 .build();
@@ -406,10 +1029,17 @@ impl Window {
                     self,
                     move |_: EntryPane| {
                         window.set_unsaved_changes(true);
+                        window.update_materials();
                     }
                 ),
             );
         }
+        settings
+            .bind("supply-unit", &imp.supplies_pane.get(), "display-unit")
+            .build();
+        settings
+            .bind("part-unit", &imp.parts_pane.get(), "display-unit")
+            .build();
         let pane = &imp.solver_pane.get();
         settings.bind("match-scale", pane, "match-scale").build();
         settings.bind("display-font", pane, "display-font").build();
@@ -418,10 +1048,15 @@ impl Window {
         settings
             .bind("size-precision", pane, "size-precision")
             .build();
+        settings.bind("supply-unit", pane, "supply-unit").build();
+        settings.bind("part-unit", pane, "part-unit").build();
+        settings.bind("dual-units", pane, "dual-units").build();
         settings
             .bind("price-precision", pane, "price-precision")
             .build();
         settings.bind("default-unit", pane, "default-unit").build();
+        settings.bind("label-layout", pane, "label-layout").build();
+        settings.bind("label-qr", pane, "label-qr").build();
         pane.connect_closure(
             "fields-updated",
             false,
@@ -445,6 +1080,73 @@ impl Window {
                 glib::Propagation::Proceed
             }
         });
+
+        // Projects and CSV files can be dropped onto the window
+        let drop_target = gtk::DropTarget::new(gdk::FileList::static_type(), gdk::DragAction::COPY);
+        drop_target.connect_drop(clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[upgrade_or]
+            false,
+            move |_, value, _, _| {
+                let Ok(file_list) = value.get::<gdk::FileList>() else {
+                    return false;
+                };
+                for file_path in file_list.files().iter().filter_map(|f| f.path()) {
+                    window.open_dropped_file(&file_path);
+                }
+                true
+            }
+        ));
+        self.add_controller(drop_target);
+
+        // Each window may have its own format, so the active one decides how numbers appear
+        self.connect_is_active_notify(|window| {
+            if window.is_active() {
+                window.apply_number_format();
+            }
+        });
+        for key in ["currency-symbol", "currency-after", "decimal-separator"] {
+            self.settings().connect_changed(
+                Some(key),
+                clone!(
+                    #[weak(rename_to = window)]
+                    self,
+                    move |_, _| {
+                        if window.is_active() && window.imp().number_format.borrow().is_none() {
+                            window.apply_number_format();
+                        }
+                    }
+                ),
+            );
+        }
+        self.apply_number_format();
+
+        // Other windows may open, save or pin projects too
+        for key in ["recent-projects", "pinned-projects"] {
+            self.settings().connect_changed(
+                Some(key),
+                clone!(
+                    #[weak(rename_to = window)]
+                    self,
+                    move |_, _| {
+                        window.update_recent_projects();
+                    }
+                ),
+            );
+        }
+        self.update_recent_projects();
+
+        self.imp()
+            .page_stack
+            .connect_visible_child_name_notify(clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |_| {
+                    window.update_project_actions();
+                }
+            ));
+        self.update_project_actions();
     }
 
     fn setup_settings(&self) {
@@ -452,6 +1154,65 @@ impl Window {
         self.imp().settings.replace(settings);
     }
 
+    /// Shares the materials used in the project with both entry panes
+    fn update_materials(&self) {
+        let imp = self.imp();
+        let supply_entries = imp.supplies_pane.entry_data();
+        let part_entries = imp.parts_pane.entry_data();
+
+        let mut known: Vec<EntryData> = Vec::new();
+        for entry_data in supply_entries.iter().chain(part_entries.iter()) {
+            if !known
+                .iter()
+                .any(|k| k.material == entry_data.material && k.dimension == entry_data.dimension)
+            {
+                known.push(entry_data.clone());
+            }
+        }
+        known.sort_by(|a, b| a.material.cmp(&b.material));
+
+        let supplied: HashSet<_> = supply_entries
+            .iter()
+            .map(|e| (e.material.clone(), e.dimension))
+            .collect();
+
+        for pane in [&imp.supplies_pane.get(), &imp.parts_pane.get()] {
+            pane.update_materials(known.clone(), supplied.clone());
+        }
+    }
+
+    /// Lists the recent projects on the start page and in the primary menu
+    fn update_recent_projects(&self) {
+        let imp = self.imp();
+        let projects = recent_projects(&self.settings());
+        imp.start_page.update_recent_projects(&projects);
+        imp.recent_menu.remove_all();
+        for project in &projects {
+            // Underscores in the name would otherwise be taken as mnemonics
+            let item = gio::MenuItem::new(Some(&project.name().replace('_', "__")), None);
+            item.set_action_and_target_value(
+                Some("win.open-recent"),
+                Some(&project.path.display().to_string().to_variant()),
+            );
+            imp.recent_menu.append_item(&item);
+        }
+        if let Some(action) = self
+            .lookup_action("clear-recent")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_enabled(projects.iter().any(|p| !p.pinned));
+        }
+    }
+
+    fn update_project_actions(&self) {
+        let enabled = self.imp().page_stack.visible_child_name().as_deref() == Some("project");
+        for name in PROJECT_ACTIONS {
+            if let Some(action) = self.lookup_action(name).and_downcast::<gio::SimpleAction>() {
+                action.set_enabled(enabled);
+            }
+        }
+    }
+
     fn update_last_project(&self) {
         self.settings()
             .set("last-project", self.project_filepath().unwrap_or_default())
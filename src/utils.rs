@@ -1,7 +1,9 @@
 use fraction::{Decimal, Zero};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
 
-use crate::modeling::{Material, Solution};
+use crate::modeling::{Material, Solution, Vendor};
+use crate::size::normalize_decimal;
 
 /// Panics if the keys in `supplies` and `solution` don't match
 pub fn compute_supply_consumption(solution: &Solution) -> HashMap<Material, Vec<usize>> {
@@ -16,14 +18,262 @@ pub fn compute_supply_consumption(solution: &Solution) -> HashMap<Material, Vec<
     consumption
 }
 
+/// Counts how many of each part the solution makes (which can exceed the quantity of fill parts)
+pub fn compute_part_production(solution: &Solution) -> HashMap<Material, Vec<usize>> {
+    let mut production = HashMap::new();
+    for (material, sub_solution) in solution {
+        let mut sub_production = vec![0; sub_solution.parts.len()];
+        for cut_list in sub_solution.cut_lists.iter() {
+            for part_index in cut_list.part_indices.iter() {
+                sub_production[*part_index] += cut_list.quantity;
+            }
+        }
+        production.insert(material.clone(), sub_production);
+    }
+    production
+}
+
+/// What is owed to one vendor for the supplies bought from it
+#[derive(Debug, Clone)]
+pub struct VendorTotal {
+    /// Empty for supplies without a vendor
+    pub vendor: String,
+
+    pub subtotal: Decimal,
+    pub tax: Decimal,
+    pub fees: Decimal,
+}
+
+impl VendorTotal {
+    pub fn total(&self) -> Decimal {
+        self.subtotal + self.tax + self.fees
+    }
+}
+
+/// Totals for each vendor that supplies are bought from, sorted by name
+///
+/// Each supply is bought at its lowest cost for the number used (see `Supply::cost`). Free
+/// (on-hand) supplies are left out. Supplies naming an unknown vendor are untaxed and free of fees,
+/// like those without a vendor.
+pub fn compute_vendor_totals(solution: &Solution) -> Vec<VendorTotal> {
+    let mut subtotals: BTreeMap<String, Decimal> = BTreeMap::new();
+    let mut vendors: HashMap<String, Vendor> = HashMap::new();
+    let consumption = compute_supply_consumption(solution);
+    for (material, sub_solution) in solution {
+        for vendor in sub_solution.vendors.iter() {
+            vendors.insert(vendor.name.clone(), vendor.clone());
+        }
+        for (supply, count) in sub_solution
+            .supplies
+            .iter()
+            .zip(consumption[material].iter())
+        {
+            let cost = supply.cost(*count);
+            if cost > Decimal::zero() {
+                *subtotals.entry(supply.vendor.clone()).or_default() += cost;
+            }
+        }
+    }
+    subtotals
+        .into_iter()
+        .map(|(name, subtotal)| {
+            let vendor = vendors.get(&name).cloned().unwrap_or_default();
+            VendorTotal {
+                vendor: name,
+                subtotal,
+                tax: subtotal * vendor.tax_rate,
+                fees: vendor.fees(subtotal),
+            }
+        })
+        .collect()
+}
+
+/// Includes sales tax and vendor fees
+///
 /// Panics if the keys in `supplies` and `solution` don't match
 pub fn compute_total_price(solution: &Solution) -> Decimal {
-    let mut total_price = Decimal::zero();
-    for sub_solution in solution.values() {
-        for cut_list in sub_solution.cut_lists.iter() {
-            let unit_price = sub_solution.supplies[cut_list.supply_index].price;
-            total_price += unit_price * cut_list.quantity;
+    compute_vendor_totals(solution)
+        .iter()
+        .map(|t| t.total())
+        .fold(Decimal::zero(), |a, b| a + b)
+}
+
+/// Returns `None` if any material has no lower bound
+pub fn compute_total_lower_bound(solution: &Solution) -> Option<Decimal> {
+    solution.values().map(|s| s.lower_bound).sum()
+}
+
+/// Cost of the supplies bought, before sales tax and vendor fees
+pub fn compute_supply_subtotal(solution: &Solution) -> Decimal {
+    compute_vendor_totals(solution)
+        .iter()
+        .map(|t| t.subtotal)
+        .fold(Decimal::zero(), |a, b| a + b)
+}
+
+/// How far the supply subtotal is above the lower bound, as a percentage of the subtotal
+///
+/// Lower bounds leave out tax and fees, so the subtotal is compared rather than the total price.
+pub fn compute_optimality_gap(solution: &Solution) -> Option<f64> {
+    let bound: f64 = compute_total_lower_bound(solution)?.try_into().unwrap();
+    let subtotal: f64 = compute_supply_subtotal(solution).try_into().unwrap();
+    if subtotal > 0.0 {
+        Some(((subtotal - bound) / subtotal * 100.0).max(0.0))
+    } else {
+        Some(0.0)
+    }
+}
+
+/// Splits CSV text into rows of fields, handling quoted fields (with `""` for a quote)
+///
+/// The delimiter is a semicolon if the first line has semicolons but no commas (as written by
+/// spreadsheets in locales with a decimal comma), and a comma otherwise. Blank lines are skipped.
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let first_line = text.lines().next().unwrap_or_default();
+    let delimiter = if first_line.contains(';') && !first_line.contains(',') {
+        ';'
+    } else {
+        ','
+    };
+
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            c if quoted => field.push(c),
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|f| !f.trim().is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                } else {
+                    row.clear();
+                }
+            }
+            c => field.push(c),
         }
     }
-    total_price
+    row.push(field);
+    if row.iter().any(|f| !f.trim().is_empty()) {
+        rows.push(row);
+    }
+    rows
+}
+
+/// Reads an amount written with `separator` as the decimal separator and an optional currency
+/// symbol
+///
+/// `currency_symbol` may come before or after the amount (e.g., "€3,50" or "3,50 kr"). Other
+/// symbols are skipped too, so prices stay valid when the currency symbol is changed, but other
+/// letters are not (e.g., "12abc"). The other separator isn't accepted (see `normalize_decimal`).
+pub fn parse_amount(text: &str, currency_symbol: &str, separator: char) -> Option<Decimal> {
+    let text = text.trim();
+    let symbol = currency_symbol.trim();
+    let amount = if symbol.is_empty() {
+        text
+    } else {
+        text.strip_prefix(symbol)
+            .or_else(|| text.strip_suffix(symbol))
+            .unwrap_or(text)
+    };
+    let amount = amount
+        .trim()
+        .trim_matches(|c: char| !c.is_alphanumeric() && !c.is_whitespace() && !".,-+/".contains(c))
+        .trim();
+    Decimal::from_str(&normalize_decimal(amount, separator)?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Part, Problem, SubProblem, Supply};
+    use crate::size::Size;
+    use crate::solvers::Solver;
+    use crate::solvers::naive_solver::NaiveSolver;
+
+    #[test]
+    fn test_parse_csv() {
+        let text = "Name,Length\r\n\"Shelf, top\",\"36\"\"\"\n\nLeg,29\n";
+        assert_eq!(
+            parse_csv(text),
+            vec![
+                vec!["Name", "Length"],
+                vec!["Shelf, top", "36\""],
+                vec!["Leg", "29"],
+            ]
+        );
+        assert_eq!(
+            parse_csv("Name;Length\nRail;2,5"),
+            vec![vec!["Name", "Length"], vec!["Rail", "2,5"]]
+        );
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        let amount = |text| parse_amount(text, "$", '.');
+        assert_eq!(amount("3.50"), Some(Decimal::from(3.5)));
+        assert_eq!(amount("$3.50"), Some(Decimal::from(3.5)));
+        assert_eq!(amount(" 3.50 $ "), Some(Decimal::from(3.5)));
+        assert_eq!(amount("€1200"), Some(Decimal::from(1200)));
+
+        // The other separator and letters other than the currency symbol are rejected
+        assert_eq!(amount("3,50"), None);
+        assert_eq!(amount("$1,200"), None);
+        assert_eq!(amount("1,200.50"), None);
+        assert_eq!(amount("12abc"), None);
+        assert_eq!(amount("3.50 kr"), None);
+
+        let comma = |text| parse_amount(text, "€", ',');
+        assert_eq!(comma("3,50"), Some(Decimal::from(3.5)));
+        assert_eq!(comma("€12,500"), Some(Decimal::from(12.5)));
+        assert_eq!(comma(" 3,50 € "), Some(Decimal::from(3.5)));
+        assert_eq!(parse_amount("3,50 kr", "kr", ','), Some(Decimal::from(3.5)));
+        assert_eq!(comma("3.50"), None);
+        assert_eq!(comma("1.200,50"), None);
+    }
+
+    #[test]
+    fn test_optimality_gap() {
+        let material = Material::default();
+        let mut problem = Problem::new();
+        problem.insert(
+            material.clone(),
+            SubProblem {
+                supplies: vec![Supply {
+                    length: Size::from_meters(2.0),
+                    price: Decimal::from(3),
+                    max_quantity: -1,
+                    vendor: String::from("Lumber Yard"),
+                    ..Default::default()
+                }],
+                parts: vec![Part {
+                    length: Size::from_meters(2.0),
+                    quantity: 2,
+                    ..Default::default()
+                }],
+                vendors: vec![Vendor {
+                    name: String::from("Lumber Yard"),
+                    tax_rate: Decimal::from(0.1),
+                    delivery_fee: Decimal::from(10),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        );
+
+        // Two boards can't be beaten, whatever the tax and delivery fee add to the total
+        let solution = NaiveSolver::default().solve(problem, None).unwrap();
+        assert_eq!(compute_total_price(&solution), Decimal::from(16.6));
+        assert_eq!(compute_supply_subtotal(&solution), Decimal::from(6));
+        assert_eq!(compute_optimality_gap(&solution), Some(0.0));
+    }
 }
@@ -0,0 +1,16 @@
+#!/usr/bin/env bash
+
+# Extract translatable strings from the files listed in po/POTFILES
+cd po || exit 1
+xgettext --files-from=POTFILES --directory=.. --output=chop-chop.pot \
+    --from-code=UTF-8 --add-comments=Translators --package-name=chop-chop \
+    --keyword=i18n_f --keyword=ni18n_f:1,2 --keyword=gettext_noop
+
+# Add new strings to each translation (see po/LINGUAS)
+for lang in $(cat LINGUAS); do
+    if [ -f "$lang.po" ]; then
+        msgmerge --update --backup=none "$lang.po" chop-chop.pot
+    else
+        msginit --no-translator --locale="$lang" --input=chop-chop.pot --output-file="$lang.po"
+    fi
+done